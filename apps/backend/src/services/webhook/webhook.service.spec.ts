@@ -0,0 +1,148 @@
+import { Test, TestingModule } from '@nestjs/testing';
+import { getRepositoryToken } from '@nestjs/typeorm';
+import * as crypto from 'crypto';
+import axios, { AxiosResponse } from 'axios';
+import { WebhookService } from './webhook.service';
+import { Webhook } from '../../modules/webhook/webhook.entity';
+import { WebhookPayload } from '../../types/webhook/webhook.types';
+
+// RFC 8032 section 7.1, test 1
+const SEED = '9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60';
+const PUBLIC_KEY =
+  'd75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a';
+
+// DER prefix wrapping a raw 32-byte ed25519 public key as SPKI
+const ED25519_SPKI_PREFIX = Buffer.from('302a300506032b6570032100', 'hex');
+
+function publishedKey(hex: string): crypto.KeyObject {
+  return crypto.createPublicKey({
+    key: Buffer.concat([ED25519_SPKI_PREFIX, Buffer.from(hex, 'hex')]),
+    format: 'der',
+    type: 'spki',
+  });
+}
+
+describe('WebhookService', () => {
+  let service: WebhookService;
+  let post: jest.SpyInstance;
+
+  const payload: WebhookPayload = {
+    event: 'escrow.released',
+    data: { escrowId: 'escrow-123', milestone: 0 },
+    timestamp: '2026-01-01T00:00:00.000Z',
+  };
+
+  async function createService(signingKey?: string): Promise<WebhookService> {
+    if (signingKey === undefined) {
+      delete process.env.WEBHOOK_SIGNING_KEY;
+    } else {
+      process.env.WEBHOOK_SIGNING_KEY = signingKey;
+    }
+
+    const module: TestingModule = await Test.createTestingModule({
+      providers: [
+        WebhookService,
+        {
+          provide: getRepositoryToken(Webhook),
+          useValue: { find: jest.fn(), findOne: jest.fn() },
+        },
+      ],
+    }).compile();
+
+    return module.get<WebhookService>(WebhookService);
+  }
+
+  function sentHeaders(): Record<string, string> {
+    const [, , config] = post.mock.calls[0] as [
+      string,
+      unknown,
+      { headers: Record<string, string> },
+    ];
+    return config.headers;
+  }
+
+  beforeEach(() => {
+    post = jest
+      .spyOn(axios, 'post')
+      .mockResolvedValue({ status: 200 } as AxiosResponse);
+  });
+
+  afterEach(() => {
+    delete process.env.WEBHOOK_SIGNING_KEY;
+    jest.restoreAllMocks();
+  });
+
+  describe('with a notifier signing key', () => {
+    beforeEach(async () => {
+      service = await createService(SEED);
+    });
+
+    it('should publish the public half of the configured seed', () => {
+      expect(service.getNotifierPublicKey()).toBe(PUBLIC_KEY);
+    });
+
+    it('should sign payloads verifiably against the published key', () => {
+      const signature = service.signWithNotifierKey(payload);
+
+      expect(signature).not.toBeNull();
+      const verified = crypto.verify(
+        null,
+        Buffer.from(JSON.stringify(payload)),
+        publishedKey(service.getNotifierPublicKey()!),
+        Buffer.from(signature!, 'hex'),
+      );
+      expect(verified).toBe(true);
+    });
+
+    it('should not verify a tampered payload', () => {
+      const signature = service.signWithNotifierKey(payload);
+      const tampered = { ...payload, event: 'escrow.cancelled' };
+
+      const verified = crypto.verify(
+        null,
+        Buffer.from(JSON.stringify(tampered)),
+        publishedKey(PUBLIC_KEY),
+        Buffer.from(signature!, 'hex'),
+      );
+      expect(verified).toBe(false);
+    });
+
+    it('should send the notifier signature with each delivery', async () => {
+      const webhook = { url: 'https://example.com/hook', secret: 'shh' };
+
+      await service.deliverWebhook(webhook as Webhook, payload);
+
+      expect(sentHeaders()['X-Vaultix-Notifier-Signature']).toBe(
+        service.signWithNotifierKey(payload),
+      );
+    });
+  });
+
+  describe('without a notifier signing key', () => {
+    beforeEach(async () => {
+      service = await createService();
+    });
+
+    it('should neither sign nor publish a key', () => {
+      expect(service.signWithNotifierKey(payload)).toBeNull();
+      expect(service.getNotifierPublicKey()).toBeNull();
+    });
+
+    it('should omit the notifier signature header', async () => {
+      const webhook = { url: 'https://example.com/hook', secret: 'shh' };
+
+      await service.deliverWebhook(webhook as Webhook, payload);
+
+      expect(sentHeaders()).not.toHaveProperty('X-Vaultix-Notifier-Signature');
+      expect(sentHeaders()['X-Vaultix-Signature']).toBe(
+        service.signPayload('shh', payload),
+      );
+    });
+  });
+
+  it('should reject a signing key that is not a 32-byte seed', async () => {
+    await expect(createService('abcd')).rejects.toThrow(
+      'WEBHOOK_SIGNING_KEY must be a 32-byte hex ed25519 seed',
+    );
+  });
+});
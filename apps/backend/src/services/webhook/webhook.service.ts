@@ -17,6 +17,9 @@ import axios from 'axios';
 @Injectable()
 export class WebhookService {
   private readonly logger = new Logger(WebhookService.name);
+  private readonly notifierKey = loadNotifierKey(
+    process.env.WEBHOOK_SIGNING_KEY,
+  );
 
   constructor(
     @InjectRepository(Webhook)
@@ -78,12 +81,17 @@ export class WebhookService {
     const maxAttempts = 5;
     const backoff = Math.pow(2, attempt) * 1000;
     const signature = this.signPayload(webhook.secret, payload);
+    const headers: Record<string, string> = {
+      'X-Vaultix-Signature': signature,
+      'Content-Type': 'application/json',
+    };
+    const notifierSignature = this.signWithNotifierKey(payload);
+    if (notifierSignature) {
+      headers['X-Vaultix-Notifier-Signature'] = notifierSignature;
+    }
     try {
       await axios.post(webhook.url, payload, {
-        headers: {
-          'X-Vaultix-Signature': signature,
-          'Content-Type': 'application/json',
-        },
+        headers,
         timeout: 5000,
       });
       this.logger.log(`Webhook delivered to ${webhook.url}`);
@@ -114,6 +122,27 @@ export class WebhookService {
     return hmac.digest('hex');
   }
 
+  /**
+   * Signs the payload with the notifier's ed25519 key, whose public half the
+   * escrow contract publishes through `get_notifier_key`. Returns the
+   * signature as hex, or null when no signing key is configured.
+   */
+  signWithNotifierKey(payload: WebhookPayload): string | null {
+    if (!this.notifierKey) return null;
+    return crypto
+      .sign(null, Buffer.from(JSON.stringify(payload)), this.notifierKey)
+      .toString('hex');
+  }
+
+  /** Hex public key to register on-chain with `set_notifier_key` */
+  getNotifierPublicKey(): string | null {
+    if (!this.notifierKey) return null;
+    const spki = crypto
+      .createPublicKey(this.notifierKey)
+      .export({ format: 'der', type: 'spki' });
+    return spki.subarray(-32).toString('hex');
+  }
+
   verifySignature(
     secret: string,
     payload: WebhookPayload,
@@ -126,3 +155,22 @@ export class WebhookService {
     );
   }
 }
+
+// DER prefix wrapping a raw 32-byte ed25519 seed as a PKCS#8 private key
+const ED25519_PKCS8_PREFIX = Buffer.from(
+  '302e020100300506032b657004220420',
+  'hex',
+);
+
+function loadNotifierKey(seedHex?: string): crypto.KeyObject | null {
+  if (!seedHex) return null;
+  const seed = Buffer.from(seedHex, 'hex');
+  if (seed.length !== 32) {
+    throw new Error('WEBHOOK_SIGNING_KEY must be a 32-byte hex ed25519 seed');
+  }
+  return crypto.createPrivateKey({
+    key: Buffer.concat([ED25519_PKCS8_PREFIX, seed]),
+    format: 'der',
+    type: 'pkcs8',
+  });
+}
@@ -0,0 +1,438 @@
+use crate::fee::{calculate_fee, is_valid_bps, split_burn, DEFAULT_FEE_BPS};
+use crate::{
+    validate_milestone_amounts, Error, Escrow, EscrowStatus, Milestone, MilestoneStatus, Resolution,
+};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+/// Destination of a simulated token movement out of the escrow
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Account<A> {
+    Party(A),
+    Treasury,
+    Burn,
+}
+
+/// Token movement produced by a state transition
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Transfer<A> {
+    pub to: Account<A>,
+    pub amount: i128,
+}
+
+/// In-memory escrow engine applying the same checks and transitions as the
+/// contract. Authorization is out of scope: callers are trusted.
+#[derive(Clone, Debug)]
+pub struct Engine<A> {
+    escrows: BTreeMap<u64, Escrow<A>>,
+    partial_disputes: BTreeMap<(u64, u32), i128>,
+    fee_bps: i128,
+    burn_bps_of_fee: i128,
+    paused: bool,
+}
+
+impl<A: Clone + PartialEq> Default for Engine<A> {
+    fn default() -> Self {
+        Self::new(DEFAULT_FEE_BPS)
+    }
+}
+
+impl<A: Clone + PartialEq> Engine<A> {
+    pub fn new(fee_bps: i128) -> Self {
+        Self {
+            escrows: BTreeMap::new(),
+            partial_disputes: BTreeMap::new(),
+            fee_bps,
+            burn_bps_of_fee: 0,
+            paused: false,
+        }
+    }
+
+    pub fn set_fee_bps(&mut self, fee_bps: i128) -> Result<(), Error> {
+        if !is_valid_bps(fee_bps) {
+            return Err(Error::InvalidFeeConfiguration);
+        }
+        self.fee_bps = fee_bps;
+        Ok(())
+    }
+
+    pub fn set_burn_bps(&mut self, burn_bps_of_fee: i128) -> Result<(), Error> {
+        if !is_valid_bps(burn_bps_of_fee) {
+            return Err(Error::InvalidFeeConfiguration);
+        }
+        self.burn_bps_of_fee = burn_bps_of_fee;
+        Ok(())
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn get_escrow(&self, escrow_id: u64) -> Result<&Escrow<A>, Error> {
+        self.escrows.get(&escrow_id).ok_or(Error::EscrowNotFound)
+    }
+
+    pub fn create_escrow(
+        &mut self,
+        escrow_id: u64,
+        depositor: A,
+        recipient: A,
+        amounts: &[i128],
+    ) -> Result<(), Error> {
+        self.ensure_not_paused()?;
+        if depositor == recipient {
+            return Err(Error::SelfDealing);
+        }
+        if self.escrows.contains_key(&escrow_id) {
+            return Err(Error::EscrowAlreadyExists);
+        }
+        let total_amount = validate_milestone_amounts(amounts.iter().copied())?;
+
+        let milestones = amounts
+            .iter()
+            .map(|&amount| Milestone {
+                amount,
+                status: MilestoneStatus::Pending,
+            })
+            .collect();
+        self.escrows.insert(
+            escrow_id,
+            Escrow {
+                depositor,
+                recipient,
+                total_amount,
+                total_released: 0,
+                milestones,
+                status: EscrowStatus::Created,
+                resolution: Resolution::None,
+                fee_bps: self.fee_bps,
+            },
+        );
+        Ok(())
+    }
+
+    /// Funds the escrow and returns the amount pulled from the depositor
+    pub fn deposit_funds(&mut self, escrow_id: u64) -> Result<i128, Error> {
+        self.ensure_not_paused()?;
+        let escrow = self.escrow_mut(escrow_id)?;
+        if escrow.status != EscrowStatus::Created {
+            return Err(Error::EscrowAlreadyFunded);
+        }
+        escrow.status = EscrowStatus::Active;
+        Ok(escrow.total_amount)
+    }
+
+    /// Returns the `(payout, fee)` a release of the milestone would produce now
+    pub fn quote_release(
+        &self,
+        escrow_id: u64,
+        milestone_index: u32,
+    ) -> Result<(i128, i128), Error> {
+        let escrow = self.get_escrow(escrow_id)?;
+        if escrow.status != EscrowStatus::Active {
+            return Err(Error::EscrowNotActive);
+        }
+        let (amount, _) = self.releasable(escrow, escrow_id, milestone_index)?;
+        let fee = calculate_fee(amount, escrow.fee_bps).ok_or(Error::InvalidMilestoneAmount)?;
+        Ok((amount - fee, fee))
+    }
+
+    /// Depositor-driven release: pays the recipient net of the platform fee
+    pub fn release_milestone(
+        &mut self,
+        escrow_id: u64,
+        milestone_index: u32,
+    ) -> Result<Vec<Transfer<A>>, Error> {
+        self.ensure_not_paused()?;
+        let (payout, fee) = self.quote_release(escrow_id, milestone_index)?;
+        let mut transfers = self.release(escrow_id, milestone_index, payout)?;
+        transfers.extend(self.fee_transfers(fee)?);
+        Ok(transfers)
+    }
+
+    /// Buyer confirmation: pays the recipient the full amount with no fee
+    pub fn confirm_delivery(
+        &mut self,
+        escrow_id: u64,
+        milestone_index: u32,
+    ) -> Result<Vec<Transfer<A>>, Error> {
+        self.ensure_not_paused()?;
+        let escrow = self.get_escrow(escrow_id)?;
+        if escrow.status != EscrowStatus::Active {
+            return Err(Error::EscrowNotActive);
+        }
+        let (amount, _) = self.releasable(escrow, escrow_id, milestone_index)?;
+        self.release(escrow_id, milestone_index, amount)
+    }
+
+    pub fn raise_dispute(&mut self, escrow_id: u64, caller: &A) -> Result<(), Error> {
+        self.ensure_not_paused()?;
+        let escrow = self.escrow_mut(escrow_id)?;
+        if *caller != escrow.depositor && *caller != escrow.recipient {
+            return Err(Error::UnauthorizedAccess);
+        }
+        if escrow.status == EscrowStatus::Disputed {
+            return Err(Error::AlreadyInDispute);
+        }
+        if escrow.status != EscrowStatus::Active && escrow.status != EscrowStatus::Created {
+            return Err(Error::InvalidEscrowStatus);
+        }
+        for milestone in escrow.milestones.iter_mut() {
+            if milestone.status == MilestoneStatus::Pending {
+                milestone.status = MilestoneStatus::Disputed;
+            }
+        }
+        escrow.status = EscrowStatus::Disputed;
+        escrow.resolution = Resolution::None;
+        Ok(())
+    }
+
+    pub fn resolve_dispute(
+        &mut self,
+        escrow_id: u64,
+        winner: &A,
+    ) -> Result<Vec<Transfer<A>>, Error> {
+        let escrow = self.escrow_mut(escrow_id)?;
+        if escrow.status != EscrowStatus::Disputed {
+            return Err(Error::InvalidEscrowStatus);
+        }
+        if *winner != escrow.depositor && *winner != escrow.recipient {
+            return Err(Error::InvalidWinner);
+        }
+
+        let outstanding = escrow
+            .total_amount
+            .checked_sub(escrow.total_released)
+            .ok_or(Error::InvalidMilestoneAmount)?;
+        if *winner == escrow.recipient {
+            for milestone in escrow.milestones.iter_mut() {
+                if milestone.status != MilestoneStatus::SettledOffChain {
+                    milestone.status = MilestoneStatus::Released;
+                }
+            }
+            escrow.total_released = escrow.total_amount;
+            escrow.resolution = Resolution::Recipient;
+        } else {
+            escrow.resolution = Resolution::Depositor;
+        }
+        escrow.status = EscrowStatus::Resolved;
+
+        Ok(payment(winner.clone(), outstanding))
+    }
+
+    pub fn open_partial_dispute(
+        &mut self,
+        escrow_id: u64,
+        milestone_index: u32,
+        caller: &A,
+        disputed_amount: i128,
+    ) -> Result<(), Error> {
+        self.ensure_not_paused()?;
+        let escrow = self.get_escrow(escrow_id)?;
+        if *caller != escrow.depositor && *caller != escrow.recipient {
+            return Err(Error::UnauthorizedAccess);
+        }
+        if escrow.status != EscrowStatus::Active {
+            return Err(Error::EscrowNotActive);
+        }
+        let milestone = milestone_at(escrow, milestone_index)?;
+        if milestone.status != MilestoneStatus::Pending {
+            return Err(Error::MilestoneAlreadyReleased);
+        }
+        if disputed_amount <= 0 || disputed_amount > milestone.amount {
+            return Err(Error::InvalidMilestoneAmount);
+        }
+        if self
+            .partial_disputes
+            .contains_key(&(escrow_id, milestone_index))
+        {
+            return Err(Error::AlreadyInDispute);
+        }
+        self.partial_disputes
+            .insert((escrow_id, milestone_index), disputed_amount);
+        Ok(())
+    }
+
+    pub fn resolve_partial_dispute(
+        &mut self,
+        escrow_id: u64,
+        milestone_index: u32,
+        winner: &A,
+    ) -> Result<Vec<Transfer<A>>, Error> {
+        let disputed_amount = *self
+            .partial_disputes
+            .get(&(escrow_id, milestone_index))
+            .ok_or(Error::InvalidEscrowStatus)?;
+        let escrow = self.escrow_mut(escrow_id)?;
+        if escrow.status != EscrowStatus::Active {
+            return Err(Error::EscrowNotActive);
+        }
+        if *winner != escrow.depositor && *winner != escrow.recipient {
+            return Err(Error::InvalidWinner);
+        }
+        let recipient_won = *winner == escrow.recipient;
+        let depositor = escrow.depositor.clone();
+        let recipient = escrow.recipient.clone();
+        let milestone = escrow
+            .milestones
+            .get_mut(milestone_index as usize)
+            .ok_or(Error::MilestoneNotFound)?;
+        let remainder_released = milestone.status == MilestoneStatus::Disputed;
+
+        let mut transfers = Vec::new();
+        let mut fee = 0;
+        if recipient_won {
+            if remainder_released {
+                fee = calculate_fee(disputed_amount, escrow.fee_bps)
+                    .ok_or(Error::InvalidMilestoneAmount)?;
+                transfers.extend(payment(recipient, disputed_amount - fee));
+                milestone.status = MilestoneStatus::Released;
+                escrow.total_released += disputed_amount;
+            }
+        } else {
+            transfers.extend(payment(depositor, disputed_amount));
+            milestone.amount -= disputed_amount;
+            escrow.total_amount -= disputed_amount;
+            if remainder_released || milestone.amount == 0 {
+                milestone.status = MilestoneStatus::Released;
+            }
+        }
+
+        self.partial_disputes.remove(&(escrow_id, milestone_index));
+        transfers.extend(self.fee_transfers(fee)?);
+        Ok(transfers)
+    }
+
+    pub fn cancel_escrow(&mut self, escrow_id: u64) -> Result<Vec<Transfer<A>>, Error> {
+        self.ensure_not_paused()?;
+        let escrow = self.escrow_mut(escrow_id)?;
+        if escrow.status != EscrowStatus::Active && escrow.status != EscrowStatus::Created {
+            return Err(Error::InvalidEscrowStatus);
+        }
+        if escrow.total_released > 0 {
+            return Err(Error::MilestoneAlreadyReleased);
+        }
+        let refund = if escrow.status == EscrowStatus::Active {
+            payment(escrow.depositor.clone(), escrow.total_amount)
+        } else {
+            Vec::new()
+        };
+        escrow.status = EscrowStatus::Cancelled;
+        Ok(refund)
+    }
+
+    pub fn complete_escrow(&mut self, escrow_id: u64) -> Result<(), Error> {
+        self.ensure_not_paused()?;
+        let escrow = self.escrow_mut(escrow_id)?;
+        if escrow.status != EscrowStatus::Active {
+            return Err(Error::InvalidEscrowStatus);
+        }
+        let all_closed = escrow.milestones.iter().all(|m| {
+            m.status == MilestoneStatus::Released || m.status == MilestoneStatus::SettledOffChain
+        });
+        if !all_closed {
+            return Err(Error::EscrowNotActive);
+        }
+        escrow.status = EscrowStatus::Completed;
+        Ok(())
+    }
+
+    fn ensure_not_paused(&self) -> Result<(), Error> {
+        if self.paused {
+            return Err(Error::ContractPaused);
+        }
+        Ok(())
+    }
+
+    fn escrow_mut(&mut self, escrow_id: u64) -> Result<&mut Escrow<A>, Error> {
+        self.escrows
+            .get_mut(&escrow_id)
+            .ok_or(Error::EscrowNotFound)
+    }
+
+    /// Amount releasable now and the status the milestone moves to
+    fn releasable(
+        &self,
+        escrow: &Escrow<A>,
+        escrow_id: u64,
+        milestone_index: u32,
+    ) -> Result<(i128, MilestoneStatus), Error> {
+        let milestone = milestone_at(escrow, milestone_index)?;
+        if milestone.status != MilestoneStatus::Pending {
+            return Err(Error::MilestoneAlreadyReleased);
+        }
+        match self.partial_disputes.get(&(escrow_id, milestone_index)) {
+            None => Ok((milestone.amount, MilestoneStatus::Released)),
+            Some(disputed) => {
+                let remainder = milestone.amount - disputed;
+                if remainder <= 0 {
+                    return Err(Error::AlreadyInDispute);
+                }
+                Ok((remainder, MilestoneStatus::Disputed))
+            }
+        }
+    }
+
+    /// Marks the milestone released and pays `payout` to the recipient
+    fn release(
+        &mut self,
+        escrow_id: u64,
+        milestone_index: u32,
+        payout: i128,
+    ) -> Result<Vec<Transfer<A>>, Error> {
+        let escrow = self.get_escrow(escrow_id)?;
+        let (amount, next_status) = self.releasable(escrow, escrow_id, milestone_index)?;
+
+        let escrow = self.escrow_mut(escrow_id)?;
+        escrow.milestones[milestone_index as usize].status = next_status;
+        escrow.total_released = escrow
+            .total_released
+            .checked_add(amount)
+            .ok_or(Error::InvalidMilestoneAmount)?;
+        Ok(payment(escrow.recipient.clone(), payout))
+    }
+
+    fn fee_transfers(&self, fee: i128) -> Result<Vec<Transfer<A>>, Error> {
+        fee_transfers(fee, self.burn_bps_of_fee)
+    }
+}
+
+fn milestone_at<A>(escrow: &Escrow<A>, milestone_index: u32) -> Result<&Milestone, Error> {
+    escrow
+        .milestones
+        .get(milestone_index as usize)
+        .ok_or(Error::MilestoneNotFound)
+}
+
+/// Treasury and burn shares of a collected fee
+pub(crate) fn fee_transfers<A>(
+    fee: i128,
+    burn_bps_of_fee: i128,
+) -> Result<Vec<Transfer<A>>, Error> {
+    let (treasury, burn) = split_burn(fee, burn_bps_of_fee).ok_or(Error::InvalidMilestoneAmount)?;
+    let mut transfers = Vec::new();
+    if treasury > 0 {
+        transfers.push(Transfer {
+            to: Account::Treasury,
+            amount: treasury,
+        });
+    }
+    if burn > 0 {
+        transfers.push(Transfer {
+            to: Account::Burn,
+            amount: burn,
+        });
+    }
+    Ok(transfers)
+}
+
+pub(crate) fn payment<A>(to: A, amount: i128) -> Vec<Transfer<A>> {
+    let mut transfers = Vec::new();
+    if amount > 0 {
+        transfers.push(Transfer {
+            to: Account::Party(to),
+            amount,
+        });
+    }
+    transfers
+}
@@ -0,0 +1,20 @@
+//! Fee arithmetic shared by the contract and the off-chain engine.
+
+pub const DEFAULT_FEE_BPS: i128 = 50;
+pub const BPS_DENOMINATOR: i128 = 10000;
+
+/// Basis-point share of `amount`, rounded down. `None` on overflow.
+pub fn calculate_fee(amount: i128, fee_bps: i128) -> Option<i128> {
+    amount.checked_mul(fee_bps)?.checked_div(BPS_DENOMINATOR)
+}
+
+/// Whether `bps` is a valid basis-point value (0..=10000)
+pub fn is_valid_bps(bps: i128) -> bool {
+    (0..=BPS_DENOMINATOR).contains(&bps)
+}
+
+/// Splits a collected fee into `(treasury_amount, burn_amount)`
+pub fn split_burn(fee: i128, burn_bps_of_fee: i128) -> Option<(i128, i128)> {
+    let burn = calculate_fee(fee, burn_bps_of_fee)?;
+    Some((fee.checked_sub(burn)?, burn))
+}
@@ -0,0 +1,108 @@
+//! Pure-Rust model of the Vaultix escrow state machine.
+//!
+//! The types and transitions mirror the Soroban contract in `apps/onchain`
+//! without depending on a Soroban `Env`, so backends can simulate escrow
+//! outcomes (fee quotes, dispute settlements) instantly without RPC calls.
+//! The contract reuses the [`fee`] module directly.
+#![no_std]
+
+extern crate alloc;
+
+mod engine;
+pub mod fee;
+pub mod whatif;
+
+use alloc::vec::Vec;
+
+pub use engine::{Account, Engine, Transfer};
+
+/// Maximum number of milestones a single escrow may hold
+pub const MAX_MILESTONES: u32 = 20;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MilestoneStatus {
+    Pending,
+    Released,
+    Disputed,
+    SettledOffChain,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EscrowStatus {
+    Created,
+    Active,
+    Completed,
+    Cancelled,
+    Disputed,
+    Resolved,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Resolution {
+    None,
+    Depositor,
+    Recipient,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Milestone {
+    pub amount: i128,
+    pub status: MilestoneStatus,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Escrow<A> {
+    pub depositor: A,
+    pub recipient: A,
+    pub total_amount: i128,
+    pub total_released: i128,
+    pub milestones: Vec<Milestone>,
+    pub status: EscrowStatus,
+    pub resolution: Resolution,
+    /// Platform fee snapshotted at creation
+    pub fee_bps: i128,
+}
+
+/// Errors raised by the engine; discriminants match the contract's error codes
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum Error {
+    EscrowNotFound = 1,
+    EscrowAlreadyExists = 2,
+    MilestoneNotFound = 3,
+    MilestoneAlreadyReleased = 4,
+    UnauthorizedAccess = 5,
+    InvalidMilestoneAmount = 6,
+    EscrowNotActive = 9,
+    VectorTooLarge = 10,
+    ZeroAmount = 11,
+    SelfDealing = 13,
+    EscrowAlreadyFunded = 14,
+    InvalidFeeConfiguration = 17,
+    InvalidEscrowStatus = 20,
+    AlreadyInDispute = 21,
+    InvalidWinner = 22,
+    ContractPaused = 23,
+}
+
+/// Validates milestone amounts and returns their sum
+pub fn validate_milestone_amounts(
+    amounts: impl ExactSizeIterator<Item = i128>,
+) -> Result<i128, Error> {
+    if amounts.len() > MAX_MILESTONES as usize {
+        return Err(Error::VectorTooLarge);
+    }
+    let mut total: i128 = 0;
+    for amount in amounts {
+        if amount <= 0 {
+            return Err(Error::ZeroAmount);
+        }
+        total = total
+            .checked_add(amount)
+            .ok_or(Error::InvalidMilestoneAmount)?;
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod test;
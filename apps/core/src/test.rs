@@ -0,0 +1,223 @@
+use super::*;
+use alloc::vec;
+
+const DEPOSITOR: &str = "depositor";
+const RECIPIENT: &str = "recipient";
+
+fn funded_engine(amounts: &[i128]) -> Engine<&'static str> {
+    funded_engine_with_fee(amounts, fee::DEFAULT_FEE_BPS)
+}
+
+fn funded_engine_with_fee(amounts: &[i128], fee_bps: i128) -> Engine<&'static str> {
+    let mut engine = Engine::new(fee_bps);
+    engine
+        .create_escrow(1, DEPOSITOR, RECIPIENT, amounts)
+        .unwrap();
+    engine.deposit_funds(1).unwrap();
+    engine
+}
+
+#[test]
+fn test_fee_calculation_matches_contract() {
+    assert_eq!(fee::calculate_fee(10_000, 50), Some(50));
+    assert_eq!(fee::calculate_fee(199, 50), Some(0));
+    assert_eq!(fee::calculate_fee(i128::MAX, 50), None);
+    assert_eq!(fee::split_burn(600, 5000), Some((300, 300)));
+}
+
+#[test]
+fn test_release_quotes_and_transfers() {
+    let mut engine = funded_engine(&[6000, 4000]);
+
+    assert_eq!(engine.quote_release(1, 0), Ok((5970, 30)));
+    let transfers = engine.release_milestone(1, 0).unwrap();
+    assert_eq!(
+        transfers,
+        vec![
+            Transfer {
+                to: Account::Party(RECIPIENT),
+                amount: 5970
+            },
+            Transfer {
+                to: Account::Treasury,
+                amount: 30
+            },
+        ]
+    );
+
+    assert_eq!(
+        engine.release_milestone(1, 0),
+        Err(Error::MilestoneAlreadyReleased)
+    );
+    engine.confirm_delivery(1, 1).unwrap();
+    engine.complete_escrow(1).unwrap();
+    assert_eq!(
+        engine.get_escrow(1).unwrap().status,
+        EscrowStatus::Completed
+    );
+}
+
+#[test]
+fn test_creation_rules() {
+    let mut engine: Engine<&str> = Engine::default();
+    assert_eq!(
+        engine.create_escrow(1, DEPOSITOR, DEPOSITOR, &[100]),
+        Err(Error::SelfDealing)
+    );
+    assert_eq!(
+        engine.create_escrow(1, DEPOSITOR, RECIPIENT, &[100, 0]),
+        Err(Error::ZeroAmount)
+    );
+    assert_eq!(
+        engine.create_escrow(1, DEPOSITOR, RECIPIENT, &[1; 21]),
+        Err(Error::VectorTooLarge)
+    );
+
+    engine.set_paused(true);
+    assert_eq!(
+        engine.create_escrow(1, DEPOSITOR, RECIPIENT, &[100]),
+        Err(Error::ContractPaused)
+    );
+}
+
+#[test]
+fn test_dispute_resolution_to_depositor() {
+    let mut engine = funded_engine(&[6000, 4000]);
+    engine.release_milestone(1, 0).unwrap();
+    engine.raise_dispute(1, &RECIPIENT).unwrap();
+
+    assert_eq!(engine.release_milestone(1, 1), Err(Error::EscrowNotActive));
+    let transfers = engine.resolve_dispute(1, &DEPOSITOR).unwrap();
+    assert_eq!(
+        transfers,
+        vec![Transfer {
+            to: Account::Party(DEPOSITOR),
+            amount: 4000
+        }]
+    );
+    let escrow = engine.get_escrow(1).unwrap();
+    assert_eq!(escrow.status, EscrowStatus::Resolved);
+    assert_eq!(escrow.resolution, Resolution::Depositor);
+}
+
+#[test]
+fn test_partial_dispute_projection() {
+    let mut engine = funded_engine_with_fee(&[5000], 0);
+    engine.open_partial_dispute(1, 0, &DEPOSITOR, 1000).unwrap();
+
+    assert_eq!(engine.quote_release(1, 0), Ok((4000, 0)));
+    engine.release_milestone(1, 0).unwrap();
+
+    let transfers = engine.resolve_partial_dispute(1, 0, &RECIPIENT).unwrap();
+    assert_eq!(
+        transfers,
+        vec![Transfer {
+            to: Account::Party(RECIPIENT),
+            amount: 1000
+        }]
+    );
+    engine.complete_escrow(1).unwrap();
+}
+
+#[test]
+fn test_cancel_refunds_only_funded_escrows() {
+    let mut engine: Engine<&str> = Engine::default();
+    engine
+        .create_escrow(1, DEPOSITOR, RECIPIENT, &[100])
+        .unwrap();
+    assert_eq!(engine.cancel_escrow(1), Ok(vec![]));
+
+    let mut engine = funded_engine(&[100, 200]);
+    assert_eq!(
+        engine.cancel_escrow(1),
+        Ok(vec![Transfer {
+            to: Account::Party(DEPOSITOR),
+            amount: 300
+        }])
+    );
+}
+
+#[test]
+fn test_fee_snapshotted_at_creation() {
+    let mut engine = funded_engine(&[10_000]);
+    engine.set_fee_bps(1000).unwrap();
+    engine
+        .create_escrow(2, DEPOSITOR, RECIPIENT, &[10_000])
+        .unwrap();
+    engine.deposit_funds(2).unwrap();
+
+    assert_eq!(engine.get_escrow(1).unwrap().fee_bps, 50);
+    assert_eq!(engine.quote_release(1, 0), Ok((9950, 50)));
+    assert_eq!(engine.quote_release(2, 0), Ok((9000, 1000)));
+}
+
+#[test]
+fn test_what_if_ruling_against_settlement_offer() {
+    use whatif::{simulate_ruling, simulate_settlement_offer, Ruling};
+
+    let mut engine = funded_engine(&[6000, 4000]);
+    engine.release_milestone(1, 0).unwrap();
+    engine.raise_dispute(1, &DEPOSITOR).unwrap();
+    let snapshot = engine.get_escrow(1).unwrap();
+
+    let refund = simulate_ruling(snapshot, Ruling::Refund).unwrap();
+    assert_eq!(
+        refund.transfers,
+        vec![Transfer {
+            to: Account::Party(DEPOSITOR),
+            amount: 4000
+        }]
+    );
+
+    let split = simulate_ruling(
+        snapshot,
+        Ruling::Split {
+            recipient_bps: 2500,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        (split.to_recipient, split.to_depositor, split.fee),
+        (1000, 3000, 0)
+    );
+
+    let offer = simulate_settlement_offer(snapshot, 2500, 5000).unwrap();
+    assert_eq!(
+        (offer.to_recipient, offer.to_depositor, offer.fee),
+        (995, 3000, 5)
+    );
+    assert_eq!(
+        offer.transfers,
+        vec![
+            Transfer {
+                to: Account::Party(RECIPIENT),
+                amount: 995
+            },
+            Transfer {
+                to: Account::Party(DEPOSITOR),
+                amount: 3000
+            },
+            Transfer {
+                to: Account::Treasury,
+                amount: 3
+            },
+            Transfer {
+                to: Account::Burn,
+                amount: 2
+            },
+        ]
+    );
+
+    assert_eq!(
+        simulate_ruling(
+            snapshot,
+            Ruling::Split {
+                recipient_bps: 10_001
+            }
+        ),
+        Err(Error::InvalidMilestoneAmount)
+    );
+    // The engine's own resolution matches the simulated award
+    let award = simulate_ruling(snapshot, Ruling::Award).unwrap();
+    assert_eq!(engine.resolve_dispute(1, &RECIPIENT), Ok(award.transfers));
+}
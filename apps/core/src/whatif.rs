@@ -0,0 +1,96 @@
+//! What-if settlements of a disputed escrow, computed from a snapshot.
+//!
+//! Lets parties weigh a settlement offer against the arbiter rulings it
+//! would replace before going to arbitration. Nothing here mutates state.
+
+use crate::engine::{fee_transfers, payment, Transfer};
+use crate::fee::{calculate_fee, is_valid_bps, BPS_DENOMINATOR};
+use crate::{Error, Escrow, EscrowStatus};
+use alloc::vec::Vec;
+
+/// Hypothetical arbiter ruling over an escrow's outstanding funds
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Ruling {
+    /// Everything outstanding goes back to the depositor
+    Refund,
+    /// Everything outstanding goes to the recipient
+    Award,
+    /// The recipient gets `recipient_bps` of what is outstanding and the
+    /// depositor the rest
+    Split { recipient_bps: i128 },
+}
+
+/// Result of a simulated settlement
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct WhatIf<A> {
+    pub transfers: Vec<Transfer<A>>,
+    pub to_recipient: i128,
+    pub to_depositor: i128,
+    pub fee: i128,
+}
+
+/// Outcome of `ruling` on an escrow that is funded and not yet settled. As
+/// on-chain, an arbiter's award carries no platform fee.
+pub fn simulate_ruling<A: Clone>(escrow: &Escrow<A>, ruling: Ruling) -> Result<WhatIf<A>, Error> {
+    let outstanding = outstanding(escrow)?;
+    let recipient_bps = match ruling {
+        Ruling::Refund => 0,
+        Ruling::Award => BPS_DENOMINATOR,
+        Ruling::Split { recipient_bps } => recipient_bps,
+    };
+    let (to_recipient, to_depositor) = split(outstanding, recipient_bps)?;
+
+    let mut transfers = payment(escrow.recipient.clone(), to_recipient);
+    transfers.extend(payment(escrow.depositor.clone(), to_depositor));
+    Ok(WhatIf {
+        transfers,
+        to_recipient,
+        to_depositor,
+        fee: 0,
+    })
+}
+
+/// Outcome of settling out of court: the recipient's `recipient_bps` share
+/// is released like a milestone, paying the escrow's platform fee, and the
+/// rest is refunded
+pub fn simulate_settlement_offer<A: Clone>(
+    escrow: &Escrow<A>,
+    recipient_bps: i128,
+    burn_bps_of_fee: i128,
+) -> Result<WhatIf<A>, Error> {
+    let outstanding = outstanding(escrow)?;
+    let (share, to_depositor) = split(outstanding, recipient_bps)?;
+    let fee = calculate_fee(share, escrow.fee_bps).ok_or(Error::InvalidMilestoneAmount)?;
+    let to_recipient = share - fee;
+
+    let mut transfers = payment(escrow.recipient.clone(), to_recipient);
+    transfers.extend(payment(escrow.depositor.clone(), to_depositor));
+    transfers.extend(fee_transfers(fee, burn_bps_of_fee)?);
+    Ok(WhatIf {
+        transfers,
+        to_recipient,
+        to_depositor,
+        fee,
+    })
+}
+
+/// Funds still held for the escrow
+fn outstanding<A>(escrow: &Escrow<A>) -> Result<i128, Error> {
+    if escrow.status != EscrowStatus::Active && escrow.status != EscrowStatus::Disputed {
+        return Err(Error::InvalidEscrowStatus);
+    }
+    escrow
+        .total_amount
+        .checked_sub(escrow.total_released)
+        .ok_or(Error::InvalidMilestoneAmount)
+}
+
+/// Splits `amount` into the recipient's `recipient_bps` share, rounded down,
+/// and the depositor's remainder
+fn split(amount: i128, recipient_bps: i128) -> Result<(i128, i128), Error> {
+    if !is_valid_bps(recipient_bps) {
+        return Err(Error::InvalidMilestoneAmount);
+    }
+    let to_recipient = calculate_fee(amount, recipient_bps).ok_or(Error::InvalidMilestoneAmount)?;
+    Ok((to_recipient, amount - to_recipient))
+}
@@ -0,0 +1,84 @@
+use clap::{Subcommand, ValueEnum};
+use std::io::{self, BufRead, Write};
+
+/// Privileged contract calls. Fee and pause changes must be signed by the
+/// treasury, role grants by the admin.
+#[derive(Clone, Debug, PartialEq, Eq, Subcommand)]
+pub enum AdminAction {
+    /// Assign a role to an address
+    GrantRole { role: Role, address: String },
+    /// Change the platform fee, in basis points
+    SetFee { fee_bps: i64 },
+    /// Halt escrow creation, funding, claims and disputes
+    Pause,
+    /// Lift a pause
+    Unpause,
+}
+
+/// Roles the admin can assign
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Role {
+    /// Confirms off-chain settlements of milestones
+    Attestor,
+}
+
+impl AdminAction {
+    /// Contract function and `--name value` arguments the action invokes
+    pub fn invocation(&self) -> (&'static str, Vec<(&'static str, String)>) {
+        match self {
+            AdminAction::GrantRole {
+                role: Role::Attestor,
+                address,
+            } => ("set_attestor", vec![("attestor", address.clone())]),
+            AdminAction::SetFee { fee_bps } => {
+                ("update_fee", vec![("new_fee_bps", fee_bps.to_string())])
+            }
+            AdminAction::Pause => ("set_paused", vec![("paused", "true".into())]),
+            AdminAction::Unpause => ("set_paused", vec![("paused", "false".into())]),
+        }
+    }
+}
+
+/// Renders an invocation the way an operator would read it back
+pub fn describe(function: &str, args: &[(&str, String)]) -> String {
+    args.iter()
+        .fold(function.to_string(), |line, (name, value)| {
+            format!("{line} --{name} {value}")
+        })
+}
+
+/// Asks on stdin whether to go ahead, defaulting to no
+pub fn confirm(prompt: &str) -> Result<bool, String> {
+    print!("{prompt} [y/N] ");
+    io::stdout().flush().map_err(|e| e.to_string())?;
+    let mut answer = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut answer)
+        .map_err(|e| format!("failed to read confirmation: {e}"))?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_actions_map_to_contract_calls() {
+        let (function, args) = AdminAction::SetFee { fee_bps: 75 }.invocation();
+        assert_eq!(describe(function, &args), "update_fee --new_fee_bps 75");
+
+        let (function, args) = AdminAction::Unpause.invocation();
+        assert_eq!(describe(function, &args), "set_paused --paused false");
+
+        let grant = AdminAction::GrantRole {
+            role: Role::Attestor,
+            address: "GATTESTOR".into(),
+        };
+        let (function, args) = grant.invocation();
+        assert_eq!(
+            describe(function, &args),
+            "set_attestor --attestor GATTESTOR"
+        );
+    }
+}
@@ -0,0 +1,230 @@
+mod admin;
+mod manifest;
+mod rpc;
+mod terms;
+
+use admin::AdminAction;
+use clap::{Parser, Subcommand};
+use manifest::{Deployment, Lockfile, Manifest, Network};
+use rpc::{file_hash, SorobanCli};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use terms::Provenance;
+
+/// Deploys the Vaultix escrow contract and tracks deployments per network
+#[derive(Parser)]
+#[command(name = "vaultix-deploy")]
+struct Cli {
+    /// Deployment manifest
+    #[arg(long, default_value = "deploy.toml")]
+    manifest: PathBuf,
+
+    /// Lockfile recording deployed contract IDs and wasm hashes
+    #[arg(long, default_value = "deploy.lock")]
+    lockfile: PathBuf,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Install, deploy and initialize the contract on a network
+    Deploy { network: Network },
+    /// Check that the deployed wasm matches the local build
+    Verify { network: Network },
+    /// Print the recorded deployments
+    Status,
+    /// Check a local document against the terms an escrow's parties signed
+    VerifyTerms {
+        network: Network,
+        /// Escrow whose terms to check
+        #[arg(long)]
+        escrow: u64,
+        /// Agreement document as signed off-chain
+        #[arg(long)]
+        file: PathBuf,
+    },
+    /// Run a privileged call against the recorded deployment on a network
+    Admin {
+        network: Network,
+        /// Identity to sign with; defaults to the manifest's `source`
+        #[arg(long)]
+        source: Option<String>,
+        /// Simulate the call and print its result without submitting it
+        #[arg(long)]
+        dry_run: bool,
+        /// Skip the confirmation prompt
+        #[arg(long)]
+        yes: bool,
+        #[command(subcommand)]
+        action: AdminAction,
+    },
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<(), String> {
+    let mut lockfile = Lockfile::load(&cli.lockfile)?;
+
+    match cli.command {
+        Command::Deploy { network } => {
+            let manifest = Manifest::load(&cli.manifest)?;
+            let config = manifest.network(network)?;
+            let wasm = wasm_path(&cli.manifest, &manifest);
+            let soroban = SorobanCli::new(config);
+
+            let wasm_hash = soroban.install(&wasm)?;
+            let contract_id = soroban.deploy(&wasm_hash)?;
+
+            let mut init_args = vec![("treasury", config.treasury.clone())];
+            if let Some(fee_bps) = config.fee_bps {
+                init_args.push(("fee_bps", fee_bps.to_string()));
+            }
+            soroban.invoke(&contract_id, "initialize", &init_args)?;
+            if let Some(admin) = &config.admin {
+                soroban.invoke(&contract_id, "init", &[("admin", admin.clone())])?;
+            }
+
+            println!("{network}: deployed {contract_id} (wasm {wasm_hash})");
+            lockfile.record(
+                network,
+                Deployment {
+                    contract_id,
+                    wasm_hash,
+                },
+            );
+            lockfile.save(&cli.lockfile)
+        }
+        Command::Verify { network } => {
+            let manifest = Manifest::load(&cli.manifest)?;
+            let config = manifest.network(network)?;
+            let deployment = lockfile
+                .deployments
+                .get(&network)
+                .ok_or_else(|| format!("no recorded deployment for `{network}`"))?;
+
+            let local = file_hash(&wasm_path(&cli.manifest, &manifest))?;
+            let fetched = std::env::temp_dir().join(format!("vaultix-{network}.wasm"));
+            SorobanCli::new(config).fetch(&deployment.contract_id, &fetched)?;
+            let deployed = file_hash(&fetched)?;
+
+            if deployed != local {
+                return Err(format!(
+                    "{network}: deployed wasm {deployed} does not match local build {local}"
+                ));
+            }
+            if deployed != deployment.wasm_hash {
+                return Err(format!(
+                    "{network}: deployed wasm {deployed} does not match lockfile {}",
+                    deployment.wasm_hash
+                ));
+            }
+            println!("{network}: {} matches local build", deployment.contract_id);
+            Ok(())
+        }
+        Command::Status => {
+            for (network, deployment) in &lockfile.deployments {
+                println!(
+                    "{network}: {} (wasm {})",
+                    deployment.contract_id, deployment.wasm_hash
+                );
+            }
+            Ok(())
+        }
+        Command::VerifyTerms {
+            network,
+            escrow,
+            file,
+        } => {
+            let manifest = Manifest::load(&cli.manifest)?;
+            let config = manifest.network(network)?;
+            let deployment = lockfile
+                .deployments
+                .get(&network)
+                .ok_or_else(|| format!("no recorded deployment for `{network}`"))?;
+            let soroban = SorobanCli::new(config);
+            let contract_id = &deployment.contract_id;
+
+            let document_hash = file_hash(&file)?;
+            let escrow_id = ("escrow_id", escrow.to_string());
+            let matches = soroban.simulate(
+                contract_id,
+                "verify_terms",
+                &[escrow_id.clone(), ("document_hash", document_hash.clone())],
+            )?;
+            if matches != "true" {
+                return Err(format!(
+                    "{}: sha256 {document_hash} is not the accepted terms of escrow {escrow}",
+                    file.display()
+                ));
+            }
+
+            let provenance =
+                Provenance::parse(&soroban.simulate(contract_id, "get_escrow", &[escrow_id])?)?;
+            println!(
+                "{}: sha256 {document_hash} matches the terms of escrow {escrow}",
+                file.display()
+            );
+            println!(
+                "  set by depositor {} ({})",
+                provenance.depositor,
+                provenance.depositor_signers()
+            );
+            println!("  accepted by recipient {}", provenance.recipient);
+            Ok(())
+        }
+        Command::Admin {
+            network,
+            source,
+            dry_run,
+            yes,
+            action,
+        } => {
+            let manifest = Manifest::load(&cli.manifest)?;
+            let config = manifest.network(network)?;
+            let deployment = lockfile
+                .deployments
+                .get(&network)
+                .ok_or_else(|| format!("no recorded deployment for `{network}`"))?;
+            let mut soroban = SorobanCli::new(config);
+            if let Some(source) = source {
+                soroban = soroban.signed_by(source);
+            }
+
+            let (function, args) = action.invocation();
+            let call = admin::describe(function, &args);
+            let contract_id = &deployment.contract_id;
+            if dry_run {
+                let result = soroban.simulate(contract_id, function, &args)?;
+                println!("{network}: simulated {call} on {contract_id}");
+                if !result.is_empty() {
+                    println!("{result}");
+                }
+                return Ok(());
+            }
+            if !yes && !admin::confirm(&format!("{network}: {call} on {contract_id}?"))? {
+                return Err("aborted".into());
+            }
+            soroban.invoke(contract_id, function, &args)?;
+            println!("{network}: {call} on {contract_id}");
+            Ok(())
+        }
+    }
+}
+
+fn wasm_path(manifest_path: &Path, manifest: &Manifest) -> PathBuf {
+    manifest_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(&manifest.wasm)
+}
@@ -0,0 +1,158 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// Networks the escrow contract can be deployed to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    Testnet,
+    Futurenet,
+    Mainnet,
+}
+
+impl Network {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Network::Testnet => "testnet",
+            Network::Futurenet => "futurenet",
+            Network::Mainnet => "mainnet",
+        }
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Network {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "testnet" => Ok(Network::Testnet),
+            "futurenet" => Ok(Network::Futurenet),
+            "mainnet" => Ok(Network::Mainnet),
+            other => Err(format!("unknown network `{other}`")),
+        }
+    }
+}
+
+/// Per-network deployment settings, read from `deploy.toml`
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct NetworkConfig {
+    pub rpc_url: String,
+    pub network_passphrase: String,
+    /// Identity (as known to the stellar CLI) that signs deployment transactions
+    pub source: String,
+    /// Treasury address passed to `initialize`
+    pub treasury: String,
+    /// Platform fee in basis points; the contract default applies when unset
+    pub fee_bps: Option<i64>,
+    /// Dispute admin passed to `init`, if any
+    pub admin: Option<String>,
+}
+
+/// Deployment manifest describing every network the contract ships to
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct Manifest {
+    /// Path to the optimized contract wasm, relative to the manifest
+    pub wasm: String,
+    pub networks: BTreeMap<Network, NetworkConfig>,
+}
+
+impl Manifest {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let raw = fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+        toml::from_str(&raw).map_err(|e| format!("invalid manifest {}: {e}", path.display()))
+    }
+
+    pub fn network(&self, network: Network) -> Result<&NetworkConfig, String> {
+        self.networks
+            .get(&network)
+            .ok_or_else(|| format!("network `{network}` is not configured in the manifest"))
+    }
+}
+
+/// Recorded result of a deployment to one network
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Deployment {
+    pub contract_id: String,
+    pub wasm_hash: String,
+}
+
+/// Lockfile tracking the contract IDs and wasm hashes deployed per network
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Lockfile {
+    #[serde(default)]
+    pub deployments: BTreeMap<Network, Deployment>,
+}
+
+impl Lockfile {
+    /// Loads the lockfile, treating a missing file as empty
+    pub fn load(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let raw = fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+        toml::from_str(&raw).map_err(|e| format!("invalid lockfile {}: {e}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let raw = toml::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, raw).map_err(|e| format!("failed to write {}: {e}", path.display()))
+    }
+
+    pub fn record(&mut self, network: Network, deployment: Deployment) {
+        self.deployments.insert(network, deployment);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_manifest() {
+        let manifest: Manifest = toml::from_str(
+            r#"
+            wasm = "../onchain/target/wasm32-unknown-unknown/release/onchain.wasm"
+
+            [networks.testnet]
+            rpc_url = "https://soroban-testnet.stellar.org"
+            network_passphrase = "Test SDF Network ; September 2015"
+            source = "deployer"
+            treasury = "GTREASURY"
+            fee_bps = 50
+            "#,
+        )
+        .unwrap();
+
+        let testnet = manifest.network(Network::Testnet).unwrap();
+        assert_eq!(testnet.fee_bps, Some(50));
+        assert_eq!(testnet.admin, None);
+        assert!(manifest.network(Network::Mainnet).is_err());
+    }
+
+    #[test]
+    fn test_lockfile_round_trip() {
+        let mut lockfile = Lockfile::default();
+        lockfile.record(
+            Network::Futurenet,
+            Deployment {
+                contract_id: "CCONTRACT".into(),
+                wasm_hash: "ab".repeat(32),
+            },
+        );
+
+        let raw = toml::to_string_pretty(&lockfile).unwrap();
+        let parsed: Lockfile = toml::from_str(&raw).unwrap();
+        assert_eq!(parsed, lockfile);
+    }
+}
@@ -0,0 +1,121 @@
+use crate::manifest::NetworkConfig;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::process::Command;
+
+/// Thin wrapper around the `stellar` CLI, which owns key management and
+/// talks to soroban RPC on our behalf.
+pub struct SorobanCli<'a> {
+    bin: String,
+    config: &'a NetworkConfig,
+    source: String,
+}
+
+impl<'a> SorobanCli<'a> {
+    pub fn new(config: &'a NetworkConfig) -> Self {
+        let bin = std::env::var("VAULTIX_STELLAR_BIN").unwrap_or_else(|_| "stellar".into());
+        let source = config.source.clone();
+        Self {
+            bin,
+            config,
+            source,
+        }
+    }
+
+    /// Signs with `source` instead of the manifest's deployment identity
+    pub fn signed_by(mut self, source: String) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Uploads the wasm and returns its on-chain hash
+    pub fn install(&self, wasm: &Path) -> Result<String, String> {
+        let wasm = wasm.to_string_lossy();
+        self.run(&["contract", "install", "--wasm", &wasm], &[])
+    }
+
+    /// Instantiates a contract from an installed wasm hash and returns its ID
+    pub fn deploy(&self, wasm_hash: &str) -> Result<String, String> {
+        self.run(&["contract", "deploy", "--wasm-hash", wasm_hash], &[])
+    }
+
+    /// Invokes a contract function with `--name value` style arguments
+    pub fn invoke(
+        &self,
+        contract_id: &str,
+        function: &str,
+        args: &[(&str, String)],
+    ) -> Result<String, String> {
+        self.run(
+            &["contract", "invoke", "--id", contract_id],
+            &function_args(function, args),
+        )
+    }
+
+    /// Simulates an invocation without submitting it, returning what the
+    /// call would return
+    pub fn simulate(
+        &self,
+        contract_id: &str,
+        function: &str,
+        args: &[(&str, String)],
+    ) -> Result<String, String> {
+        self.run(
+            &["contract", "invoke", "--id", contract_id, "--send=no"],
+            &function_args(function, args),
+        )
+    }
+
+    /// Downloads the wasm currently backing a deployed contract
+    pub fn fetch(&self, contract_id: &str, out: &Path) -> Result<(), String> {
+        let out = out.to_string_lossy();
+        self.run(
+            &["contract", "fetch", "--id", contract_id, "--out-file", &out],
+            &[],
+        )
+        .map(|_| ())
+    }
+
+    fn run(&self, args: &[&str], fn_args: &[String]) -> Result<String, String> {
+        let mut cmd = Command::new(&self.bin);
+        cmd.args(args)
+            .args(["--source", &self.source])
+            .args(["--rpc-url", &self.config.rpc_url])
+            .args(["--network-passphrase", &self.config.network_passphrase]);
+        if !fn_args.is_empty() {
+            cmd.arg("--").args(fn_args);
+        }
+
+        let output = cmd
+            .output()
+            .map_err(|e| format!("failed to run `{}`: {e}", self.bin))?;
+        if !output.status.success() {
+            return Err(format!(
+                "`{} {}` failed: {}",
+                self.bin,
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+}
+
+/// Function name followed by its arguments as `--name value` pairs
+fn function_args(function: &str, args: &[(&str, String)]) -> Vec<String> {
+    let mut fn_args = vec![function.to_string()];
+    for (name, value) in args {
+        fn_args.push(format!("--{name}"));
+        fn_args.push(value.clone());
+    }
+    fn_args
+}
+
+/// Hex-encoded sha256 of a file. For a wasm file this is the hash soroban
+/// assigns on install; for a terms document, the `terms_hash` it was
+/// registered under.
+pub fn file_hash(path: &Path) -> Result<String, String> {
+    let bytes =
+        std::fs::read(path).map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+    Ok(hex::encode(Sha256::digest(bytes)))
+}
@@ -0,0 +1,67 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Parties behind an escrow's terms, read back from `get_escrow`. The
+/// depositor fixes the terms hash at creation and the recipient accepts it,
+/// each signing as the escrow's auth mode requires.
+#[derive(Debug, Deserialize)]
+pub struct Provenance {
+    pub depositor: String,
+    pub recipient: String,
+    auth_mode: Value,
+}
+
+impl Provenance {
+    /// Parses the JSON the CLI prints for a `get_escrow` call
+    pub fn parse(escrow_json: &str) -> Result<Self, String> {
+        serde_json::from_str(escrow_json).map_err(|e| format!("unexpected escrow output: {e}"))
+    }
+
+    /// Who signs for the depositor: `Strict`, `Delegated(operator)` or
+    /// `Multisig(co-signers)`, as the CLI renders the contract enum
+    pub fn depositor_signers(&self) -> String {
+        match &self.auth_mode {
+            Value::String(mode) => mode.clone(),
+            Value::Object(variant) => variant
+                .iter()
+                .map(|(mode, signers)| match signers {
+                    Value::String(operator) => format!("{mode} via {operator}"),
+                    Value::Array(cosigners) => {
+                        let cosigners: Vec<&str> =
+                            cosigners.iter().filter_map(Value::as_str).collect();
+                        format!("{mode} with {}", cosigners.join(", "))
+                    }
+                    other => format!("{mode} {other}"),
+                })
+                .collect(),
+            other => other.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_provenance_renders_auth_mode() {
+        let strict = Provenance::parse(
+            r#"{"depositor":"GDEP","recipient":"GREC","auth_mode":"Strict","total_amount":"1000"}"#,
+        )
+        .unwrap();
+        assert_eq!(strict.depositor, "GDEP");
+        assert_eq!(strict.depositor_signers(), "Strict");
+
+        let delegated = Provenance::parse(
+            r#"{"depositor":"GDEP","recipient":"GREC","auth_mode":{"Delegated":"GOPS"}}"#,
+        )
+        .unwrap();
+        assert_eq!(delegated.depositor_signers(), "Delegated via GOPS");
+
+        let multisig = Provenance::parse(
+            r#"{"depositor":"GDEP","recipient":"GREC","auth_mode":{"Multisig":["GA","GB"]}}"#,
+        )
+        .unwrap();
+        assert_eq!(multisig.depositor_signers(), "Multisig with GA, GB");
+    }
+}
@@ -0,0 +1,109 @@
+mod scenarios;
+mod stellar;
+
+use clap::Parser;
+use scenarios::{Deployment, Scenario, ADMIN, DEPOSITOR, FEE_BPS, RECIPIENT, TREASURY};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use stellar::{Network, StellarCli};
+
+/// Deploys the Vaultix escrow contract to a live network and drives
+/// representative escrow lifecycles against it, checking balances and
+/// events through RPC. Needs the `stellar` CLI on the PATH (or in
+/// `VAULTIX_STELLAR_BIN`) and a network with friendbot.
+#[derive(Parser)]
+#[command(name = "vaultix-e2e")]
+struct Cli {
+    /// Network to run against; `local` expects `stellar/quickstart --local`
+    #[arg(long, value_enum, default_value_t = Network::Local)]
+    network: Network,
+
+    /// Override the network's default RPC endpoint
+    #[arg(long)]
+    rpc_url: Option<String>,
+
+    /// Contract wasm to deploy
+    #[arg(
+        long,
+        default_value = "../onchain/target/wasm32-unknown-unknown/release/onchain.wasm"
+    )]
+    wasm: PathBuf,
+
+    /// Scenarios to run; all of them when omitted
+    #[arg(long = "scenario", value_enum)]
+    scenarios: Vec<Scenario>,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<(), String> {
+    let stellar = StellarCli::new(cli.network, cli.rpc_url.clone());
+    let deployment = deploy(&stellar, &cli)?;
+    println!(
+        "{}: deployed {} (token {})",
+        cli.network, deployment.contract, deployment.token
+    );
+
+    let scenarios = if cli.scenarios.is_empty() {
+        Scenario::ALL.to_vec()
+    } else {
+        cli.scenarios
+    };
+    let mut failed = 0;
+    for scenario in scenarios {
+        match deployment.run(scenario) {
+            Ok(()) => println!("ok     {scenario:?}"),
+            Err(e) => {
+                failed += 1;
+                println!("FAILED {scenario:?}: {e}");
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(format!("{failed} scenario(s) failed"));
+    }
+    Ok(())
+}
+
+/// Funds the suite's identities, deploys and initializes a fresh contract
+fn deploy<'a>(stellar: &'a StellarCli, cli: &Cli) -> Result<Deployment<'a>, String> {
+    let admin = stellar.fund_identity(ADMIN)?;
+    let treasury = stellar.fund_identity(TREASURY)?;
+    let depositor = stellar.fund_identity(DEPOSITOR)?;
+    let recipient = stellar.fund_identity(RECIPIENT)?;
+
+    let start_ledger = stellar.latest_ledger()?;
+    let contract = stellar.deploy(&cli.wasm, ADMIN)?;
+    let token = stellar.native_asset(ADMIN)?;
+
+    stellar.invoke(
+        &contract,
+        TREASURY,
+        "initialize",
+        &[
+            ("treasury", treasury.clone()),
+            ("fee_bps", FEE_BPS.to_string()),
+        ],
+    )?;
+    stellar.invoke(&contract, ADMIN, "init", &[("admin", admin)])?;
+
+    Ok(Deployment {
+        cli: stellar,
+        contract,
+        token,
+        treasury,
+        depositor,
+        recipient,
+        start_ledger,
+    })
+}
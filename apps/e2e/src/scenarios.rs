@@ -0,0 +1,296 @@
+use crate::stellar::{has_event, parse_i128, StellarCli};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const ADMIN: &str = "vaultix-e2e-admin";
+pub const TREASURY: &str = "vaultix-e2e-treasury";
+pub const DEPOSITOR: &str = "vaultix-e2e-depositor";
+pub const RECIPIENT: &str = "vaultix-e2e-recipient";
+
+/// Platform fee the suite initializes the contract with
+pub const FEE_BPS: i128 = 100;
+/// How far ahead the expiry scenario sets its deadline; long enough for the
+/// escrow to be created and funded first
+const EXPIRY_DEADLINE_SECS: u64 = 30;
+/// Ledgers an allowance stays valid for, comfortably longer than a run
+const ALLOWANCE_LEDGERS: u32 = 10_000;
+
+/// Lifecycles the suite can exercise
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Scenario {
+    /// Fund, release every milestone and complete
+    HappyPath,
+    /// Fund, dispute and have the admin award the recipient
+    Dispute,
+    /// Fund a refund guarantee and let the buyer claim it after the deadline
+    Expiry,
+}
+
+impl Scenario {
+    pub const ALL: [Scenario; 3] = [Scenario::HappyPath, Scenario::Dispute, Scenario::Expiry];
+
+    /// Escrow ID the scenario uses; every run deploys a fresh contract
+    fn escrow_id(&self) -> u64 {
+        match self {
+            Scenario::HappyPath => 1,
+            Scenario::Dispute => 2,
+            Scenario::Expiry => 3,
+        }
+    }
+}
+
+/// A freshly deployed and initialized escrow contract plus its token and
+/// parties, shared by every scenario of a run
+pub struct Deployment<'a> {
+    pub cli: &'a StellarCli,
+    pub contract: String,
+    /// Native XLM asset contract; escrow amounts are in stroops
+    pub token: String,
+    pub treasury: String,
+    pub depositor: String,
+    pub recipient: String,
+    /// Ledger the deployment started at, for event queries
+    pub start_ledger: u32,
+}
+
+impl Deployment<'_> {
+    pub fn run(&self, scenario: Scenario) -> Result<(), String> {
+        match scenario {
+            Scenario::HappyPath => self.happy_path(scenario.escrow_id()),
+            Scenario::Dispute => self.dispute(scenario.escrow_id()),
+            Scenario::Expiry => self.expiry(scenario.escrow_id()),
+        }
+    }
+
+    fn happy_path(&self, escrow_id: u64) -> Result<(), String> {
+        let milestones = [("Design", 10_000_000), ("Build", 20_000_000)];
+        let total = self.create_and_fund(escrow_id, &milestones, "Standard", far_deadline())?;
+
+        let recipient_before = self.balance(&self.recipient)?;
+        let treasury_before = self.balance(&self.treasury)?;
+        for index in 0..milestones.len() {
+            self.invoke(
+                DEPOSITOR,
+                "release_milestone",
+                &[
+                    ("escrow_id", escrow_id.to_string()),
+                    ("milestone_index", index.to_string()),
+                ],
+            )?;
+        }
+        self.invoke(
+            DEPOSITOR,
+            "complete_escrow",
+            &[("escrow_id", escrow_id.to_string())],
+        )?;
+
+        let fee = milestones
+            .iter()
+            .map(|(_, amount)| amount * FEE_BPS / 10_000)
+            .sum::<i128>();
+        expect_eq(
+            "recipient payout",
+            self.balance(&self.recipient)? - recipient_before,
+            total - fee,
+        )?;
+        expect_eq(
+            "treasury fees",
+            self.balance(&self.treasury)? - treasury_before,
+            fee,
+        )?;
+        self.expect_events(&[
+            "EscrowCreated",
+            "EscrowFunded",
+            "MilestoneReleased",
+            "EscrowCompleted",
+        ])
+    }
+
+    fn dispute(&self, escrow_id: u64) -> Result<(), String> {
+        let milestones = [("Audit", 15_000_000)];
+        let total = self.create_and_fund(escrow_id, &milestones, "Standard", far_deadline())?;
+
+        let recipient_before = self.balance(&self.recipient)?;
+        self.invoke(
+            DEPOSITOR,
+            "raise_dispute",
+            &[
+                ("escrow_id", escrow_id.to_string()),
+                ("caller", self.depositor.clone()),
+            ],
+        )?;
+        self.invoke(
+            ADMIN,
+            "resolve_dispute",
+            &[
+                ("escrow_id", escrow_id.to_string()),
+                ("winner", self.recipient.clone()),
+            ],
+        )?;
+
+        // Dispute awards are paid in full, without the platform fee
+        expect_eq(
+            "dispute award",
+            self.balance(&self.recipient)? - recipient_before,
+            total,
+        )?;
+        self.expect_events(&["DisputeRaised", "DisputeResolved"])
+    }
+
+    fn expiry(&self, escrow_id: u64) -> Result<(), String> {
+        let milestones = [("Ship", 12_000_000)];
+        let deadline = now() + EXPIRY_DEADLINE_SECS;
+        let total = self.create_and_fund(escrow_id, &milestones, "RefundGuarantee", deadline)?;
+
+        let premature = self.invoke(
+            RECIPIENT,
+            "claim_guarantee",
+            &[("escrow_id", escrow_id.to_string())],
+        );
+        if premature.is_ok() {
+            return Err("guarantee was claimable before the deadline".into());
+        }
+
+        // Ledger close times trail the wall clock by up to a few seconds
+        let wait = (deadline + 10).saturating_sub(now());
+        thread::sleep(Duration::from_secs(wait));
+
+        let contract_before = self.balance(&self.contract)?;
+        let claimed = parse_i128(&self.invoke(
+            RECIPIENT,
+            "claim_guarantee",
+            &[("escrow_id", escrow_id.to_string())],
+        )?)?;
+        // The buyer pays the transaction fee, so measure the contract side
+        expect_eq("guarantee claimed", claimed, total)?;
+        expect_eq(
+            "escrow balance released",
+            contract_before - self.balance(&self.contract)?,
+            total,
+        )?;
+        self.expect_events(&["GuaranteeClaimed"])
+    }
+
+    /// Creates an escrow with default options, approves and deposits it, and
+    /// returns its total
+    fn create_and_fund(
+        &self,
+        escrow_id: u64,
+        milestones: &[(&str, i128)],
+        kind: &str,
+        deadline: u64,
+    ) -> Result<i128, String> {
+        let total: i128 = milestones.iter().map(|(_, amount)| amount).sum();
+        let milestones_json = serde_json::Value::Array(
+            milestones
+                .iter()
+                .map(|(label, amount)| {
+                    serde_json::json!({
+                        "amount": amount.to_string(),
+                        "status": "Pending",
+                        "description": label,
+                    })
+                })
+                .collect(),
+        );
+        let options = serde_json::json!({
+            "kind": kind,
+            "cliff_until": null,
+            "refund_to": "Depositor",
+            "referral": "None",
+            "lock_recipient": false,
+            "auth_mode": "Strict",
+            "release_schedule": "Immediate",
+            "dispute_policy": "ContinueOthers",
+            "allow_duplicate": false,
+            "fee_payer": "Recipient",
+            "promo": "None",
+        });
+
+        self.invoke(
+            DEPOSITOR,
+            "create_escrow",
+            &[
+                ("escrow_id", escrow_id.to_string()),
+                ("depositor", self.depositor.clone()),
+                ("recipient", self.recipient.clone()),
+                ("token_address", self.token.clone()),
+                ("milestones", milestones_json.to_string()),
+                ("deadline", deadline.to_string()),
+                ("expected_total", total.to_string()),
+                ("options", options.to_string()),
+            ],
+        )?;
+
+        let expiration = self.cli.latest_ledger()? + ALLOWANCE_LEDGERS;
+        self.cli.invoke(
+            &self.token,
+            DEPOSITOR,
+            "approve",
+            &[
+                ("from", self.depositor.clone()),
+                ("spender", self.contract.clone()),
+                ("amount", total.to_string()),
+                ("expiration_ledger", expiration.to_string()),
+            ],
+        )?;
+
+        let contract_before = self.balance(&self.contract)?;
+        self.invoke(
+            DEPOSITOR,
+            "deposit_funds",
+            &[("escrow_id", escrow_id.to_string())],
+        )?;
+        expect_eq(
+            "escrow deposit",
+            self.balance(&self.contract)? - contract_before,
+            total,
+        )?;
+        Ok(total)
+    }
+
+    fn invoke(
+        &self,
+        source: &str,
+        function: &str,
+        args: &[(&str, String)],
+    ) -> Result<String, String> {
+        self.cli.invoke(&self.contract, source, function, args)
+    }
+
+    fn balance(&self, address: &str) -> Result<i128, String> {
+        parse_i128(&self.cli.invoke(
+            &self.token,
+            DEPOSITOR,
+            "balance",
+            &[("id", address.to_string())],
+        )?)
+    }
+
+    fn expect_events(&self, names: &[&str]) -> Result<(), String> {
+        let events = self.cli.events(&self.contract, self.start_ledger)?;
+        match names.iter().find(|name| !has_event(&events, name)) {
+            Some(missing) => Err(format!("no `{missing}` event was emitted")),
+            None => Ok(()),
+        }
+    }
+}
+
+fn expect_eq(what: &str, actual: i128, expected: i128) -> Result<(), String> {
+    if actual != expected {
+        return Err(format!("{what}: expected {expected}, got {actual}"));
+    }
+    Ok(())
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// A deadline no scenario run will reach
+fn far_deadline() -> u64 {
+    now() + 30 * 24 * 60 * 60
+}
@@ -0,0 +1,240 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde_json::Value;
+use std::fmt;
+use std::path::Path;
+use std::process::Command;
+
+/// Networks the suite can run against
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Network {
+    /// A `stellar/quickstart --local` container on localhost:8000
+    Local,
+    /// The public testnet, for release validation
+    Testnet,
+}
+
+impl Network {
+    pub fn rpc_url(&self) -> &'static str {
+        match self {
+            Network::Local => "http://localhost:8000/soroban/rpc",
+            Network::Testnet => "https://soroban-testnet.stellar.org",
+        }
+    }
+
+    pub fn passphrase(&self) -> &'static str {
+        match self {
+            Network::Local => "Standalone Network ; February 2017",
+            Network::Testnet => "Test SDF Network ; September 2015",
+        }
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Network::Local => "local",
+            Network::Testnet => "testnet",
+        })
+    }
+}
+
+/// Wrapper around the `stellar` CLI, which signs transactions for the
+/// identities it manages and talks to soroban RPC on our behalf.
+pub struct StellarCli {
+    bin: String,
+    rpc_url: String,
+    passphrase: String,
+}
+
+impl StellarCli {
+    pub fn new(network: Network, rpc_url: Option<String>) -> Self {
+        let bin = std::env::var("VAULTIX_STELLAR_BIN").unwrap_or_else(|_| "stellar".into());
+        Self {
+            bin,
+            rpc_url: rpc_url.unwrap_or_else(|| network.rpc_url().into()),
+            passphrase: network.passphrase().into(),
+        }
+    }
+
+    /// Creates (or replaces) identity `name`, funds it through friendbot and
+    /// returns its address
+    pub fn fund_identity(&self, name: &str) -> Result<String, String> {
+        self.run(&["keys", "generate", name, "--fund", "--overwrite"], true)?;
+        self.run(&["keys", "address", name], false)
+    }
+
+    /// Uploads and instantiates the wasm, returning the contract ID
+    pub fn deploy(&self, wasm: &Path, source: &str) -> Result<String, String> {
+        let wasm = wasm.to_string_lossy();
+        self.run(
+            &["contract", "deploy", "--wasm", &wasm, "--source", source],
+            true,
+        )
+    }
+
+    /// ID of the native XLM asset contract, deploying it first on networks
+    /// that start without one
+    pub fn native_asset(&self, source: &str) -> Result<String, String> {
+        // Fails harmlessly when the contract already exists
+        let _ = self.run(
+            &[
+                "contract", "asset", "deploy", "--asset", "native", "--source", source,
+            ],
+            true,
+        );
+        self.run(&["contract", "id", "asset", "--asset", "native"], true)
+    }
+
+    /// Invokes a contract function signed by `source`, with `--name value`
+    /// style arguments
+    pub fn invoke(
+        &self,
+        contract_id: &str,
+        source: &str,
+        function: &str,
+        args: &[(&str, String)],
+    ) -> Result<String, String> {
+        let mut cmd = vec![
+            "contract",
+            "invoke",
+            "--id",
+            contract_id,
+            "--source",
+            source,
+            "--",
+            function,
+        ];
+        let flags: Vec<String> = args.iter().map(|(name, _)| format!("--{name}")).collect();
+        for ((_, value), flag) in args.iter().zip(&flags) {
+            cmd.push(flag);
+            cmd.push(value);
+        }
+        self.run(&cmd, true)
+    }
+
+    /// Sequence of the latest closed ledger
+    pub fn latest_ledger(&self) -> Result<u32, String> {
+        let out = self.run(&["ledger", "latest", "--output", "json"], true)?;
+        let ledger: Value =
+            serde_json::from_str(&out).map_err(|e| format!("unexpected ledger output: {e}"))?;
+        ledger["sequence"]
+            .as_u64()
+            .map(|seq| seq as u32)
+            .ok_or_else(|| format!("no sequence in ledger output: {out}"))
+    }
+
+    /// Events `contract_id` emitted since `start_ledger`
+    pub fn events(&self, contract_id: &str, start_ledger: u32) -> Result<Vec<Value>, String> {
+        let start = start_ledger.to_string();
+        let out = self.run(
+            &[
+                "events",
+                "--id",
+                contract_id,
+                "--start-ledger",
+                &start,
+                "--count",
+                "1000",
+                "--output",
+                "json",
+            ],
+            true,
+        )?;
+        parse_events(&out)
+    }
+
+    fn run(&self, args: &[&str], networked: bool) -> Result<String, String> {
+        let mut cmd = Command::new(&self.bin);
+        cmd.args(args);
+        if networked {
+            cmd.args(["--rpc-url", &self.rpc_url])
+                .args(["--network-passphrase", &self.passphrase]);
+        }
+
+        let output = cmd
+            .output()
+            .map_err(|e| format!("failed to run `{}`: {e}", self.bin))?;
+        if !output.status.success() {
+            return Err(format!(
+                "`{} {}` failed: {}",
+                self.bin,
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+}
+
+/// The CLI prints events as a stream of JSON objects, one per event
+fn parse_events(out: &str) -> Result<Vec<Value>, String> {
+    serde_json::Deserializer::from_str(out)
+        .into_iter::<Value>()
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("unexpected events output: {e}"))
+}
+
+/// Reads an `i128` return value, which the CLI prints as a JSON string
+pub fn parse_i128(out: &str) -> Result<i128, String> {
+    out.trim()
+        .trim_matches('"')
+        .parse()
+        .map_err(|e| format!("expected an i128, got `{out}`: {e}"))
+}
+
+/// Base64 XDR of `ScVal::Symbol(name)`, the form event topics come back in
+pub fn symbol_topic(name: &str) -> String {
+    const SCV_SYMBOL: u32 = 15;
+    let mut xdr = Vec::with_capacity(8 + name.len() + 3);
+    xdr.extend_from_slice(&SCV_SYMBOL.to_be_bytes());
+    xdr.extend_from_slice(&(name.len() as u32).to_be_bytes());
+    xdr.extend_from_slice(name.as_bytes());
+    xdr.resize(xdr.len().next_multiple_of(4), 0);
+    STANDARD.encode(xdr)
+}
+
+/// Whether any event carries both the `Vaultix` topic and `name`
+pub fn has_event(events: &[Value], name: &str) -> bool {
+    let vaultix = symbol_topic("Vaultix");
+    let name = symbol_topic(name);
+    events.iter().any(|event| {
+        let topics = event["topic"].as_array().map(Vec::as_slice).unwrap_or(&[]);
+        let has = |topic: &str| topics.iter().any(|t| t.as_str() == Some(topic));
+        has(&vaultix) && has(&name)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symbol_topic_matches_xdr() {
+        // ScVal::Symbol("Vaultix"), as printed by `stellar events`
+        assert_eq!(symbol_topic("Vaultix"), "AAAADwAAAAdWYXVsdGl4AA==");
+        assert_eq!(symbol_topic("Swap"), "AAAADwAAAARTd2Fw");
+    }
+
+    #[test]
+    fn test_has_event_needs_both_topics() {
+        let out = format!(
+            r#"{{"topic": ["{}", "{}"]}}
+            {{"topic": ["{}"]}}"#,
+            symbol_topic("Vaultix"),
+            symbol_topic("EscrowFunded"),
+            symbol_topic("EscrowCompleted"),
+        );
+        let events = parse_events(&out).unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(has_event(&events, "EscrowFunded"));
+        assert!(!has_event(&events, "EscrowCompleted"));
+    }
+
+    #[test]
+    fn test_parse_i128_accepts_quoted_output() {
+        assert_eq!(parse_i128("\"990\"\n"), Ok(990));
+        assert_eq!(parse_i128("-5"), Ok(-5));
+        assert!(parse_i128("null").is_err());
+    }
+}
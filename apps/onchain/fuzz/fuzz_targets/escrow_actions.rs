@@ -0,0 +1,156 @@
+//! Drives random sequences of escrow calls against a test `Env`.
+//!
+//! Every call must either succeed or fail with a contract `Error`; host
+//! panics are bugs. After each step the contract's token balance must cover
+//! everything it still owes across funded escrows.
+//!
+//! Run from `apps/onchain` with `cargo +nightly fuzz run escrow_actions`.
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use onchain::{
+    EscrowKind, EscrowOptions, EscrowStatus, Milestone, MilestoneStatus, VaultixEscrow,
+    VaultixEscrowClient,
+};
+use soroban_sdk::{testutils::Address as _, token, Address, Env, InvokeError, Symbol, Vec};
+
+const MAX_ESCROWS: u8 = 4;
+
+#[derive(Arbitrary, Debug)]
+enum Actor {
+    Depositor,
+    Recipient,
+    Stranger,
+}
+
+#[derive(Arbitrary, Debug)]
+enum Action {
+    Create { id: u8, amounts: std::vec::Vec<u16> },
+    Deposit { id: u8 },
+    Release { id: u8, milestone: u8 },
+    Confirm { id: u8, milestone: u8, by: Actor },
+    Cancel { id: u8 },
+    Complete { id: u8 },
+    Dispute { id: u8, by: Actor },
+    Resolve { id: u8, to: Actor },
+    PartialDispute { id: u8, milestone: u8, amount: i16 },
+    ResolvePartial { id: u8, milestone: u8, to: Actor },
+    SetPaused { paused: bool },
+}
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    fee_bps: u16,
+    actions: std::vec::Vec<Action>,
+}
+
+fuzz_target!(|input: Input| {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let stranger = Address::generate(&env);
+
+    let fee_bps = i128::from(input.fee_bps % 10_001);
+    client.initialize(&treasury, &Some(fee_bps));
+    client.init(&admin);
+
+    let token_address = env.register_stellar_asset_contract(admin.clone());
+    let token_client = token::Client::new(&env, &token_address);
+    token::StellarAssetClient::new(&env, &token_address).mint(&depositor, &i128::from(u32::MAX));
+    token_client.approve(&depositor, &contract_id, &i128::from(u32::MAX), &200);
+
+    let actor = |a: &Actor| match a {
+        Actor::Depositor => depositor.clone(),
+        Actor::Recipient => recipient.clone(),
+        Actor::Stranger => stranger.clone(),
+    };
+    let escrow_id = |id: u8| u64::from(id % MAX_ESCROWS);
+
+    for action in input.actions.iter().take(64) {
+        let outcome: Result<(), Result<onchain::Error, InvokeError>> = match action {
+            Action::Create { id, amounts } => {
+                let mut milestones = Vec::new(&env);
+                let mut total: i128 = 0;
+                for (i, amount) in amounts.iter().take(22).enumerate() {
+                    total += i128::from(*amount);
+                    milestones.push_back(Milestone {
+                        amount: i128::from(*amount),
+                        status: MilestoneStatus::Pending,
+                        description: Symbol::new(&env, &format!("m{i}")),
+                    });
+                }
+                client
+                    .try_create_escrow(
+                        &escrow_id(*id),
+                        &depositor,
+                        &recipient,
+                        &token_address,
+                        &milestones,
+                        &1_706_400_000u64,
+                        &None,
+                        &None,
+                        &total,
+                        &EscrowOptions::new(EscrowKind::Standard),
+                    )
+                    .map(|_| ())
+            }
+            Action::Deposit { id } => client.try_deposit_funds(&escrow_id(*id)).map(|_| ()),
+            Action::Release { id, milestone } => client
+                .try_release_milestone(&escrow_id(*id), &u32::from(*milestone))
+                .map(|_| ()),
+            Action::Confirm { id, milestone, by } => client
+                .try_confirm_delivery(&escrow_id(*id), &u32::from(*milestone), &actor(by))
+                .map(|_| ()),
+            Action::Cancel { id } => client.try_cancel_escrow(&escrow_id(*id)).map(|_| ()),
+            Action::Complete { id } => client.try_complete_escrow(&escrow_id(*id)).map(|_| ()),
+            Action::Dispute { id, by } => client
+                .try_raise_dispute(&escrow_id(*id), &actor(by))
+                .map(|_| ()),
+            Action::Resolve { id, to } => client
+                .try_resolve_dispute(&escrow_id(*id), &actor(to))
+                .map(|_| ()),
+            Action::PartialDispute {
+                id,
+                milestone,
+                amount,
+            } => client
+                .try_open_partial_dispute(
+                    &escrow_id(*id),
+                    &u32::from(*milestone),
+                    &depositor,
+                    &i128::from(*amount),
+                )
+                .map(|_| ()),
+            Action::ResolvePartial { id, milestone, to } => client
+                .try_resolve_partial_dispute(&escrow_id(*id), &u32::from(*milestone), &actor(to))
+                .map(|_| ()),
+            Action::SetPaused { paused } => client.try_set_paused(paused).map(|_| ()),
+        };
+
+        if let Err(Err(e)) = outcome {
+            panic!("{action:?} failed outside the contract error set: {e:?}");
+        }
+
+        let mut owed: i128 = 0;
+        for id in 0..MAX_ESCROWS {
+            if let Ok(Ok(escrow)) = client.try_get_escrow(&u64::from(id)) {
+                if escrow.status == EscrowStatus::Active || escrow.status == EscrowStatus::Disputed
+                {
+                    owed += escrow.total_amount - escrow.total_released;
+                }
+            }
+        }
+        assert!(
+            token_client.balance(&contract_id) >= owed,
+            "contract holds less than it owes after {action:?}"
+        );
+    }
+});
@@ -0,0 +1,119 @@
+use crate::criteria::criteria_key;
+use crate::{
+    archive_key, cliff_key, closed_at_key, commitments_key, counter_leg_key, depeg_override_key,
+    dispute_policy_key, ensure_not_paused, escrow_totals_key, expenses, extend_instance_ttl,
+    fee_payer_key, get_storage_key, grants::program_admin_key, has_pending_tranches,
+    held_release_key, hourly, partial_dispute_key, quarantine, recipient_lock_key, referrals,
+    refund_address_key, release_allowance_key, release_schedule_key, unclaimed, ArchivedEscrow,
+    Error, Escrow, EscrowStatus, VaultixEscrow, VaultixEscrowClient,
+};
+use soroban_sdk::{contractimpl, xdr::ToXdr, Address, Env, Symbol};
+
+#[cfg(any(test, feature = "testutils"))]
+use crate::__VaultixEscrow_fn_set_registry;
+
+/// How long a finished escrow keeps its full record before it may be archived
+pub const ARCHIVE_RETENTION_SECS: u64 = 30 * 24 * 60 * 60;
+
+#[contractimpl]
+impl VaultixEscrow {
+    /// Replaces a finished escrow with a compact `ArchivedEscrow` once the
+    /// retention window has passed, and drops its per-escrow settings.
+    /// Terms hashes and attestations are kept as evidence.
+    pub fn archive_escrow(env: Env, escrow_id: u64, caller: Address) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        let storage_key = get_storage_key(escrow_id);
+        ensure_not_paused(&env)?;
+
+        let escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&storage_key)
+            .ok_or(Error::EscrowNotFound)?;
+        if caller != escrow.depositor && caller != escrow.recipient {
+            return Err(Error::UnauthorizedAccess);
+        }
+        escrow.authorize(escrow_id, &caller);
+        if !matches!(
+            escrow.status,
+            EscrowStatus::Completed | EscrowStatus::Cancelled | EscrowStatus::Resolved
+        ) {
+            return Err(Error::InvalidEscrowStatus);
+        }
+        // Unclaimed tranches and payouts, and quarantined refunds, still
+        // read the escrow
+        if has_pending_tranches(&env, escrow_id, &escrow)
+            || unclaimed::unclaimed_total(&env, escrow_id) > 0
+            || quarantine::quarantined_refunds(&env, escrow_id) > 0
+        {
+            return Err(Error::InvalidEscrowStatus);
+        }
+
+        // Escrows closed before close times were recorded fall back to the deadline
+        let closed_at: u64 = env
+            .storage()
+            .persistent()
+            .get(&closed_at_key(escrow_id))
+            .unwrap_or(escrow.deadline);
+        if env.ledger().timestamp() < closed_at.saturating_add(ARCHIVE_RETENTION_SECS) {
+            return Err(Error::RetentionNotElapsed);
+        }
+
+        let milestone_count = escrow.milestones.len();
+        let record = ArchivedEscrow {
+            state_hash: env.crypto().sha256(&escrow.clone().to_xdr(&env)),
+            depositor: escrow.depositor,
+            recipient: escrow.recipient,
+            token_address: escrow.token_address,
+            total_amount: escrow.total_amount,
+            total_released: escrow.total_released,
+            status: escrow.status,
+            closed_at,
+        };
+        let key = archive_key(escrow_id);
+        env.storage().persistent().set(&key, &record);
+        env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+
+        let storage = env.storage().persistent();
+        storage.remove(&storage_key);
+        storage.remove(&closed_at_key(escrow_id));
+        storage.remove(&commitments_key(escrow_id));
+        storage.remove(&counter_leg_key(escrow_id));
+        storage.remove(&cliff_key(escrow_id));
+        storage.remove(&release_allowance_key(escrow_id));
+        storage.remove(&refund_address_key(escrow_id));
+        storage.remove(&recipient_lock_key(escrow_id));
+        storage.remove(&release_schedule_key(escrow_id));
+        storage.remove(&dispute_policy_key(escrow_id));
+        storage.remove(&fee_payer_key(escrow_id));
+        storage.remove(&escrow_totals_key(escrow_id));
+        hourly::remove_work_logs(&env, escrow_id);
+        for milestone_index in 0..milestone_count {
+            storage.remove(&criteria_key(escrow_id, milestone_index));
+            storage.remove(&depeg_override_key(escrow_id, milestone_index));
+            storage.remove(&held_release_key(escrow_id, milestone_index));
+            storage.remove(&partial_dispute_key(escrow_id, milestone_index));
+            expenses::remove_expense_claims(&env, escrow_id, milestone_index);
+        }
+        storage.remove(&referrals::escrow_referral_key(escrow_id));
+        storage.remove(&program_admin_key(escrow_id));
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "EscrowArchived"),
+                escrow_id,
+            ),
+            (caller, record.state_hash),
+        );
+
+        Ok(())
+    }
+
+    pub fn get_archived_escrow(env: Env, escrow_id: u64) -> Result<ArchivedEscrow, Error> {
+        env.storage()
+            .persistent()
+            .get(&archive_key(escrow_id))
+            .ok_or(Error::EscrowNotFound)
+    }
+}
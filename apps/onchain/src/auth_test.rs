@@ -0,0 +1,279 @@
+//! Authorization flows where an escrow party is a custom account contract
+//! (a smart wallet or policy signer) instead of a keypair.
+//!
+//! These tests submit real authorization entries with `set_auths`, so every
+//! `require_auth` in the escrow contract goes through the account's
+//! `__check_auth` with the contexts the host builds for the call.
+extern crate std;
+
+use super::*;
+use soroban_sdk::{
+    auth::{Context, ContractContext, CustomAccountInterface},
+    contract, contracterror, contractimpl,
+    testutils::Address as _,
+    token, vec,
+    xdr::{
+        InvokeContractArgs, ScAddress, ScSymbol, ScVal, SorobanAddressCredentials,
+        SorobanAuthorizationEntry, SorobanAuthorizedFunction, SorobanAuthorizedInvocation,
+        SorobanCredentials, VecM,
+    },
+    Address, Env, IntoVal, TryFromVal, Val,
+};
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PolicyError {
+    NotAllowed = 1,
+}
+
+/// Smart wallet that approves calls to one escrow contract, restricted to an
+/// allow-list of entrypoints. Signatures are empty: the policy is the signer.
+#[contract]
+pub struct PolicyAccount;
+
+#[contractimpl]
+impl PolicyAccount {
+    pub fn init(env: Env, escrow: Address, allowed: Vec<Symbol>) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("escrow"), &escrow);
+        env.storage()
+            .instance()
+            .set(&symbol_short!("allowed"), &allowed);
+    }
+
+    pub fn checks(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("checks"))
+            .unwrap_or(0)
+    }
+}
+
+#[contractimpl]
+impl CustomAccountInterface for PolicyAccount {
+    type Signature = ();
+    type Error = PolicyError;
+
+    #[allow(non_snake_case)]
+    fn __check_auth(
+        env: Env,
+        _signature_payload: BytesN<32>,
+        _signatures: (),
+        auth_contexts: Vec<Context>,
+    ) -> Result<(), PolicyError> {
+        let escrow: Address = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("escrow"))
+            .unwrap();
+        let allowed: Vec<Symbol> = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("allowed"))
+            .unwrap();
+
+        for context in auth_contexts.iter() {
+            match context {
+                Context::Contract(call)
+                    if call.contract == escrow && allowed.contains(&call.fn_name) => {}
+                _ => return Err(PolicyError::NotAllowed),
+            }
+        }
+
+        let checks = Self::checks(env.clone()) + 1;
+        env.storage()
+            .instance()
+            .set(&symbol_short!("checks"), &checks);
+        Ok(())
+    }
+}
+
+/// Builds the authorization entry a wallet would sign for `signer` invoking
+/// `fn_name(args)` on `contract`, with no sub-invocations.
+fn auth_entry(
+    env: &Env,
+    signer: &Address,
+    nonce: i64,
+    contract: &Address,
+    fn_name: &str,
+    args: Vec<Val>,
+) -> SorobanAuthorizationEntry {
+    let args: std::vec::Vec<ScVal> = args
+        .iter()
+        .map(|arg| ScVal::try_from_val(env, &arg).unwrap())
+        .collect();
+    SorobanAuthorizationEntry {
+        credentials: SorobanCredentials::Address(SorobanAddressCredentials {
+            address: ScAddress::try_from(signer).unwrap(),
+            nonce,
+            signature_expiration_ledger: env.ledger().sequence() + 100,
+            signature: ScVal::Void,
+        }),
+        root_invocation: SorobanAuthorizedInvocation {
+            function: SorobanAuthorizedFunction::ContractFn(InvokeContractArgs {
+                contract_address: ScAddress::try_from(contract).unwrap(),
+                function_name: ScSymbol(fn_name.try_into().unwrap()),
+                args: args.try_into().unwrap(),
+            }),
+            sub_invocations: VecM::default(),
+        },
+    }
+}
+
+struct Setup {
+    env: Env,
+    client: VaultixEscrowClient<'static>,
+    contract_id: Address,
+    wallet: Address,
+    recipient: Address,
+    token: token::Client<'static>,
+}
+
+/// Funded escrow 1 whose depositor is a policy wallet allowed to call `allowed`
+fn setup(allowed: &[&str]) -> Setup {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+
+    let wallet = env.register_contract(None, PolicyAccount);
+    let mut allowed_fns = Vec::new(&env);
+    for name in allowed {
+        allowed_fns.push_back(Symbol::new(&env, name));
+    }
+    PolicyAccountClient::new(&env, &wallet).init(&contract_id, &allowed_fns);
+
+    let admin = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(admin);
+    let token = token::Client::new(&env, &token_address);
+    token::StellarAssetClient::new(&env, &token_address).mint(&wallet, &1000);
+    token.approve(&wallet, &contract_id, &1000, &200);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 400,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Draft"),
+        },
+        Milestone {
+            amount: 600,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Final"),
+        },
+    ];
+    client.create_escrow(
+        &1,
+        &wallet,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+        &None,
+        &None,
+        &1000,
+        &EscrowOptions::new(EscrowKind::Standard),
+    );
+    client.deposit_funds(&1);
+
+    Setup {
+        env,
+        client,
+        contract_id,
+        wallet,
+        recipient,
+        token,
+    }
+}
+
+#[test]
+fn test_policy_wallet_releases_milestone() {
+    let s = setup(&["release_milestone", "confirm_delivery"]);
+
+    s.env.set_auths(&[auth_entry(
+        &s.env,
+        &s.wallet,
+        1,
+        &s.contract_id,
+        "release_milestone",
+        (1u64, 0u32).into_val(&s.env),
+    )]);
+    s.client.release_milestone(&1, &0);
+    assert_eq!(s.token.balance(&s.recipient), 400);
+
+    // confirm_delivery authorizes the buyer argument, here the wallet itself
+    s.env.set_auths(&[auth_entry(
+        &s.env,
+        &s.wallet,
+        2,
+        &s.contract_id,
+        "confirm_delivery",
+        (1u64, 1u32, s.wallet.clone()).into_val(&s.env),
+    )]);
+    s.client.confirm_delivery(&1, &1, &s.wallet);
+    assert_eq!(s.token.balance(&s.recipient), 1000);
+
+    assert_eq!(PolicyAccountClient::new(&s.env, &s.wallet).checks(), 2);
+}
+
+/// Calls the wallet's `__check_auth` directly with the context the host would
+/// build for `fn_name(args)` on `contract`. Rejected authorizations are
+/// asserted this way rather than through a failing escrow call, since a host
+/// auth failure cannot be caught by `try_` clients.
+fn check_call(
+    s: &Setup,
+    contract: &Address,
+    fn_name: &str,
+    args: Vec<Val>,
+) -> Result<(), PolicyError> {
+    let context = Context::Contract(ContractContext {
+        contract: contract.clone(),
+        fn_name: Symbol::new(&s.env, fn_name),
+        args,
+    });
+    s.env
+        .try_invoke_contract_check_auth::<PolicyError>(
+            &s.wallet,
+            &BytesN::from_array(&s.env, &[0u8; 32]),
+            ().into_val(&s.env),
+            &vec![&s.env, context],
+        )
+        .map_err(|e| e.unwrap())
+}
+
+#[test]
+fn test_policy_wallet_blocks_disallowed_calls() {
+    let s = setup(&["release_milestone"]);
+    let release_args: Vec<Val> = (1u64, 1u32).into_val(&s.env);
+
+    assert_eq!(
+        check_call(
+            &s,
+            &s.contract_id,
+            "release_milestone",
+            release_args.clone()
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        check_call(
+            &s,
+            &s.contract_id,
+            "cancel_escrow",
+            (1u64,).into_val(&s.env)
+        ),
+        Err(PolicyError::NotAllowed)
+    );
+
+    // The same entrypoint on any other contract is refused
+    let other = Address::generate(&s.env);
+    assert_eq!(
+        check_call(&s, &other, "release_milestone", release_args),
+        Err(PolicyError::NotAllowed)
+    );
+}
@@ -0,0 +1,168 @@
+use crate::{
+    ensure_not_paused, extend_instance_ttl, Criterion, Error, EscrowStatus, MilestoneStatus,
+    VaultixEscrow, VaultixEscrowClient,
+};
+use soroban_sdk::{contractimpl, symbol_short, BytesN, Env, Symbol, Vec};
+
+#[cfg(any(test, feature = "testutils"))]
+use crate::__VaultixEscrow_fn_set_registry;
+
+/// Most acceptance criteria a milestone may carry
+pub const MAX_CRITERIA: u32 = 10;
+
+#[contractimpl]
+impl VaultixEscrow {
+    /// Attaches a checklist of acceptance-criteria hashes to a milestone of
+    /// an unfunded escrow, replacing any earlier one. Set by the party that
+    /// approves releases.
+    pub fn set_acceptance_criteria(
+        env: Env,
+        escrow_id: u64,
+        milestone_index: u32,
+        criteria: Vec<BytesN<32>>,
+    ) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        ensure_not_paused(&env)?;
+        let escrow = Self::get_escrow(env.clone(), escrow_id)?;
+        escrow.authorize(escrow_id, escrow.releaser());
+
+        if escrow.status != EscrowStatus::Created {
+            return Err(Error::InvalidEscrowStatus);
+        }
+        if milestone_index >= escrow.milestones.len() {
+            return Err(Error::MilestoneNotFound);
+        }
+        if criteria.len() > MAX_CRITERIA {
+            return Err(Error::VectorTooLarge);
+        }
+
+        let mut checklist = Vec::new(&env);
+        for hash in criteria.iter() {
+            checklist.push_back(Criterion {
+                hash,
+                delivered: false,
+                accepted: false,
+            });
+        }
+        save_checklist(&env, escrow_id, milestone_index, &checklist);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "CriteriaSet"),
+                escrow_id,
+                milestone_index,
+            ),
+            criteria,
+        );
+
+        Ok(())
+    }
+
+    /// Checks a criterion off as delivered. Signed by the payee while the
+    /// milestone is still pending.
+    pub fn mark_criterion_delivered(
+        env: Env,
+        escrow_id: u64,
+        milestone_index: u32,
+        criterion_index: u32,
+    ) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        ensure_not_paused(&env)?;
+        let escrow = Self::get_escrow(env.clone(), escrow_id)?;
+        escrow.authorize(escrow_id, escrow.payee());
+
+        let milestone = escrow
+            .milestones
+            .get(milestone_index)
+            .ok_or(Error::MilestoneNotFound)?;
+        if escrow.status != EscrowStatus::Active || milestone.status != MilestoneStatus::Pending {
+            return Err(Error::InvalidEscrowStatus);
+        }
+        let mut checklist = Self::get_acceptance_criteria(env.clone(), escrow_id, milestone_index);
+        let mut criterion = checklist
+            .get(criterion_index)
+            .ok_or(Error::MilestoneNotFound)?;
+        criterion.delivered = true;
+        checklist.set(criterion_index, criterion);
+        save_checklist(&env, escrow_id, milestone_index, &checklist);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "CriterionDelivered"),
+                escrow_id,
+                milestone_index,
+            ),
+            criterion_index,
+        );
+
+        Ok(())
+    }
+
+    /// Records which delivered criteria the approving party accepted, as an
+    /// audit trail should the milestone be disputed. Accepting does not
+    /// release the milestone.
+    pub fn accept_criteria(
+        env: Env,
+        escrow_id: u64,
+        milestone_index: u32,
+        accepted: Vec<u32>,
+    ) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        ensure_not_paused(&env)?;
+        let escrow = Self::get_escrow(env.clone(), escrow_id)?;
+        escrow.authorize(escrow_id, escrow.releaser());
+
+        if escrow.status != EscrowStatus::Active {
+            return Err(Error::InvalidEscrowStatus);
+        }
+        let mut checklist = Self::get_acceptance_criteria(env.clone(), escrow_id, milestone_index);
+        for criterion_index in accepted.iter() {
+            let mut criterion = checklist
+                .get(criterion_index)
+                .ok_or(Error::MilestoneNotFound)?;
+            if !criterion.delivered {
+                return Err(Error::InvalidEscrowStatus);
+            }
+            criterion.accepted = true;
+            checklist.set(criterion_index, criterion);
+        }
+        save_checklist(&env, escrow_id, milestone_index, &checklist);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "CriteriaAccepted"),
+                escrow_id,
+                milestone_index,
+            ),
+            accepted,
+        );
+
+        Ok(())
+    }
+
+    /// A milestone's acceptance criteria and how far each got; empty when
+    /// none were set
+    pub fn get_acceptance_criteria(
+        env: Env,
+        escrow_id: u64,
+        milestone_index: u32,
+    ) -> Vec<Criterion> {
+        env.storage()
+            .persistent()
+            .get(&criteria_key(escrow_id, milestone_index))
+            .unwrap_or(Vec::new(&env))
+    }
+}
+
+pub(crate) fn criteria_key(escrow_id: u64, milestone_index: u32) -> (Symbol, u64, u32) {
+    (symbol_short!("criteria"), escrow_id, milestone_index)
+}
+
+fn save_checklist(env: &Env, escrow_id: u64, milestone_index: u32, checklist: &Vec<Criterion>) {
+    let key = criteria_key(escrow_id, milestone_index);
+    env.storage().persistent().set(&key, checklist);
+    env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+}
@@ -0,0 +1,321 @@
+use crate::expenses;
+use crate::settlement::{settle, Outcome};
+use crate::{
+    ensure_disputable, ensure_not_paused, extend_instance_ttl, get_admin, get_storage_key,
+    held_release_key, partial_dispute_key, set_status, unlock_value, Error, Escrow, EscrowKind,
+    EscrowStatus, MilestoneStatus, Resolution, VaultixEscrow, VaultixEscrowClient,
+};
+use soroban_sdk::{contractimpl, Address, Env, Symbol, Vec};
+
+#[cfg(any(test, feature = "testutils"))]
+use crate::__VaultixEscrow_fn_set_registry;
+
+#[contractimpl]
+impl VaultixEscrow {
+    pub fn raise_dispute(env: Env, escrow_id: u64, caller: Address) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        let storage_key = get_storage_key(escrow_id);
+        ensure_not_paused(&env)?;
+
+        let mut escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&storage_key)
+            .ok_or(Error::EscrowNotFound)?;
+
+        ensure_disputable(&escrow, &caller)?;
+        escrow.authorize(escrow_id, &caller);
+
+        let mut updated_milestones = Vec::new(&env);
+        for milestone in escrow.milestones.iter() {
+            let mut m = milestone.clone();
+            if m.status == MilestoneStatus::Pending {
+                m.status = MilestoneStatus::Disputed;
+            }
+            updated_milestones.push_back(m);
+        }
+
+        escrow.milestones = updated_milestones;
+        set_status(&env, escrow_id, &mut escrow, EscrowStatus::Disputed);
+        escrow.resolution = Resolution::None;
+        env.storage().persistent().set(&storage_key, &escrow);
+
+        // Standardized Event
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "DisputeRaised"),
+                escrow_id,
+            ),
+            caller,
+        );
+
+        Ok(())
+    }
+
+    pub fn resolve_dispute(env: Env, escrow_id: u64, winner: Address) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+
+        let storage_key = get_storage_key(escrow_id);
+        let mut escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&storage_key)
+            .ok_or(Error::EscrowNotFound)?;
+
+        if escrow.status != EscrowStatus::Disputed {
+            return Err(Error::InvalidEscrowStatus);
+        }
+        if winner != escrow.depositor && winner != escrow.recipient {
+            return Err(Error::InvalidWinner);
+        }
+
+        let outstanding = escrow
+            .total_amount
+            .checked_sub(escrow.total_released)
+            .ok_or(Error::InvalidMilestoneAmount)?;
+        // The ruling settles held payouts along with everything else
+        for (i, milestone) in escrow.milestones.iter().enumerate() {
+            if milestone.status == MilestoneStatus::Held {
+                env.storage()
+                    .persistent()
+                    .remove(&held_release_key(escrow_id, i as u32));
+            }
+        }
+        if winner == escrow.recipient {
+            let mut updated_milestones = Vec::new(&env);
+            for milestone in escrow.milestones.iter() {
+                let mut m = milestone.clone();
+                if m.status != MilestoneStatus::Released
+                    && m.status != MilestoneStatus::SettledOffChain
+                {
+                    m.status = MilestoneStatus::Released;
+                }
+                updated_milestones.push_back(m);
+            }
+            escrow.milestones = updated_milestones;
+            escrow.resolution = Resolution::Recipient;
+            settle(
+                &env,
+                escrow_id,
+                &mut escrow,
+                None,
+                outstanding,
+                Outcome::Award { charge_fee: false },
+            )?;
+        } else {
+            let mut updated_milestones = Vec::new(&env);
+            for milestone in escrow.milestones.iter() {
+                let mut m = milestone.clone();
+                if matches!(
+                    m.status,
+                    MilestoneStatus::Pending | MilestoneStatus::Disputed | MilestoneStatus::Held
+                ) {
+                    m.status = MilestoneStatus::Disputed;
+                }
+                updated_milestones.push_back(m);
+            }
+            escrow.milestones = updated_milestones;
+            escrow.resolution = Resolution::Depositor;
+            settle(
+                &env,
+                escrow_id,
+                &mut escrow,
+                None,
+                outstanding,
+                Outcome::Refund,
+            )?;
+        }
+
+        set_status(&env, escrow_id, &mut escrow, EscrowStatus::Resolved);
+        env.storage().persistent().set(&storage_key, &escrow);
+
+        // Standardized Event
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "DisputeResolved"),
+                escrow_id,
+            ),
+            winner,
+        );
+
+        Ok(())
+    }
+
+    /// Disputes only `disputed_amount` of a pending milestone. The escrow stays
+    /// active: the undisputed remainder can still be released while the
+    /// disputed slice is frozen until the admin resolves it.
+    pub fn open_partial_dispute(
+        env: Env,
+        escrow_id: u64,
+        milestone_index: u32,
+        caller: Address,
+        disputed_amount: i128,
+    ) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        let storage_key = get_storage_key(escrow_id);
+        ensure_not_paused(&env)?;
+
+        let escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&storage_key)
+            .ok_or(Error::EscrowNotFound)?;
+
+        if caller != escrow.depositor && caller != escrow.recipient {
+            return Err(Error::UnauthorizedAccess);
+        }
+        escrow.authorize(escrow_id, &caller);
+
+        if escrow.status != EscrowStatus::Active {
+            return Err(Error::EscrowNotActive);
+        }
+        // Holdbacks assume the depositor is the paying party
+        if escrow.kind != EscrowKind::Standard {
+            return Err(Error::InvalidEscrowKind);
+        }
+
+        let milestone = escrow
+            .milestones
+            .get(milestone_index)
+            .ok_or(Error::MilestoneNotFound)?;
+        if milestone.status != MilestoneStatus::Pending {
+            return Err(Error::MilestoneAlreadyReleased);
+        }
+        if disputed_amount <= 0 || disputed_amount > milestone.amount {
+            return Err(Error::InvalidMilestoneAmount);
+        }
+        // An expense cap shrinks claim by claim and is refunded on close,
+        // which would settle a frozen slice twice
+        if expenses::is_expense_milestone(&env, escrow_id, milestone_index) {
+            return Err(Error::InvalidEscrowKind);
+        }
+
+        let dispute_key = partial_dispute_key(escrow_id, milestone_index);
+        if env.storage().persistent().has(&dispute_key) {
+            return Err(Error::AlreadyInDispute);
+        }
+        env.storage()
+            .persistent()
+            .set(&dispute_key, &disputed_amount);
+        env.storage()
+            .persistent()
+            .extend_ttl(&dispute_key, 100, 2_000_000);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "PartialDisputeRaised"),
+                escrow_id,
+                milestone_index,
+            ),
+            (caller, disputed_amount),
+        );
+
+        Ok(())
+    }
+
+    pub fn get_partial_dispute(env: Env, escrow_id: u64, milestone_index: u32) -> Option<i128> {
+        env.storage()
+            .persistent()
+            .get(&partial_dispute_key(escrow_id, milestone_index))
+    }
+
+    /// Settles a partial dispute. A recipient win unfreezes the slice (paying
+    /// it out if the remainder was already released); a depositor win refunds
+    /// the slice and removes it from the milestone.
+    pub fn resolve_partial_dispute(
+        env: Env,
+        escrow_id: u64,
+        milestone_index: u32,
+        winner: Address,
+    ) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+
+        let storage_key = get_storage_key(escrow_id);
+        let mut escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&storage_key)
+            .ok_or(Error::EscrowNotFound)?;
+
+        if escrow.status != EscrowStatus::Active {
+            return Err(Error::EscrowNotActive);
+        }
+        if winner != escrow.depositor && winner != escrow.recipient {
+            return Err(Error::InvalidWinner);
+        }
+
+        let dispute_key = partial_dispute_key(escrow_id, milestone_index);
+        let disputed_amount: i128 = env
+            .storage()
+            .persistent()
+            .get(&dispute_key)
+            .ok_or(Error::InvalidEscrowStatus)?;
+        let mut milestone = escrow
+            .milestones
+            .get(milestone_index)
+            .ok_or(Error::MilestoneNotFound)?;
+        let remainder_released = milestone.status == MilestoneStatus::Disputed;
+
+        if winner == escrow.recipient {
+            if remainder_released {
+                // The slice is paid like the rest of its milestone, net of fee
+                settle(
+                    &env,
+                    escrow_id,
+                    &mut escrow,
+                    Some(milestone_index),
+                    disputed_amount,
+                    Outcome::Award { charge_fee: true },
+                )?;
+                milestone.status = MilestoneStatus::Released;
+            }
+        } else {
+            settle(
+                &env,
+                escrow_id,
+                &mut escrow,
+                Some(milestone_index),
+                disputed_amount,
+                Outcome::Refund,
+            )?;
+            milestone.amount = milestone
+                .amount
+                .checked_sub(disputed_amount)
+                .ok_or(Error::InvalidMilestoneAmount)?;
+            escrow.total_amount = escrow
+                .total_amount
+                .checked_sub(disputed_amount)
+                .ok_or(Error::InvalidMilestoneAmount)?;
+            unlock_value(&env, &escrow.token_address, disputed_amount);
+            if remainder_released || milestone.amount == 0 {
+                milestone.status = MilestoneStatus::Released;
+            }
+        }
+
+        escrow.milestones.set(milestone_index, milestone);
+        env.storage().persistent().remove(&dispute_key);
+        env.storage().persistent().set(&storage_key, &escrow);
+        env.storage()
+            .persistent()
+            .extend_ttl(&storage_key, 100, 2_000_000);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "PartialDisputeResolved"),
+                escrow_id,
+                milestone_index,
+            ),
+            (winner, disputed_amount),
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,312 @@
+use crate::settlement::{settle, Outcome};
+use crate::{
+    ensure_not_paused, extend_instance_ttl, get_storage_key, partial_dispute_key, unlock_value,
+    Error, Escrow, EscrowKind, EscrowStatus, ExpenseClaim, Milestone, MilestoneStatus,
+    VaultixEscrow, VaultixEscrowClient,
+};
+use soroban_sdk::{contractimpl, symbol_short, BytesN, Env, Symbol};
+
+#[cfg(any(test, feature = "testutils"))]
+use crate::__VaultixEscrow_fn_set_registry;
+
+#[contractimpl]
+impl VaultixEscrow {
+    /// Reserves a milestone of an unfunded escrow for reimbursed expenses.
+    /// Its amount becomes a cap: it pays out only through approved expense
+    /// claims, and whatever is left is refunded when it is closed.
+    pub fn set_expense_milestone(
+        env: Env,
+        escrow_id: u64,
+        milestone_index: u32,
+    ) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        ensure_not_paused(&env)?;
+        let escrow = Self::get_escrow(env.clone(), escrow_id)?;
+        escrow.authorize(escrow_id, escrow.releaser());
+
+        if escrow.status != EscrowStatus::Created {
+            return Err(Error::InvalidEscrowStatus);
+        }
+        // An hourly pool already pays out through its own approvals
+        if escrow.kind == EscrowKind::Hourly {
+            return Err(Error::InvalidEscrowKind);
+        }
+        if milestone_index >= escrow.milestones.len() {
+            return Err(Error::MilestoneNotFound);
+        }
+
+        let key = expense_milestone_key(escrow_id, milestone_index);
+        env.storage().persistent().set(&key, &true);
+        env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "ExpenseMilestoneSet"),
+                escrow_id,
+                milestone_index,
+            ),
+            (),
+        );
+
+        Ok(())
+    }
+
+    /// Files an expense claim against an expense milestone, anchored by the
+    /// hash of its receipt, returning the claim's index. Signed by the payee.
+    pub fn claim_expense(
+        env: Env,
+        escrow_id: u64,
+        milestone_index: u32,
+        amount: i128,
+        receipt_hash: BytesN<32>,
+    ) -> Result<u32, Error> {
+        extend_instance_ttl(&env);
+        ensure_not_paused(&env)?;
+        let escrow = Self::get_escrow(env.clone(), escrow_id)?;
+        escrow.authorize(escrow_id, escrow.payee());
+
+        let milestone = expense_milestone(&env, escrow_id, &escrow, milestone_index)?;
+        if amount <= 0 {
+            return Err(Error::ZeroAmount);
+        }
+        if amount > milestone.amount {
+            return Err(Error::InsufficientBalance);
+        }
+
+        let claim_index = claim_count(&env, escrow_id, milestone_index);
+        let claim = ExpenseClaim {
+            receipt_hash: receipt_hash.clone(),
+            amount,
+            approved: false,
+        };
+        save_claim(&env, escrow_id, milestone_index, claim_index, &claim);
+        let count_key = claim_count_key(escrow_id, milestone_index);
+        env.storage()
+            .persistent()
+            .set(&count_key, &(claim_index + 1));
+        env.storage()
+            .persistent()
+            .extend_ttl(&count_key, 100, 2_000_000);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "ExpenseClaimed"),
+                escrow_id,
+                milestone_index,
+            ),
+            (claim_index, amount, receipt_hash),
+        );
+
+        Ok(claim_index)
+    }
+
+    /// Approves an expense claim and pays it from the milestone's remaining
+    /// cap like any milestone release
+    pub fn approve_expense(
+        env: Env,
+        escrow_id: u64,
+        milestone_index: u32,
+        claim_index: u32,
+    ) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        ensure_not_paused(&env)?;
+        let storage_key = get_storage_key(escrow_id);
+        let mut escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&storage_key)
+            .ok_or(Error::EscrowNotFound)?;
+        escrow.authorize(escrow_id, escrow.releaser());
+
+        let mut milestone = expense_milestone(&env, escrow_id, &escrow, milestone_index)?;
+        let mut claim =
+            Self::get_expense_claim(env.clone(), escrow_id, milestone_index, claim_index)
+                .ok_or(Error::MilestoneNotFound)?;
+        if claim.approved {
+            return Err(Error::MilestoneAlreadyReleased);
+        }
+        if claim.amount > milestone.amount {
+            return Err(Error::InsufficientBalance);
+        }
+
+        settle(
+            &env,
+            escrow_id,
+            &mut escrow,
+            Some(milestone_index),
+            claim.amount,
+            Outcome::Release,
+        )?;
+        milestone.amount -= claim.amount;
+        if milestone.amount == 0 {
+            milestone.status = MilestoneStatus::Released;
+        }
+        escrow.milestones.set(milestone_index, milestone);
+        env.storage().persistent().set(&storage_key, &escrow);
+        env.storage()
+            .persistent()
+            .extend_ttl(&storage_key, 100, 2_000_000);
+
+        claim.approved = true;
+        save_claim(&env, escrow_id, milestone_index, claim_index, &claim);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "ExpenseApproved"),
+                escrow_id,
+                milestone_index,
+            ),
+            (claim_index, claim.amount),
+        );
+
+        Ok(())
+    }
+
+    /// Closes an expense milestone, refunding the unspent cap to the
+    /// depositor. Claims still awaiting approval lapse.
+    pub fn close_expenses(env: Env, escrow_id: u64, milestone_index: u32) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        ensure_not_paused(&env)?;
+        let storage_key = get_storage_key(escrow_id);
+        let mut escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&storage_key)
+            .ok_or(Error::EscrowNotFound)?;
+        escrow.authorize(escrow_id, escrow.releaser());
+
+        let mut milestone = expense_milestone(&env, escrow_id, &escrow, milestone_index)?;
+        let unspent = milestone.amount;
+        settle(
+            &env,
+            escrow_id,
+            &mut escrow,
+            Some(milestone_index),
+            unspent,
+            Outcome::Refund,
+        )?;
+        escrow.total_amount = escrow
+            .total_amount
+            .checked_sub(unspent)
+            .ok_or(Error::InvalidMilestoneAmount)?;
+        unlock_value(&env, &escrow.token_address, unspent);
+        milestone.amount = 0;
+        milestone.status = MilestoneStatus::Released;
+        escrow.milestones.set(milestone_index, milestone);
+        env.storage().persistent().set(&storage_key, &escrow);
+        env.storage()
+            .persistent()
+            .extend_ttl(&storage_key, 100, 2_000_000);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "ExpensesClosed"),
+                escrow_id,
+                milestone_index,
+            ),
+            unspent,
+        );
+
+        Ok(())
+    }
+
+    pub fn get_expense_claim(
+        env: Env,
+        escrow_id: u64,
+        milestone_index: u32,
+        claim_index: u32,
+    ) -> Option<ExpenseClaim> {
+        env.storage()
+            .persistent()
+            .get(&claim_key(escrow_id, milestone_index, claim_index))
+    }
+}
+
+fn expense_milestone_key(escrow_id: u64, milestone_index: u32) -> (Symbol, u64, u32) {
+    (symbol_short!("expenses"), escrow_id, milestone_index)
+}
+
+fn claim_key(escrow_id: u64, milestone_index: u32, claim_index: u32) -> (Symbol, u64, u32, u32) {
+    (
+        symbol_short!("exp_claim"),
+        escrow_id,
+        milestone_index,
+        claim_index,
+    )
+}
+
+fn claim_count_key(escrow_id: u64, milestone_index: u32) -> (Symbol, u64, u32) {
+    (symbol_short!("exp_count"), escrow_id, milestone_index)
+}
+
+/// Whether a milestone pays out through expense claims rather than a release
+pub(crate) fn is_expense_milestone(env: &Env, escrow_id: u64, milestone_index: u32) -> bool {
+    env.storage()
+        .persistent()
+        .has(&expense_milestone_key(escrow_id, milestone_index))
+}
+
+/// The expense milestone at `milestone_index`, while it can still take and
+/// pay claims
+fn expense_milestone(
+    env: &Env,
+    escrow_id: u64,
+    escrow: &Escrow,
+    milestone_index: u32,
+) -> Result<Milestone, Error> {
+    if escrow.status != EscrowStatus::Active {
+        return Err(Error::EscrowNotActive);
+    }
+    let milestone = escrow
+        .milestones
+        .get(milestone_index)
+        .ok_or(Error::MilestoneNotFound)?;
+    if !is_expense_milestone(env, escrow_id, milestone_index) {
+        return Err(Error::InvalidEscrowKind);
+    }
+    if milestone.status != MilestoneStatus::Pending {
+        return Err(Error::MilestoneAlreadyReleased);
+    }
+    // A disputed slice stays frozen until the arbiter rules on it
+    if env
+        .storage()
+        .persistent()
+        .has(&partial_dispute_key(escrow_id, milestone_index))
+    {
+        return Err(Error::AlreadyInDispute);
+    }
+    Ok(milestone)
+}
+
+/// Drops a milestone's expense marker with every claim filed against it
+pub(crate) fn remove_expense_claims(env: &Env, escrow_id: u64, milestone_index: u32) {
+    let storage = env.storage().persistent();
+    for claim_index in 0..claim_count(env, escrow_id, milestone_index) {
+        storage.remove(&claim_key(escrow_id, milestone_index, claim_index));
+    }
+    storage.remove(&claim_count_key(escrow_id, milestone_index));
+    storage.remove(&expense_milestone_key(escrow_id, milestone_index));
+}
+
+fn claim_count(env: &Env, escrow_id: u64, milestone_index: u32) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&claim_count_key(escrow_id, milestone_index))
+        .unwrap_or(0)
+}
+
+fn save_claim(
+    env: &Env,
+    escrow_id: u64,
+    milestone_index: u32,
+    claim_index: u32,
+    claim: &ExpenseClaim,
+) {
+    let key = claim_key(escrow_id, milestone_index, claim_index);
+    env.storage().persistent().set(&key, claim);
+    env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+}
@@ -0,0 +1,202 @@
+use crate::participants;
+use crate::settlement::{settle, Outcome};
+use crate::{
+    add_to_recipient_index, ensure_not_paused, ensure_recipient_changeable, extend_instance_ttl,
+    forget_deal, get_storage_key, has_disputed_milestone, record_deal, set_status,
+    shift_status_count, Error, Escrow, EscrowKind, EscrowStatus, MilestoneStatus, VaultixEscrow,
+    VaultixEscrowClient,
+};
+use soroban_sdk::{contractimpl, symbol_short, Address, Env, Symbol};
+
+#[cfg(any(test, feature = "testutils"))]
+use crate::__VaultixEscrow_fn_set_registry;
+
+#[contractimpl]
+impl VaultixEscrow {
+    /// Designates the program admin of a grant escrow. The depositor sets the
+    /// first admin before funding; afterwards only the current admin can hand
+    /// the role over.
+    pub fn set_program_admin(
+        env: Env,
+        escrow_id: u64,
+        program_admin: Address,
+    ) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        ensure_not_paused(&env)?;
+        let escrow = Self::get_escrow(env.clone(), escrow_id)?;
+        if escrow.kind != EscrowKind::Grant {
+            return Err(Error::InvalidEscrowKind);
+        }
+
+        let old_admin: Option<Address> = env
+            .storage()
+            .persistent()
+            .get(&program_admin_key(escrow_id));
+        match old_admin {
+            Some(ref current) => current.require_auth(),
+            None => {
+                escrow.authorize(escrow_id, &escrow.depositor);
+                if escrow.status != EscrowStatus::Created {
+                    return Err(Error::InvalidEscrowStatus);
+                }
+            }
+        }
+
+        let key = program_admin_key(escrow_id);
+        env.storage().persistent().set(&key, &program_admin);
+        env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "ProgramAdminUpdated"),
+                escrow_id,
+            ),
+            (old_admin, program_admin),
+        );
+
+        Ok(())
+    }
+
+    pub fn get_program_admin(env: Env, escrow_id: u64) -> Option<Address> {
+        env.storage()
+            .persistent()
+            .get(&program_admin_key(escrow_id))
+    }
+
+    /// Returns every unreleased grant milestone to the depositor and closes
+    /// the grant. Already released milestones stay with the grantee.
+    pub fn clawback_grant(env: Env, escrow_id: u64) -> Result<i128, Error> {
+        extend_instance_ttl(&env);
+        let storage_key = get_storage_key(escrow_id);
+        ensure_not_paused(&env)?;
+
+        let mut escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&storage_key)
+            .ok_or(Error::EscrowNotFound)?;
+        let program_admin = require_program_admin(&env, escrow_id, &escrow)?;
+
+        if escrow.status != EscrowStatus::Active && escrow.status != EscrowStatus::Created {
+            return Err(Error::InvalidEscrowStatus);
+        }
+        // Disputed funds are the arbiter's to settle
+        if has_disputed_milestone(&env, escrow_id, &escrow) {
+            return Err(Error::AlreadyInDispute);
+        }
+        // Held releases are already confirmed to the grantee and must pay
+        // out before the rest can be clawed back
+        if escrow
+            .milestones
+            .iter()
+            .any(|m| m.status == MilestoneStatus::Held)
+        {
+            return Err(Error::ChargebackWindowOpen);
+        }
+
+        let mut clawed_back = 0;
+        if escrow.status == EscrowStatus::Active {
+            clawed_back = escrow
+                .total_amount
+                .checked_sub(escrow.total_released)
+                .ok_or(Error::InvalidMilestoneAmount)?;
+            settle(
+                &env,
+                escrow_id,
+                &mut escrow,
+                None,
+                clawed_back,
+                Outcome::Refund,
+            )?;
+        }
+
+        set_status(&env, escrow_id, &mut escrow, EscrowStatus::Cancelled);
+        env.storage().persistent().set(&storage_key, &escrow);
+        env.storage()
+            .persistent()
+            .extend_ttl(&storage_key, 100, 2_000_000);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "GrantClawedBack"),
+                escrow_id,
+            ),
+            (program_admin, clawed_back),
+        );
+
+        Ok(clawed_back)
+    }
+
+    /// Moves the remaining grant to a new grantee, e.g. when the original
+    /// one abandons the project. The depositor must consent as well, and
+    /// grants created with `lock_recipient` cannot be reassigned.
+    pub fn reassign_recipient(
+        env: Env,
+        escrow_id: u64,
+        new_recipient: Address,
+    ) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        let storage_key = get_storage_key(escrow_id);
+        ensure_not_paused(&env)?;
+
+        let mut escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&storage_key)
+            .ok_or(Error::EscrowNotFound)?;
+        require_program_admin(&env, escrow_id, &escrow)?;
+
+        if escrow.status != EscrowStatus::Active && escrow.status != EscrowStatus::Created {
+            return Err(Error::InvalidEscrowStatus);
+        }
+        if new_recipient == escrow.depositor {
+            return Err(Error::SelfDealing);
+        }
+        ensure_recipient_changeable(&env, escrow_id, &escrow)?;
+        participants::ensure_participant(&env, &new_recipient)?;
+
+        let before = escrow.clone();
+        let old_recipient = escrow.recipient.clone();
+        escrow.recipient = new_recipient.clone();
+        forget_deal(&env, escrow_id, &before);
+        record_deal(&env, escrow_id, &escrow);
+        env.storage().persistent().set(&storage_key, &escrow);
+        env.storage()
+            .persistent()
+            .extend_ttl(&storage_key, 100, 2_000_000);
+        add_to_recipient_index(&env, &new_recipient, escrow_id);
+        shift_status_count(&env, &old_recipient, Some(escrow.status), None);
+        shift_status_count(&env, &new_recipient, None, Some(escrow.status));
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "RecipientReassigned"),
+                escrow_id,
+            ),
+            (old_recipient, new_recipient),
+        );
+
+        Ok(())
+    }
+}
+
+pub(crate) fn program_admin_key(escrow_id: u64) -> (Symbol, u64) {
+    (symbol_short!("prog_adm"), escrow_id)
+}
+
+/// Authorizes the grant's program admin and returns it
+fn require_program_admin(env: &Env, escrow_id: u64, escrow: &Escrow) -> Result<Address, Error> {
+    if escrow.kind != EscrowKind::Grant {
+        return Err(Error::InvalidEscrowKind);
+    }
+    let program_admin: Address = env
+        .storage()
+        .persistent()
+        .get(&program_admin_key(escrow_id))
+        .ok_or(Error::UnauthorizedAccess)?;
+    program_admin.require_auth();
+    Ok(program_admin)
+}
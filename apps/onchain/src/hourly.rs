@@ -0,0 +1,196 @@
+use crate::settlement::{settle, Outcome};
+use crate::{
+    ensure_not_paused, extend_instance_ttl, get_storage_key, Error, Escrow, EscrowKind,
+    EscrowStatus, MilestoneStatus, VaultixEscrow, VaultixEscrowClient, WorkLog,
+};
+use soroban_sdk::{contractimpl, symbol_short, BytesN, Env, Symbol};
+
+#[cfg(any(test, feature = "testutils"))]
+use crate::__VaultixEscrow_fn_set_registry;
+
+/// The single milestone of an hourly escrow: the funded pool that approved
+/// work logs are paid from
+const POOL: u32 = 0;
+
+#[contractimpl]
+impl VaultixEscrow {
+    /// Anchors a work log of `hours` at `rate` per hour against an hourly
+    /// escrow, returning its index. Everything logged, approved or not, is
+    /// capped at the funded amount.
+    pub fn log_work(
+        env: Env,
+        escrow_id: u64,
+        log_hash: BytesN<32>,
+        hours: u32,
+        rate: i128,
+    ) -> Result<u32, Error> {
+        extend_instance_ttl(&env);
+        ensure_not_paused(&env)?;
+        let escrow = Self::get_escrow(env.clone(), escrow_id)?;
+        escrow.authorize(escrow_id, &escrow.recipient);
+
+        if escrow.kind != EscrowKind::Hourly {
+            return Err(Error::InvalidEscrowKind);
+        }
+        if escrow.status != EscrowStatus::Active {
+            return Err(Error::EscrowNotActive);
+        }
+        if hours == 0 || rate <= 0 {
+            return Err(Error::ZeroAmount);
+        }
+        let amount = rate
+            .checked_mul(hours as i128)
+            .ok_or(Error::InvalidMilestoneAmount)?;
+        let logged = logged_total(&env, escrow_id)
+            .checked_add(amount)
+            .ok_or(Error::InvalidMilestoneAmount)?;
+        if logged > escrow.total_amount {
+            return Err(Error::InsufficientBalance);
+        }
+
+        let index = work_log_count(&env, escrow_id);
+        let log = WorkLog {
+            log_hash: log_hash.clone(),
+            hours,
+            rate,
+            approved: false,
+        };
+        save_work_log(&env, escrow_id, index, &log);
+        let count_key = work_log_count_key(escrow_id);
+        env.storage().persistent().set(&count_key, &(index + 1));
+        env.storage()
+            .persistent()
+            .extend_ttl(&count_key, 100, 2_000_000);
+        let logged_key = logged_total_key(escrow_id);
+        env.storage().persistent().set(&logged_key, &logged);
+        env.storage()
+            .persistent()
+            .extend_ttl(&logged_key, 100, 2_000_000);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "WorkLogged"),
+                escrow_id,
+                index,
+            ),
+            (log_hash, hours, rate),
+        );
+
+        Ok(index)
+    }
+
+    /// Approves a work log, releasing `hours * rate` from the pool to the
+    /// recipient like any milestone release
+    pub fn approve_work_log(env: Env, escrow_id: u64, log_index: u32) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        ensure_not_paused(&env)?;
+        let storage_key = get_storage_key(escrow_id);
+        let mut escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&storage_key)
+            .ok_or(Error::EscrowNotFound)?;
+        escrow.authorize(escrow_id, escrow.releaser());
+
+        if escrow.status != EscrowStatus::Active {
+            return Err(Error::EscrowNotActive);
+        }
+        let mut log = Self::get_work_log(env.clone(), escrow_id, log_index)
+            .ok_or(Error::MilestoneNotFound)?;
+        if log.approved {
+            return Err(Error::MilestoneAlreadyReleased);
+        }
+        let mut pool = escrow
+            .milestones
+            .get(POOL)
+            .ok_or(Error::MilestoneNotFound)?;
+        if pool.status != MilestoneStatus::Pending {
+            return Err(Error::InvalidEscrowStatus);
+        }
+
+        let amount = log.rate * log.hours as i128;
+        settle(
+            &env,
+            escrow_id,
+            &mut escrow,
+            Some(POOL),
+            amount,
+            Outcome::Release,
+        )?;
+        pool.amount -= amount;
+        if pool.amount == 0 {
+            pool.status = MilestoneStatus::Released;
+        }
+        escrow.milestones.set(POOL, pool);
+        env.storage().persistent().set(&storage_key, &escrow);
+        env.storage()
+            .persistent()
+            .extend_ttl(&storage_key, 100, 2_000_000);
+
+        log.approved = true;
+        save_work_log(&env, escrow_id, log_index, &log);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "WorkLogApproved"),
+                escrow_id,
+                log_index,
+            ),
+            amount,
+        );
+
+        Ok(())
+    }
+
+    /// Work log `log_index` of an hourly escrow; logs are numbered from zero
+    pub fn get_work_log(env: Env, escrow_id: u64, log_index: u32) -> Option<WorkLog> {
+        env.storage()
+            .persistent()
+            .get(&work_log_key(escrow_id, log_index))
+    }
+}
+
+fn work_log_key(escrow_id: u64, log_index: u32) -> (Symbol, u64, u32) {
+    (symbol_short!("work_log"), escrow_id, log_index)
+}
+
+fn work_log_count_key(escrow_id: u64) -> (Symbol, u64) {
+    (symbol_short!("work_logs"), escrow_id)
+}
+
+fn logged_total_key(escrow_id: u64) -> (Symbol, u64) {
+    (symbol_short!("logged"), escrow_id)
+}
+
+/// Drops every work log of an escrow along with its count and logged total
+pub(crate) fn remove_work_logs(env: &Env, escrow_id: u64) {
+    let storage = env.storage().persistent();
+    for log_index in 0..work_log_count(env, escrow_id) {
+        storage.remove(&work_log_key(escrow_id, log_index));
+    }
+    storage.remove(&work_log_count_key(escrow_id));
+    storage.remove(&logged_total_key(escrow_id));
+}
+
+fn work_log_count(env: &Env, escrow_id: u64) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&work_log_count_key(escrow_id))
+        .unwrap_or(0)
+}
+
+/// Value of every work log anchored so far, approved or not
+fn logged_total(env: &Env, escrow_id: u64) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&logged_total_key(escrow_id))
+        .unwrap_or(0)
+}
+
+fn save_work_log(env: &Env, escrow_id: u64, log_index: u32, log: &WorkLog) {
+    let key = work_log_key(escrow_id, log_index);
+    env.storage().persistent().set(&key, log);
+    env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+}
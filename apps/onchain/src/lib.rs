@@ -1,9 +1,32 @@
 #![no_std]
 #![allow(unexpected_cfgs)]
+#![allow(clippy::too_many_arguments)]
+
+mod archive;
+mod criteria;
+#[cfg(feature = "disputes")]
+mod disputes;
+mod expenses;
+mod grants;
+mod hourly;
+mod merge;
+mod migration;
+mod notifications;
+mod participants;
+mod promos;
+mod quarantine;
+mod referrals;
+mod settlement;
+mod unclaimed;
+#[cfg(feature = "views-extended")]
+mod views;
+
+use settlement::{settle, Outcome};
 use soroban_sdk::{
-    contract, contracterror, contractimpl, contracttype, symbol_short, token, Address, Env, Symbol,
-    Vec,
+    contract, contractclient, contracterror, contractimpl, contracttype, symbol_short, token, vec,
+    xdr::ToXdr, Address, Bytes, BytesN, Env, String, Symbol, Vec,
 };
+use vaultix_core::fee::{is_valid_bps, DEFAULT_FEE_BPS};
 
 #[contracttype]
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -11,6 +34,8 @@ pub enum MilestoneStatus {
     Pending,
     Released,
     Disputed,
+    SettledOffChain, // Paid outside the contract, closed by an attestor
+    Held,            // Confirmed, claimable once the chargeback window passes
 }
 
 #[contracttype]
@@ -40,6 +65,16 @@ pub enum Resolution {
     Recipient,
 }
 
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EscrowKind {
+    Standard,        // Depositor pays the recipient as milestones are met
+    RefundGuarantee, // Seller locks a guarantee the buyer can claim after the deadline
+    Swap,            // Both parties deposit; each release moves assets in both directions
+    Grant,           // A program admin can claw back unreleased funds or reassign the grantee
+    Hourly,          // One funded pool, paid out as the depositor approves logged hours
+}
+
 #[contracttype]
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum ContractState {
@@ -59,6 +94,547 @@ pub struct Escrow {
     pub status: EscrowStatus,
     pub deadline: u64,
     pub resolution: Resolution,
+    pub kind: EscrowKind,
+    /// Platform fee in effect at creation; later `update_fee` calls do not
+    /// change it
+    pub fee_bps: i128,
+    /// Who may act for the depositor, fixed at creation
+    pub auth_mode: AuthMode,
+}
+
+impl Escrow {
+    /// Requires `party` to authorize the current call. Every entrypoint
+    /// checks a party through here so the escrow's `auth_mode` applies
+    /// uniformly to the depositor's actions.
+    ///
+    /// Since transactions may be submitted by a relayer, the addresses that
+    /// actually signed for `party` are published as `PartyAuthorized`.
+    fn authorize(&self, escrow_id: u64, party: &Address) {
+        let env = party.env();
+        let signers = self.signers(party);
+        for signer in signers.iter() {
+            signer.require_auth();
+        }
+
+        env.events().publish(
+            (
+                Symbol::new(env, "Vaultix"),
+                Symbol::new(env, "PartyAuthorized"),
+                escrow_id,
+            ),
+            (party.clone(), signers),
+        );
+    }
+
+    /// Addresses that must sign for `party` under the escrow's auth mode
+    fn signers(&self, party: &Address) -> Vec<Address> {
+        let mut signers = Vec::new(party.env());
+        if *party != self.depositor {
+            signers.push_back(party.clone());
+        } else {
+            match self.auth_mode {
+                AuthMode::Strict => signers.push_back(self.depositor.clone()),
+                AuthMode::Delegated(ref operator) => signers.push_back(operator.clone()),
+                AuthMode::Multisig(ref co_signers) => {
+                    signers.push_back(self.depositor.clone());
+                    signers.append(co_signers);
+                }
+            }
+        }
+        signers
+    }
+
+    /// Whether `addr` is one of the signers `authorize` requires for `party`
+    #[cfg_attr(not(feature = "views-extended"), allow(dead_code))]
+    fn signs_for(&self, addr: &Address, party: &Address) -> bool {
+        self.signers(party).contains(addr)
+    }
+
+    /// Party whose approval releases a milestone. In a refund guarantee the
+    /// roles are inverted: the buyer (`recipient`) signs off on delivery.
+    fn releaser(&self) -> &Address {
+        match self.kind {
+            EscrowKind::Standard | EscrowKind::Swap | EscrowKind::Grant | EscrowKind::Hourly => {
+                &self.depositor
+            }
+            EscrowKind::RefundGuarantee => &self.recipient,
+        }
+    }
+
+    /// Party a released milestone pays out to
+    fn payee(&self) -> &Address {
+        match self.kind {
+            EscrowKind::Standard | EscrowKind::Swap | EscrowKind::Grant | EscrowKind::Hourly => {
+                &self.recipient
+            }
+            EscrowKind::RefundGuarantee => &self.depositor,
+        }
+    }
+}
+
+/// Hash of the off-chain agreement bound to an escrow. The recipient accepts
+/// it by signing `accept_terms`; funding is blocked until then.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Terms {
+    pub hash: BytesN<32>,
+    pub accepted: bool,
+}
+
+/// Recipient's side of a swap escrow. `amounts[i]` is paid to the depositor
+/// when milestone `i` is released; a token with unit supply (an NFT) can be
+/// bartered as a single milestone of amount 1.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct CounterLeg {
+    pub token_address: Address,
+    pub amounts: Vec<i128>,
+    pub total_amount: i128,
+}
+
+/// Escrow variant and schedule settings for `create_escrow`, grouped into one
+/// argument to stay within Soroban's limit on contract function parameters.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct EscrowOptions {
+    pub kind: EscrowKind,
+    /// Earliest time milestone 0 can be released
+    pub cliff_until: Option<u64>,
+    /// Where refunds go, e.g. a cold wallet instead of the funding one
+    pub refund_to: RefundTo,
+    /// Integrator referral code that earns a share of this escrow's fees
+    pub referral: Referral,
+    /// Forbids ever changing the recipient, even with the depositor's consent
+    pub lock_recipient: bool,
+    /// Who may act for the depositor once the escrow exists
+    pub auth_mode: AuthMode,
+    /// Whether releases pay out at once or in tranches over time
+    pub release_schedule: ReleaseSchedule,
+    /// Whether a disputed milestone halts payouts of the other milestones
+    pub dispute_policy: DisputePolicy,
+    /// Permits an escrow identical to an open one (same parties, token and
+    /// milestones), which is otherwise taken for a client retry and refused
+    pub allow_duplicate: bool,
+    /// Who bears the platform fee on releases
+    pub fee_payer: FeePayer,
+    /// Discounts the escrow's platform fee
+    pub promo: Promo,
+}
+
+/// Refund destination chosen at creation. An enum rather than an
+/// `Option<Address>` field, which contract types cannot hold.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum RefundTo {
+    Depositor,
+    Address(Address),
+}
+
+/// How the depositor's actions on an escrow are authorized
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum AuthMode {
+    /// Only the depositor's own signature counts
+    Strict,
+    /// An operator signs on the depositor's behalf, so the depositor key
+    /// can stay in cold storage after funding
+    Delegated(Address),
+    /// The depositor and every listed co-signer must all sign
+    Multisig(Vec<Address>),
+}
+
+/// How each milestone payout reaches the payee
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReleaseSchedule {
+    Immediate,
+    /// Split every payout into `count` equal tranches, one claimable right
+    /// away and the next each `interval` seconds later. Limits the damage of
+    /// an erroneous approval and spreads selling of thin tokens.
+    Tranches(u32, u64),
+}
+
+/// What happens to the rest of an escrow while one of its milestones is in
+/// dispute. Either way the escrow cannot be cancelled or completed until the
+/// dispute is resolved.
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DisputePolicy {
+    /// Undisputed milestones keep releasing as usual
+    ContinueOthers,
+    /// No milestone pays out until every dispute is resolved
+    FreezeAll,
+}
+
+/// Who bears the platform fee on an escrow's releases. The depositor's
+/// share of the fee on every milestone is reserved at creation and paid in
+/// on top of the total at funding; whatever is left of it is refunded when
+/// the escrow closes.
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FeePayer {
+    /// Deducted from each payout
+    Recipient,
+    /// Paid from the depositor's reserve, so payouts arrive in full
+    Depositor,
+    /// Halved, with the recipient's half rounded up
+    Split,
+}
+
+/// Who bears an escrow's platform fee and how much of the depositor's fee
+/// reserve is still unspent
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeeSplit {
+    pub payer: FeePayer,
+    pub depositor_reserve: i128,
+}
+
+/// How a settlement moved an escrow's funds, as recorded on its receipt
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReceiptKind {
+    Release,
+    Confirmed,
+    Award,
+    Refund,
+}
+
+/// Compact record of one settlement, kept in temporary storage for
+/// `settlement::RECEIPT_TTL` ledgers so wallets can confirm it without an
+/// indexer. `net` is what the payee's side is owed after its share of the
+/// fee; tranche schedules may defer part of it.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Receipt {
+    pub kind: ReceiptKind,
+    pub milestone_index: Option<u32>,
+    pub payee: Address,
+    pub amount: i128,
+    pub fee: i128,
+    pub net: i128,
+    pub ledger: u32,
+    pub timestamp: u64,
+}
+
+/// Referral code attached at creation, if any
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Referral {
+    None,
+    Code(Symbol),
+}
+
+/// Promo code redeemed at creation for a fee discount, if any
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Promo {
+    None,
+    Code(Symbol),
+}
+
+impl EscrowOptions {
+    /// Options for an escrow of `kind` without a cliff or refund address
+    pub fn new(kind: EscrowKind) -> Self {
+        EscrowOptions {
+            kind,
+            cliff_until: None,
+            refund_to: RefundTo::Depositor,
+            referral: Referral::None,
+            lock_recipient: false,
+            auth_mode: AuthMode::Strict,
+            release_schedule: ReleaseSchedule::Immediate,
+            dispute_policy: DisputePolicy::ContinueOthers,
+            allow_duplicate: false,
+            fee_payer: FeePayer::Recipient,
+            promo: Promo::None,
+        }
+    }
+}
+
+/// Running totals of what an address moved through the contract
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AddressTotals {
+    pub deposited: i128,
+    pub received: i128,
+    pub refunded: i128,
+    /// Protocol fees withheld from the address's release payouts
+    pub fees_paid: i128,
+}
+
+/// Where an escrow's funds went, so clients can reconcile without replaying
+/// events. `released_to_recipient` is what release payouts delivered (to the
+/// depositor for a refund guarantee); `claimable_outstanding` is released
+/// but not yet delivered: held, tranched or undeliverable payouts, and
+/// refunds waiting in quarantine.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct EscrowAccounting {
+    pub deposited: i128,
+    pub released_to_recipient: i128,
+    pub fees_paid: i128,
+    pub refunded_to_depositor: i128,
+    pub claimable_outstanding: i128,
+}
+
+/// What settling one milestone would move right now, by release or by
+/// refund. `release_*` are zero when the milestone cannot be released now;
+/// `release_paid_now` is the part of the payout not deferred into tranches.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SettlementProjection {
+    pub milestone_index: u32,
+    pub status: MilestoneStatus,
+    pub releasable: bool,
+    pub release_amount: i128,
+    pub release_fee: i128,
+    pub release_payout: i128,
+    pub release_paid_now: i128,
+    pub refund_amount: i128,
+}
+
+/// Token amount split at the token's decimals, e.g. 12.5 USDC (6 decimals)
+/// is `units: 12, fraction: 500_000`
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct DisplayAmount {
+    pub units: i128,
+    pub fraction: i128,
+}
+
+/// Escrow totals scaled for display with the token's cached decimals
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct EscrowSummary {
+    pub status: EscrowStatus,
+    pub token_address: Address,
+    pub decimals: u32,
+    pub total: DisplayAmount,
+    pub released: DisplayAmount,
+    pub remaining: DisplayAmount,
+}
+
+/// Number of escrows an address is party to, by status
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct StatusCounts {
+    pub created: u32,
+    pub active: u32,
+    pub completed: u32,
+    pub cancelled: u32,
+    pub disputed: u32,
+    pub resolved: u32,
+}
+
+impl StatusCounts {
+    fn of(&mut self, status: EscrowStatus) -> &mut u32 {
+        match status {
+            EscrowStatus::Created => &mut self.created,
+            EscrowStatus::Active => &mut self.active,
+            EscrowStatus::Completed => &mut self.completed,
+            EscrowStatus::Cancelled => &mut self.cancelled,
+            EscrowStatus::Disputed => &mut self.disputed,
+            EscrowStatus::Resolved => &mut self.resolved,
+        }
+    }
+}
+
+/// Activity summary for an address across every escrow it is party to
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AddressStats {
+    pub totals: AddressTotals,
+    pub counts: StatusCounts,
+}
+
+/// Ledger thresholds used when bumping the contract instance's TTL
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct InstanceTtl {
+    pub threshold: u32,
+    pub extend_to: u32,
+}
+
+/// Entry of the platform fee history: `fee_bps` applies to escrows created
+/// at or after `effective_ledger`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeeChange {
+    pub fee_bps: i128,
+    pub effective_ledger: u32,
+}
+
+/// Integrator-registered referral code and how often it was redeemed
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReferralCode {
+    pub referrer: Address,
+    pub uses: u32,
+}
+
+/// Hours a recipient logged against an hourly escrow, anchored by the hash
+/// of the off-chain work log
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct WorkLog {
+    pub log_hash: BytesN<32>,
+    pub hours: u32,
+    pub rate: i128,
+    pub approved: bool,
+}
+
+/// Reimbursable expense filed against an expense milestone, anchored by
+/// the hash of its receipt
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExpenseClaim {
+    pub receipt_hash: BytesN<32>,
+    pub amount: i128,
+    pub approved: bool,
+}
+
+/// Acceptance criterion of a milestone, checked off by the payee as
+/// delivered and by the approving party as accepted
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Criterion {
+    pub hash: BytesN<32>,
+    pub delivered: bool,
+    pub accepted: bool,
+}
+
+/// Treasury-issued fee discount, redeemable at escrow creation until it
+/// expires or runs out of uses
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PromoCode {
+    pub discount_bps: i128,
+    pub expires_at: u64,
+    pub max_uses: u32,
+    pub uses: u32,
+}
+
+/// Confirmed release waiting out the chargeback window
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeldRelease {
+    pub amount: i128,
+    /// Milestone status once paid (`Disputed` if a slice is still frozen)
+    pub next_status: MilestoneStatus,
+    pub claimable_at: u64,
+}
+
+/// Marks a token as a stablecoin whose releases pause when `oracle` prices
+/// it more than `band_bps` away from `peg`
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct StablecoinGuard {
+    pub oracle: Address,
+    pub peg: i128,
+    pub band_bps: i128,
+}
+
+/// Payout the token refused to deliver, held for the payee to claim later
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnclaimedPayout {
+    pub payee: Address,
+    pub amount: i128,
+    /// Time of the latest diversion, from which the escheat period runs
+    pub since: u64,
+}
+
+/// Where an address wants its alerts routed, read by off-chain notifiers
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum NotificationHint {
+    /// Hash of an encrypted contact blob held off-chain
+    Contact(BytesN<32>),
+    /// Notifier contract the address prefers
+    Notifier(Address),
+}
+
+/// Settlement leg a token transfer was making
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TransferLeg {
+    Refund,
+    Treasury,
+    Referral,
+    Burn,
+    CounterLeg,
+    FeeReserve,
+}
+
+/// Settlement transfer the token refused, held for `retry_transfer`
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuarantinedTransfer {
+    pub escrow_id: u64,
+    pub leg: TransferLeg,
+    pub token: Address,
+    pub to: Address,
+    pub amount: i128,
+    pub since: u64,
+}
+
+/// Unpaid tranches of a milestone payout, claimable by anyone as they fall due
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrancheRelease {
+    /// Payee when the milestone was released; later reassignment does not
+    /// redirect tranches already earned
+    pub payee: Address,
+    pub per_tranche: i128,
+    pub remaining: i128,
+    pub tranches_left: u32,
+    pub interval: u64,
+    pub next_claimable_at: u64,
+}
+
+/// Compact terminal record left behind by `archive_escrow`. The full escrow
+/// can still be proven by matching the SHA-256 of its XDR to `state_hash`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArchivedEscrow {
+    pub state_hash: BytesN<32>,
+    pub depositor: Address,
+    pub recipient: Address,
+    pub token_address: Address,
+    pub total_amount: i128,
+    pub total_released: i128,
+    pub status: EscrowStatus,
+    pub closed_at: u64,
+}
+
+/// Budget of releases a depositor pre-approved for an escrow
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReleaseAllowance {
+    pub releases_left: u32,
+    pub amount_left: i128,
+}
+
+/// Static description of the deployed contract, used by SDKs to
+/// feature-detect at runtime.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContractInfo {
+    pub name: Symbol,
+    pub version: String,
+    pub interface_version: u32,
+    pub features: Vec<Symbol>,
+}
+
+/// Actions an address could successfully perform on an escrow right now
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuthorizedActions {
+    pub release: bool,
+    pub cancel: bool,
+    pub dispute: bool,
+    pub claim: bool,
 }
 
 #[contracterror]
@@ -87,10 +663,119 @@ pub enum Error {
     AlreadyInDispute = 21,
     InvalidWinner = 22,
     ContractPaused = 23,
+    ExternalRefAlreadyExists = 24,
+    AttestorNotInitialized = 25,
+    DuplicateMilestoneLabel = 26,
+    TermsNotAccepted = 27,
+    TermsNotFound = 28,
+    DeadlineNotReached = 29,
+    InvalidEscrowKind = 30,
+    CounterLegNotFunded = 31,
+    CommitmentMismatch = 32,
+    MilestoneSealed = 33,
+    TooManyActiveEscrows = 34,
+    AllowanceExceeded = 35,
+    CliffNotReached = 36,
+    InvalidMilestoneWeights = 37,
+    InvalidTtlConfig = 38,
+    RetentionNotElapsed = 39,
+    ChargebackWindowOpen = 40,
+    ReferralCodeTaken = 41,
+    ReferralCodeNotFound = 42,
+    RecipientLocked = 43,
+    InvalidAuthMode = 44,
+    TvlCapExceeded = 45,
+    InvalidTrancheSchedule = 46,
+    TrancheNotDue = 47,
+    DepegDetected = 48,
+    ConverterNotAllowed = 49,
+    TokenMismatch = 50,
+}
+
+impl From<vaultix_core::Error> for Error {
+    fn from(e: vaultix_core::Error) -> Self {
+        use vaultix_core::Error as Core;
+        match e {
+            Core::EscrowNotFound => Error::EscrowNotFound,
+            Core::EscrowAlreadyExists => Error::EscrowAlreadyExists,
+            Core::MilestoneNotFound => Error::MilestoneNotFound,
+            Core::MilestoneAlreadyReleased => Error::MilestoneAlreadyReleased,
+            Core::UnauthorizedAccess => Error::UnauthorizedAccess,
+            Core::InvalidMilestoneAmount => Error::InvalidMilestoneAmount,
+            Core::EscrowNotActive => Error::EscrowNotActive,
+            Core::VectorTooLarge => Error::VectorTooLarge,
+            Core::ZeroAmount => Error::ZeroAmount,
+            Core::SelfDealing => Error::SelfDealing,
+            Core::EscrowAlreadyFunded => Error::EscrowAlreadyFunded,
+            Core::InvalidFeeConfiguration => Error::InvalidFeeConfiguration,
+            Core::InvalidEscrowStatus => Error::InvalidEscrowStatus,
+            Core::AlreadyInDispute => Error::AlreadyInDispute,
+            Core::InvalidWinner => Error::InvalidWinner,
+            Core::ContractPaused => Error::ContractPaused,
+        }
+    }
+}
+
+const INTERFACE_VERSION: u32 = 1;
+
+/// Default instance TTL policy: once fewer than `threshold` ledgers remain,
+/// write paths push the instance back out to `extend_to` ledgers.
+const DEFAULT_INSTANCE_TTL_THRESHOLD: u32 = 100_000;
+const DEFAULT_INSTANCE_TTL_EXTEND_TO: u32 = 2_000_000;
+
+const MAX_BUMP_BATCH: u32 = 50;
+
+/// Upper bound on multisig co-signers, since each one is an auth check on
+/// every depositor action
+const MAX_CO_SIGNERS: u32 = 10;
+
+/// Upper bound on tranches per payout
+const MAX_TRANCHES: u32 = 52;
+
+/// Interface of an external fee-strategy contract. When one is configured,
+/// its quote replaces the built-in bps fee for every release.
+#[contractclient(name = "FeeStrategyClient")]
+pub trait FeeStrategy {
+    fn quote(env: Env, escrow: Escrow, milestone: Milestone) -> i128;
+}
+
+/// Interface of a price oracle used by the stablecoin depeg guard. Prices
+/// use the fixed-point scale the guard's `peg` is expressed in.
+#[contractclient(name = "PriceOracleClient")]
+pub trait PriceOracle {
+    fn price(env: Env, token: Address) -> i128;
+}
+
+/// Interface of a token converter allowlisted for issuer migrations. The
+/// escrow sends `amount` of `from` to the converter first; the converter
+/// must then pay the `to` equivalent out to `recipient`.
+#[contractclient(name = "TokenConverterClient")]
+pub trait TokenConverter {
+    fn convert(env: Env, from: Address, to: Address, amount: i128, recipient: Address) -> i128;
+}
+
+/// Escrow action a policy engine is asked to approve
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PolicyAction {
+    Create,
+    Release,
 }
 
-const DEFAULT_FEE_BPS: i128 = 50;
-const BPS_DENOMINATOR: i128 = 10000;
+/// Interface of an external policy engine, e.g. jurisdiction rules of a
+/// regulated operator. When one is configured it must approve every escrow
+/// creation and voluntary release; `parties` are the depositor and the
+/// recipient, in that order.
+#[contractclient(name = "PolicyEngineClient")]
+pub trait PolicyEngine {
+    fn check(
+        env: Env,
+        action: PolicyAction,
+        parties: Vec<Address>,
+        token: Address,
+        amount: i128,
+    ) -> bool;
+}
 
 #[contract]
 pub struct VaultixEscrow;
@@ -98,11 +783,12 @@ pub struct VaultixEscrow;
 #[contractimpl]
 impl VaultixEscrow {
     pub fn initialize(env: Env, treasury: Address, fee_bps: Option<i128>) -> Result<(), Error> {
+        extend_instance_ttl(&env);
         treasury.require_auth();
 
         let fee = fee_bps.unwrap_or(DEFAULT_FEE_BPS);
 
-        if !(0..=BPS_DENOMINATOR).contains(&fee) {
+        if !is_valid_bps(fee) {
             return Err(Error::InvalidFeeConfiguration);
         }
 
@@ -112,6 +798,7 @@ impl VaultixEscrow {
         env.storage()
             .instance()
             .set(&symbol_short!("fee_bps"), &fee);
+        record_fee_change(&env, fee);
 
         let vaultix_topic = Symbol::new(&env, "Vaultix");
 
@@ -139,7 +826,25 @@ impl VaultixEscrow {
         Ok(())
     }
 
+    pub fn get_contract_info(env: Env) -> ContractInfo {
+        let mut features = Vec::new(&env);
+        if cfg!(feature = "disputes") {
+            features.push_back(symbol_short!("disputes"));
+        }
+        if cfg!(feature = "views-extended") {
+            features.push_back(Symbol::new(&env, "views_extended"));
+        }
+
+        ContractInfo {
+            name: Symbol::new(&env, "VaultixEscrow"),
+            version: String::from_str(&env, env!("CARGO_PKG_VERSION")),
+            interface_version: INTERFACE_VERSION,
+            features,
+        }
+    }
+
     pub fn update_fee(env: Env, new_fee_bps: i128) -> Result<(), Error> {
+        extend_instance_ttl(&env);
         let treasury: Address = env
             .storage()
             .instance()
@@ -147,7 +852,7 @@ impl VaultixEscrow {
             .ok_or(Error::TreasuryNotInitialized)?;
         treasury.require_auth();
 
-        if !(0..=BPS_DENOMINATOR).contains(&new_fee_bps) {
+        if !is_valid_bps(new_fee_bps) {
             return Err(Error::InvalidFeeConfiguration);
         }
 
@@ -159,6 +864,7 @@ impl VaultixEscrow {
         env.storage()
             .instance()
             .set(&symbol_short!("fee_bps"), &new_fee_bps);
+        record_fee_change(&env, new_fee_bps);
 
         env.events().publish(
             (
@@ -176,21 +882,62 @@ impl VaultixEscrow {
         Ok(())
     }
 
-    pub fn get_config(env: Env) -> Result<(Address, i128), Error> {
+    /// Configures the share of every collected fee (in bps of the fee) that is
+    /// burned. When `burn_address` is set the share is sent there instead,
+    /// for tokens whose contract does not support burning.
+    pub fn set_burn_config(
+        env: Env,
+        burn_bps_of_fee: i128,
+        burn_address: Option<Address>,
+    ) -> Result<(), Error> {
+        extend_instance_ttl(&env);
         let treasury: Address = env
             .storage()
             .instance()
             .get(&symbol_short!("treasury"))
             .ok_or(Error::TreasuryNotInitialized)?;
-        let fee_bps: i128 = env
+        treasury.require_auth();
+
+        if !is_valid_bps(burn_bps_of_fee) {
+            return Err(Error::InvalidFeeConfiguration);
+        }
+
+        let old_burn_bps: i128 = env
             .storage()
             .instance()
-            .get(&symbol_short!("fee_bps"))
-            .unwrap_or(DEFAULT_FEE_BPS);
-        Ok((treasury, fee_bps))
+            .get(&symbol_short!("burn_bps"))
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&symbol_short!("burn_bps"), &burn_bps_of_fee);
+        match burn_address {
+            Some(ref addr) => env
+                .storage()
+                .instance()
+                .set(&symbol_short!("burn_addr"), addr),
+            None => env.storage().instance().remove(&symbol_short!("burn_addr")),
+        }
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "FeeUpdated"),
+            ),
+            (
+                Symbol::new(&env, "Global"),
+                Symbol::new(&env, "BurnShare"),
+                old_burn_bps,
+                burn_bps_of_fee,
+            ),
+        );
+
+        Ok(())
     }
 
-    pub fn set_paused(env: Env, paused: bool) -> Result<(), Error> {
+    /// Routes fee quotes to an external strategy contract, or back to the
+    /// built-in bps fee when `strategy` is `None`.
+    pub fn set_fee_strategy(env: Env, strategy: Option<Address>) -> Result<(), Error> {
+        extend_instance_ttl(&env);
         let treasury: Address = env
             .storage()
             .instance()
@@ -198,509 +945,2669 @@ impl VaultixEscrow {
             .ok_or(Error::TreasuryNotInitialized)?;
         treasury.require_auth();
 
-        let state = if paused {
-            ContractState::Paused
-        } else {
-            ContractState::Active
-        };
-        env.storage()
-            .instance()
-            .set(&symbol_short!("state"), &state);
+        match strategy {
+            Some(ref addr) => env
+                .storage()
+                .instance()
+                .set(&symbol_short!("fee_strat"), addr),
+            None => env.storage().instance().remove(&symbol_short!("fee_strat")),
+        }
 
         env.events().publish(
             (
                 Symbol::new(&env, "Vaultix"),
-                Symbol::new(&env, "PausedStateChanged"),
+                Symbol::new(&env, "FeeStrategyUpdated"),
             ),
-            (paused, treasury),
+            strategy,
         );
 
         Ok(())
     }
 
-    pub fn init(env: Env, admin: Address) -> Result<(), Error> {
-        if env.storage().persistent().has(&admin_storage_key()) {
-            return Err(Error::AlreadyInitialized);
-        }
-
+    /// Sets or clears the policy engine consulted on creation and release
+    pub fn set_policy(env: Env, policy: Option<Address>) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        let admin = get_admin(&env)?;
         admin.require_auth();
-        env.storage().persistent().set(&admin_storage_key(), &admin);
+
+        match policy {
+            Some(ref addr) => env.storage().instance().set(&policy_key(), addr),
+            None => env.storage().instance().remove(&policy_key()),
+        }
 
         env.events().publish(
             (
                 Symbol::new(&env, "Vaultix"),
-                Symbol::new(&env, "RoleUpdated"),
-                Symbol::new(&env, "Admin"),
+                Symbol::new(&env, "PolicyUpdated"),
             ),
-            (Option::<Address>::None, admin),
+            policy,
         );
 
         Ok(())
     }
 
-    pub fn create_escrow(
-        env: Env,
-        escrow_id: u64,
-        depositor: Address,
-        recipient: Address,
-        token_address: Address,
-        milestones: Vec<Milestone>,
-        deadline: u64,
-    ) -> Result<(), Error> {
-        depositor.require_auth();
-        ensure_not_paused(&env)?;
-
-        if depositor == recipient {
-            return Err(Error::SelfDealing);
-        }
-
-        let storage_key = get_storage_key(escrow_id);
-        if env.storage().persistent().has(&storage_key) {
-            return Err(Error::EscrowAlreadyExists);
-        }
+    pub fn get_policy(env: Env) -> Option<Address> {
+        env.storage().instance().get(&policy_key())
+    }
 
-        let total_amount = validate_milestones(&milestones)?;
+    /// Publishes the ed25519 public key the off-chain notifier signs webhook
+    /// payloads with, or withdraws it with `None`
+    pub fn set_notifier_key(env: Env, key: Option<BytesN<32>>) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        let admin = get_admin(&env)?;
+        admin.require_auth();
 
-        let mut initialized_milestones = Vec::new(&env);
-        for milestone in milestones.iter() {
-            let mut m = milestone.clone();
-            m.status = MilestoneStatus::Pending;
-            initialized_milestones.push_back(m);
+        match key {
+            Some(ref public_key) => env
+                .storage()
+                .instance()
+                .set(&notifier_key_key(), public_key),
+            None => env.storage().instance().remove(&notifier_key_key()),
         }
 
-        let escrow = Escrow {
-            depositor: depositor.clone(),
-            recipient: recipient.clone(),
-            token_address: token_address.clone(),
-            total_amount,
-            total_released: 0,
-            milestones: initialized_milestones,
-            status: EscrowStatus::Created,
-            deadline,
-            resolution: Resolution::None,
-        };
-
-        env.storage().persistent().set(&storage_key, &escrow);
-        env.storage()
-            .persistent()
-            .extend_ttl(&storage_key, 100, 2_000_000);
-
-        // Standardized Event
         env.events().publish(
             (
                 Symbol::new(&env, "Vaultix"),
-                Symbol::new(&env, "EscrowCreated"),
-                escrow_id,
+                Symbol::new(&env, "NotifierKeyUpdated"),
             ),
-            (depositor, recipient, token_address, total_amount, deadline),
+            key,
         );
 
         Ok(())
     }
 
-    pub fn deposit_funds(env: Env, escrow_id: u64) -> Result<(), Error> {
-        let storage_key = get_storage_key(escrow_id);
-        ensure_not_paused(&env)?;
+    /// Key webhook recipients verify `X-Vaultix-Notifier-Signature` against
+    pub fn get_notifier_key(env: Env) -> Option<BytesN<32>> {
+        env.storage().instance().get(&notifier_key_key())
+    }
 
-        let mut escrow: Escrow = env
+    pub fn get_fee_strategy(env: Env) -> Option<Address> {
+        env.storage().instance().get(&symbol_short!("fee_strat"))
+    }
+
+    pub fn get_config(env: Env) -> Result<(Address, i128), Error> {
+        let treasury: Address = env
             .storage()
-            .persistent()
-            .get(&storage_key)
-            .ok_or(Error::EscrowNotFound)?;
-        escrow.depositor.require_auth();
+            .instance()
+            .get(&symbol_short!("treasury"))
+            .ok_or(Error::TreasuryNotInitialized)?;
+        Ok((treasury, current_fee_bps(&env)))
+    }
 
-        if escrow.status != EscrowStatus::Created {
-            return Err(Error::EscrowAlreadyFunded);
+    /// Platform fees ever configured, oldest first. `start` is the index of
+    /// the first change returned and at most `limit` (capped at 50) are
+    /// returned.
+    pub fn get_fee_history(env: Env, start: u32, limit: u32) -> Vec<FeeChange> {
+        let count: u32 = env
+            .storage()
+            .instance()
+            .get(&fee_change_count_key())
+            .unwrap_or(0);
+        let end = start.saturating_add(limit.min(50)).min(count);
+
+        let mut history = Vec::new(&env);
+        for index in start..end {
+            if let Some(change) = env.storage().persistent().get(&fee_change_key(index)) {
+                history.push_back(change);
+            }
         }
+        history
+    }
 
-        let token_client = token::Client::new(&env, &escrow.token_address);
-        token_client.transfer_from(
-            &env.current_contract_address(),
-            &escrow.depositor,
-            &env.current_contract_address(),
-            &escrow.total_amount,
-        );
+    pub fn set_paused(env: Env, paused: bool) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        let treasury: Address = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("treasury"))
+            .ok_or(Error::TreasuryNotInitialized)?;
+        treasury.require_auth();
 
-        escrow.status = EscrowStatus::Active;
-        env.storage().persistent().set(&storage_key, &escrow);
+        let state = if paused {
+            ContractState::Paused
+        } else {
+            ContractState::Active
+        };
         env.storage()
-            .persistent()
-            .extend_ttl(&storage_key, 100, 2_000_000);
+            .instance()
+            .set(&symbol_short!("state"), &state);
 
-        // Standardized Event
         env.events().publish(
             (
                 Symbol::new(&env, "Vaultix"),
-                Symbol::new(&env, "EscrowFunded"),
-                escrow_id,
+                Symbol::new(&env, "PausedStateChanged"),
             ),
-            escrow.total_amount,
+            (paused, treasury),
         );
 
         Ok(())
     }
 
-    pub fn get_escrow(env: Env, escrow_id: u64) -> Result<Escrow, Error> {
-        let storage_key = get_storage_key(escrow_id);
-        env.storage()
-            .persistent()
-            .get(&storage_key)
-            .ok_or(Error::EscrowNotFound)
-    }
-
-    pub fn get_state(env: Env, escrow_id: u64) -> Result<EscrowStatus, Error> {
-        let escrow = Self::get_escrow(env, escrow_id)?;
-        Ok(escrow.status)
-    }
-
-    pub fn release_milestone(env: Env, escrow_id: u64, milestone_index: u32) -> Result<(), Error> {
-        let storage_key = get_storage_key(escrow_id);
-        ensure_not_paused(&env)?;
-
-        let mut escrow: Escrow = env
-            .storage()
-            .persistent()
-            .get(&storage_key)
-            .ok_or(Error::EscrowNotFound)?;
-        escrow.depositor.require_auth();
-
-        if escrow.status != EscrowStatus::Active {
-            return Err(Error::EscrowNotActive);
-        }
-        if milestone_index >= escrow.milestones.len() {
-            return Err(Error::MilestoneNotFound);
-        }
-
-        let mut milestone = escrow
-            .milestones
-            .get(milestone_index)
-            .ok_or(Error::MilestoneNotFound)?;
-        if milestone.status == MilestoneStatus::Released {
-            return Err(Error::MilestoneAlreadyReleased);
+    pub fn init(env: Env, admin: Address) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        if env.storage().persistent().has(&admin_storage_key()) {
+            return Err(Error::AlreadyInitialized);
         }
 
-        let (treasury, fee_bps) = Self::get_config(env.clone())?;
-        let fee = calculate_fee(milestone.amount, fee_bps)?;
-        let payout = milestone
-            .amount
-            .checked_sub(fee)
-            .ok_or(Error::InvalidMilestoneAmount)?;
-
-        let token_client = token::Client::new(&env, &escrow.token_address);
-        token_client.transfer(&env.current_contract_address(), &escrow.recipient, &payout);
+        admin.require_auth();
+        env.storage().persistent().set(&admin_storage_key(), &admin);
 
-        if fee > 0 {
-            token_client.transfer(&env.current_contract_address(), &treasury, &fee);
-        }
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "RoleUpdated"),
+                Symbol::new(&env, "Admin"),
+            ),
+            (Option::<Address>::None, admin),
+        );
 
-        milestone.status = MilestoneStatus::Released;
-        escrow.milestones.set(milestone_index, milestone.clone());
+        Ok(())
+    }
 
-        escrow.total_released = escrow
-            .total_released
-            .checked_add(milestone.amount)
-            .ok_or(Error::InvalidMilestoneAmount)?;
+    pub fn set_attestor(env: Env, attestor: Address) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        let admin = get_admin(&env)?;
+        admin.require_auth();
 
-        env.storage().persistent().set(&storage_key, &escrow);
+        let old_attestor: Option<Address> = env.storage().persistent().get(&attestor_storage_key());
         env.storage()
             .persistent()
-            .extend_ttl(&storage_key, 100, 2_000_000);
+            .set(&attestor_storage_key(), &attestor);
 
-        // Standardized Event
         env.events().publish(
             (
                 Symbol::new(&env, "Vaultix"),
-                Symbol::new(&env, "MilestoneReleased"),
-                escrow_id,
-                milestone_index,
+                Symbol::new(&env, "RoleUpdated"),
+                Symbol::new(&env, "Attestor"),
             ),
-            (payout, fee),
+            (old_attestor, attestor),
         );
 
         Ok(())
     }
 
-    pub fn confirm_delivery(
-        env: Env,
-        escrow_id: u64,
-        milestone_index: u32,
-        buyer: Address,
-    ) -> Result<(), Error> {
-        let storage_key = get_storage_key(escrow_id);
-        ensure_not_paused(&env)?;
-
-        let mut escrow: Escrow = env
-            .storage()
-            .persistent()
-            .get(&storage_key)
-            .ok_or(Error::EscrowNotFound)?;
-        buyer.require_auth();
+    /// Sets the TTL policy applied to the contract instance on every write.
+    /// `extend_to` may not exceed the network's maximum entry TTL.
+    pub fn set_instance_ttl(env: Env, threshold: u32, extend_to: u32) -> Result<(), Error> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
 
-        if escrow.depositor != buyer {
-            return Err(Error::UnauthorizedAccess);
-        }
-        if escrow.status != EscrowStatus::Active {
-            return Err(Error::EscrowNotActive);
-        }
-        if milestone_index >= escrow.milestones.len() {
-            return Err(Error::MilestoneNotFound);
+        if extend_to == 0 || threshold > extend_to || extend_to > env.storage().max_ttl() {
+            return Err(Error::InvalidTtlConfig);
         }
+        let config = InstanceTtl {
+            threshold,
+            extend_to,
+        };
+        env.storage()
+            .instance()
+            .set(&symbol_short!("ttl_cfg"), &config);
+        extend_instance_ttl(&env);
 
-        let mut milestone = escrow
-            .milestones
-            .get(milestone_index)
-            .ok_or(Error::MilestoneNotFound)?;
-        if milestone.status == MilestoneStatus::Released {
-            return Err(Error::MilestoneAlreadyReleased);
-        }
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "InstanceTtlUpdated"),
+            ),
+            (threshold, extend_to),
+        );
 
-        milestone.status = MilestoneStatus::Released;
-        escrow.milestones.set(milestone_index, milestone.clone());
+        Ok(())
+    }
 
-        escrow.total_released = escrow
-            .total_released
-            .checked_add(milestone.amount)
-            .ok_or(Error::InvalidMilestoneAmount)?;
+    pub fn get_instance_ttl(env: Env) -> InstanceTtl {
+        get_instance_ttl_config(&env)
+    }
 
-        let token_client = token::Client::new(&env, &escrow.token_address);
-        token_client.transfer(
-            &env.current_contract_address(),
-            &escrow.recipient,
-            &milestone.amount,
-        );
+    /// Extends the contract instance and code to the full configured TTL
+    /// regardless of the threshold, e.g. ahead of a quiet period.
+    pub fn extend_contract_ttl(env: Env) -> Result<(), Error> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
 
-        env.storage().persistent().set(&storage_key, &escrow);
+        let config = get_instance_ttl_config(&env);
+        env.storage()
+            .instance()
+            .extend_ttl(config.extend_to, config.extend_to);
 
-        // Standardized Event
         env.events().publish(
             (
                 Symbol::new(&env, "Vaultix"),
-                Symbol::new(&env, "MilestoneReleased"),
-                escrow_id,
-                milestone_index,
+                Symbol::new(&env, "ContractTtlExtended"),
             ),
-            (milestone.amount, 0i128),
+            config.extend_to,
         );
 
         Ok(())
     }
 
-    pub fn raise_dispute(env: Env, escrow_id: u64, caller: Address) -> Result<(), Error> {
-        let storage_key = get_storage_key(escrow_id);
-        ensure_not_paused(&env)?;
-
-        let mut escrow: Escrow = env
-            .storage()
-            .persistent()
-            .get(&storage_key)
-            .ok_or(Error::EscrowNotFound)?;
-
-        if caller != escrow.depositor && caller != escrow.recipient {
-            return Err(Error::UnauthorizedAccess);
-        }
-        caller.require_auth();
-
-        if escrow.status == EscrowStatus::Disputed {
-            return Err(Error::AlreadyInDispute);
+    /// Extends the TTL of up to 50 escrows (or their archived records) to
+    /// `ledgers` from now. Anyone may pay for this, e.g. a keeper. Returns,
+    /// per ID, whether a record was found and extended.
+    pub fn bump_escrows(env: Env, escrow_ids: Vec<u64>, ledgers: u32) -> Result<Vec<bool>, Error> {
+        extend_instance_ttl(&env);
+        if escrow_ids.len() > MAX_BUMP_BATCH {
+            return Err(Error::VectorTooLarge);
         }
-        if escrow.status != EscrowStatus::Active && escrow.status != EscrowStatus::Created {
-            return Err(Error::InvalidEscrowStatus);
+        if ledgers == 0 || ledgers > env.storage().max_ttl() {
+            return Err(Error::InvalidTtlConfig);
         }
 
-        let mut updated_milestones = Vec::new(&env);
-        for milestone in escrow.milestones.iter() {
-            let mut m = milestone.clone();
-            if m.status == MilestoneStatus::Pending {
-                m.status = MilestoneStatus::Disputed;
-            }
-            updated_milestones.push_back(m);
+        let storage = env.storage().persistent();
+        let mut results = Vec::new(&env);
+        for escrow_id in escrow_ids.iter() {
+            let key = if storage.has(&get_storage_key(escrow_id)) {
+                get_storage_key(escrow_id)
+            } else if storage.has(&archive_key(escrow_id)) {
+                archive_key(escrow_id)
+            } else {
+                results.push_back(false);
+                continue;
+            };
+            storage.extend_ttl(&key, ledgers, ledgers);
+            results.push_back(true);
         }
+        Ok(results)
+    }
 
-        escrow.milestones = updated_milestones;
-        escrow.status = EscrowStatus::Disputed;
-        escrow.resolution = Resolution::None;
-        env.storage().persistent().set(&storage_key, &escrow);
+    /// Caps how many unfinished escrows a single depositor may hold at once;
+    /// `0` removes the cap.
+    pub fn set_max_active_escrows(env: Env, max_active: u32) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+
+        env.storage()
+            .instance()
+            .set(&symbol_short!("max_activ"), &max_active);
 
-        // Standardized Event
         env.events().publish(
             (
                 Symbol::new(&env, "Vaultix"),
-                Symbol::new(&env, "DisputeRaised"),
-                escrow_id,
+                Symbol::new(&env, "MaxActiveEscrowsUpdated"),
             ),
-            caller,
+            max_active,
         );
 
         Ok(())
     }
 
-    pub fn resolve_dispute(env: Env, escrow_id: u64, winner: Address) -> Result<(), Error> {
+    pub fn get_max_active_escrows(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("max_activ"))
+            .unwrap_or(0)
+    }
+
+    /// Rejects new escrows with a milestone smaller than `units` whole
+    /// tokens (scaled by each token's decimals), against dust-milestone
+    /// spam. `0` removes the minimum.
+    pub fn set_min_milestone_units(env: Env, units: i128) -> Result<(), Error> {
+        extend_instance_ttl(&env);
         let admin = get_admin(&env)?;
         admin.require_auth();
-
-        let storage_key = get_storage_key(escrow_id);
-        let mut escrow: Escrow = env
-            .storage()
-            .persistent()
-            .get(&storage_key)
-            .ok_or(Error::EscrowNotFound)?;
-
-        if escrow.status != EscrowStatus::Disputed {
-            return Err(Error::InvalidEscrowStatus);
-        }
-        if winner != escrow.depositor && winner != escrow.recipient {
-            return Err(Error::InvalidWinner);
-        }
-
-        let outstanding = escrow
-            .total_amount
-            .checked_sub(escrow.total_released)
-            .ok_or(Error::InvalidMilestoneAmount)?;
-        let token_client = token::Client::new(&env, &escrow.token_address);
-
-        if winner == escrow.recipient {
-            let mut updated_milestones = Vec::new(&env);
-            for milestone in escrow.milestones.iter() {
-                let mut m = milestone.clone();
-                if m.status != MilestoneStatus::Released {
-                    m.status = MilestoneStatus::Released;
-                }
-                updated_milestones.push_back(m);
-            }
-            escrow.milestones = updated_milestones;
-            escrow.total_released = escrow.total_amount;
-            escrow.resolution = Resolution::Recipient;
-
-            if outstanding > 0 {
-                token_client.transfer(
-                    &env.current_contract_address(),
-                    &escrow.recipient,
-                    &outstanding,
-                );
-            }
-        } else {
-            let mut updated_milestones = Vec::new(&env);
-            for milestone in escrow.milestones.iter() {
-                let mut m = milestone.clone();
-                if m.status == MilestoneStatus::Pending || m.status == MilestoneStatus::Disputed {
-                    m.status = MilestoneStatus::Disputed;
-                }
-                updated_milestones.push_back(m);
-            }
-            escrow.milestones = updated_milestones;
-            escrow.resolution = Resolution::Depositor;
-
-            if outstanding > 0 {
-                token_client.transfer(
-                    &env.current_contract_address(),
-                    &escrow.depositor,
-                    &outstanding,
-                );
-            }
+        if units < 0 {
+            return Err(Error::InvalidMilestoneAmount);
         }
 
-        escrow.status = EscrowStatus::Resolved;
-        env.storage().persistent().set(&storage_key, &escrow);
+        env.storage()
+            .instance()
+            .set(&symbol_short!("min_units"), &units);
 
-        // Standardized Event
         env.events().publish(
             (
                 Symbol::new(&env, "Vaultix"),
-                Symbol::new(&env, "DisputeResolved"),
-                escrow_id,
+                Symbol::new(&env, "MinMilestoneUpdated"),
             ),
-            winner,
+            units,
         );
 
         Ok(())
     }
 
-    pub fn cancel_escrow(env: Env, escrow_id: u64) -> Result<(), Error> {
-        let storage_key = get_storage_key(escrow_id);
-        ensure_not_paused(&env)?;
+    pub fn get_min_milestone_units(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("min_units"))
+            .unwrap_or(0)
+    }
+
+    /// Caps the value held by open escrows, for a graduated rollout. `None`
+    /// sets the global cap, which sums raw amounts across all tokens; a
+    /// token address sets that token's cap. `0` removes the cap.
+    pub fn set_tvl_cap(env: Env, token: Option<Address>, cap: i128) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        if cap < 0 {
+            return Err(Error::InvalidMilestoneAmount);
+        }
+
+        env.storage().instance().set(&tvl_cap_key(&token), &cap);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "TvlCapUpdated"),
+            ),
+            (token, cap),
+        );
+
+        Ok(())
+    }
+
+    pub fn get_tvl_cap(env: Env, token: Option<Address>) -> i128 {
+        env.storage()
+            .instance()
+            .get(&tvl_cap_key(&token))
+            .unwrap_or(0)
+    }
+
+    /// Flags `token` as a stablecoin guarded against depegs, or clears the
+    /// flag when `guard` is `None`
+    pub fn set_stablecoin_guard(
+        env: Env,
+        token: Address,
+        guard: Option<StablecoinGuard>,
+    ) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+
+        let key = stablecoin_guard_key(&token);
+        match guard {
+            Some(ref guard) => {
+                if guard.peg <= 0 || !is_valid_bps(guard.band_bps) {
+                    return Err(Error::InvalidFeeConfiguration);
+                }
+                env.storage().instance().set(&key, guard);
+            }
+            None => env.storage().instance().remove(&key),
+        }
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "StablecoinGuardUpdated"),
+                token,
+            ),
+            guard,
+        );
+
+        Ok(())
+    }
+
+    pub fn get_stablecoin_guard(env: Env, token: Address) -> Option<StablecoinGuard> {
+        env.storage().instance().get(&stablecoin_guard_key(&token))
+    }
+
+    /// Lets the next payout of a milestone go through even though its token
+    /// is off peg. Both parties must sign, since either side may be the one
+    /// hurt by settling in a depegged asset.
+    pub fn override_depeg_guard(
+        env: Env,
+        escrow_id: u64,
+        milestone_index: u32,
+    ) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        ensure_not_paused(&env)?;
+        let escrow = Self::get_escrow(env.clone(), escrow_id)?;
+        escrow.authorize(escrow_id, &escrow.depositor);
+        escrow.authorize(escrow_id, &escrow.recipient);
+        if milestone_index >= escrow.milestones.len() {
+            return Err(Error::MilestoneNotFound);
+        }
+
+        let key = depeg_override_key(escrow_id, milestone_index);
+        env.storage().persistent().set(&key, &true);
+        env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "DepegGuardOverridden"),
+                escrow_id,
+                milestone_index,
+            ),
+            (),
+        );
+
+        Ok(())
+    }
+
+    /// Value committed to escrows that are not yet completed, cancelled or
+    /// resolved, globally (`None`) or for one token
+    pub fn get_total_value_locked(env: Env, token: Option<Address>) -> i128 {
+        env.storage().instance().get(&tvl_key(&token)).unwrap_or(0)
+    }
+
+    /// Routes the caller's future release payouts to `payout` (e.g. a
+    /// custodial or exchange deposit address) without changing which address
+    /// the escrows name. `None` restores payouts to the caller itself.
+    pub fn set_payout_address(
+        env: Env,
+        party: Address,
+        payout: Option<Address>,
+    ) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        party.require_auth();
+        ensure_not_paused(&env)?;
+
+        let key = payout_address_key(&party);
+        match payout {
+            Some(ref payout) if *payout != party => {
+                env.storage().persistent().set(&key, payout);
+                env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+            }
+            _ => env.storage().persistent().remove(&key),
+        }
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "PayoutAddressUpdated"),
+            ),
+            (party, payout),
+        );
+
+        Ok(())
+    }
+
+    pub fn get_payout_address(env: Env, party: Address) -> Address {
+        payout_address(&env, &party)
+    }
+
+    /// Sends the escrow's refunds (cancellation, attested settlements,
+    /// disputes lost by the recipient, grant clawbacks) to `refund_to`.
+    /// `None` restores refunds to the depositor.
+    pub fn set_refund_address(
+        env: Env,
+        escrow_id: u64,
+        refund_to: Option<Address>,
+    ) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        ensure_not_paused(&env)?;
+        let escrow = Self::get_escrow(env.clone(), escrow_id)?;
+        escrow.authorize(escrow_id, &escrow.depositor);
+
+        store_refund_address(&env, escrow_id, &escrow, refund_to.clone());
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "RefundAddressUpdated"),
+                escrow_id,
+            ),
+            refund_to,
+        );
+
+        Ok(())
+    }
+
+    pub fn get_refund_address(env: Env, escrow_id: u64) -> Result<Address, Error> {
+        let escrow = Self::get_escrow(env.clone(), escrow_id)?;
+        Ok(refund_address(&env, escrow_id, &escrow))
+    }
+
+    /// Marks a pending milestone as settled off-chain (e.g. a confirmed bank
+    /// wire). No tokens go to the recipient; if the escrow is funded the
+    /// milestone amount is returned to the depositor, and in every case it
+    /// is removed from the escrow's token-denominated total.
+    pub fn attest_settlement(
+        env: Env,
+        escrow_id: u64,
+        milestone_index: u32,
+        attestation_hash: BytesN<32>,
+    ) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        let attestor: Address = env
+            .storage()
+            .persistent()
+            .get(&attestor_storage_key())
+            .ok_or(Error::AttestorNotInitialized)?;
+        attestor.require_auth();
+        ensure_not_paused(&env)?;
+
+        let storage_key = get_storage_key(escrow_id);
+        let mut escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&storage_key)
+            .ok_or(Error::EscrowNotFound)?;
+
+        if escrow.status != EscrowStatus::Active && escrow.status != EscrowStatus::Created {
+            return Err(Error::InvalidEscrowStatus);
+        }
+        if escrow.kind == EscrowKind::Swap {
+            return Err(Error::InvalidEscrowKind);
+        }
+
+        let mut milestone = escrow
+            .milestones
+            .get(milestone_index)
+            .ok_or(Error::MilestoneNotFound)?;
+        if milestone.status != MilestoneStatus::Pending {
+            return Err(Error::MilestoneAlreadyReleased);
+        }
+        if milestone.amount == 0 {
+            return Err(Error::MilestoneSealed);
+        }
+
+        if escrow.status == EscrowStatus::Active {
+            settle(
+                &env,
+                escrow_id,
+                &mut escrow,
+                Some(milestone_index),
+                milestone.amount,
+                Outcome::Refund,
+            )?;
+        }
+
+        escrow.total_amount = escrow
+            .total_amount
+            .checked_sub(milestone.amount)
+            .ok_or(Error::InvalidMilestoneAmount)?;
+        unlock_value(&env, &escrow.token_address, milestone.amount);
+        milestone.status = MilestoneStatus::SettledOffChain;
+        escrow.milestones.set(milestone_index, milestone);
+
+        env.storage().persistent().set(&storage_key, &escrow);
+        env.storage()
+            .persistent()
+            .extend_ttl(&storage_key, 100, 2_000_000);
+
+        let attestation_key = attestation_key(escrow_id, milestone_index);
+        env.storage()
+            .persistent()
+            .set(&attestation_key, &attestation_hash);
+        env.storage()
+            .persistent()
+            .extend_ttl(&attestation_key, 100, 2_000_000);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "MilestoneSettledOffChain"),
+                escrow_id,
+                milestone_index,
+            ),
+            (attestor, attestation_hash),
+        );
+
+        Ok(())
+    }
+
+    pub fn create_escrow(
+        env: Env,
+        escrow_id: u64,
+        depositor: Address,
+        recipient: Address,
+        token_address: Address,
+        milestones: Vec<Milestone>,
+        deadline: u64,
+        external_ref: Option<BytesN<32>>,
+        terms_hash: Option<BytesN<32>>,
+        expected_total: i128,
+        options: EscrowOptions,
+    ) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        depositor.require_auth();
+        open_escrow(
+            &env,
+            escrow_id,
+            depositor,
+            recipient,
+            token_address,
+            milestones,
+            deadline,
+            external_ref,
+            terms_hash,
+            expected_total,
+            options,
+        )
+    }
+
+    /// Creates and funds an escrow in one step on the depositor's behalf.
+    /// Instead of signing, the depositor pre-approves `spender` (e.g. a
+    /// marketplace backend) for the total on the escrow's token; `spender`
+    /// signs and the deposit is drawn from that allowance. Funding right
+    /// away leaves no room for terms acceptance or a swap's counter leg.
+    pub fn create_escrow_from_allowance(
+        env: Env,
+        spender: Address,
+        escrow_id: u64,
+        depositor: Address,
+        recipient: Address,
+        token_address: Address,
+        milestones: Vec<Milestone>,
+        deadline: u64,
+        expected_total: i128,
+        options: EscrowOptions,
+    ) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        spender.require_auth();
+        if options.kind == EscrowKind::Swap {
+            return Err(Error::InvalidEscrowKind);
+        }
+        open_escrow(
+            &env,
+            escrow_id,
+            depositor,
+            recipient,
+            token_address,
+            milestones,
+            deadline,
+            None,
+            None,
+            expected_total,
+            options,
+        )?;
+
+        let mut escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&get_storage_key(escrow_id))
+            .ok_or(Error::EscrowNotFound)?;
+        fund_escrow(&env, escrow_id, &mut escrow, &spender);
+        Ok(())
+    }
+
+    /// Creates an escrow from one `total_amount` split by basis-point weights
+    /// that must sum to 10000. Each milestone gets its floored share and the
+    /// last one absorbs the rounding remainder, so amounts always add up.
+    pub fn create_escrow_by_bps(
+        env: Env,
+        escrow_id: u64,
+        depositor: Address,
+        recipient: Address,
+        token_address: Address,
+        total_amount: i128,
+        labels: Vec<Symbol>,
+        weights_bps: Vec<u32>,
+        deadline: u64,
+    ) -> Result<(), Error> {
+        let milestones = weighted_milestones(&env, total_amount, &labels, &weights_bps)?;
+        Self::create_escrow(
+            env,
+            escrow_id,
+            depositor,
+            recipient,
+            token_address,
+            milestones,
+            deadline,
+            None,
+            None,
+            total_amount,
+            EscrowOptions::new(EscrowKind::Standard),
+        )
+    }
+
+    /// Creates an escrow whose per-milestone amounts stay private until
+    /// payout. Only `total_amount` is stored in clear; each milestone holds a
+    /// commitment `sha256(amount as 16-byte big-endian i128 || salt)` and an
+    /// amount of zero until `reveal_milestone` opens it.
+    pub fn create_sealed_escrow(
+        env: Env,
+        escrow_id: u64,
+        depositor: Address,
+        recipient: Address,
+        token_address: Address,
+        total_amount: i128,
+        labels: Vec<Symbol>,
+        commitments: Vec<BytesN<32>>,
+        deadline: u64,
+    ) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        depositor.require_auth();
+        ensure_not_paused(&env)?;
+
+        if depositor == recipient {
+            return Err(Error::SelfDealing);
+        }
+        participants::ensure_participant(&env, &depositor)?;
+        participants::ensure_participant(&env, &recipient)?;
+        let storage_key = get_storage_key(escrow_id);
+        if env.storage().persistent().has(&storage_key)
+            || env.storage().persistent().has(&archive_key(escrow_id))
+        {
+            return Err(Error::EscrowAlreadyExists);
+        }
+        if labels.len() > vaultix_core::MAX_MILESTONES {
+            return Err(Error::VectorTooLarge);
+        }
+        if labels.len() != commitments.len() || labels.len() as i128 > total_amount {
+            return Err(Error::InvalidMilestoneAmount);
+        }
+
+        let mut milestones = Vec::new(&env);
+        for description in labels.iter() {
+            milestones.push_back(Milestone {
+                amount: 0,
+                status: MilestoneStatus::Pending,
+                description,
+            });
+        }
+        ensure_unique_labels(&milestones)?;
+        ensure_below_active_cap(&env, &depositor)?;
+        ensure_policy_allows(
+            &env,
+            PolicyAction::Create,
+            &depositor,
+            &recipient,
+            &token_address,
+            total_amount,
+        )?;
+        lock_value(&env, &token_address, total_amount)?;
+
+        let escrow = Escrow {
+            depositor,
+            recipient,
+            token_address,
+            total_amount,
+            total_released: 0,
+            milestones,
+            status: EscrowStatus::Created,
+            deadline,
+            resolution: Resolution::None,
+            kind: EscrowKind::Standard,
+            fee_bps: current_fee_bps(&env),
+            auth_mode: AuthMode::Strict,
+        };
+        let commitments_key = commitments_key(escrow_id);
+        env.storage()
+            .persistent()
+            .set(&commitments_key, &commitments);
+        env.storage()
+            .persistent()
+            .extend_ttl(&commitments_key, 100, 2_000_000);
+        store_new_escrow(&env, escrow_id, &escrow, None, None);
+
+        Ok(())
+    }
+
+    /// Opens a sealed milestone's amount so it can be released. The revealed
+    /// amounts must add up to the escrow total once every milestone is open.
+    pub fn reveal_milestone(
+        env: Env,
+        escrow_id: u64,
+        milestone_index: u32,
+        amount: i128,
+        salt: BytesN<32>,
+    ) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        let storage_key = get_storage_key(escrow_id);
+        ensure_not_paused(&env)?;
+
+        let mut escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&storage_key)
+            .ok_or(Error::EscrowNotFound)?;
+        escrow.authorize(escrow_id, escrow.releaser());
+
+        if escrow.status != EscrowStatus::Active {
+            return Err(Error::EscrowNotActive);
+        }
+        let commitments: Vec<BytesN<32>> = env
+            .storage()
+            .persistent()
+            .get(&commitments_key(escrow_id))
+            .ok_or(Error::CommitmentMismatch)?;
+        let commitment = commitments
+            .get(milestone_index)
+            .ok_or(Error::MilestoneNotFound)?;
+        let mut milestone = escrow
+            .milestones
+            .get(milestone_index)
+            .ok_or(Error::MilestoneNotFound)?;
+        if milestone.amount != 0 || milestone.status != MilestoneStatus::Pending {
+            return Err(Error::MilestoneAlreadyReleased);
+        }
+
+        if amount <= 0 {
+            return Err(Error::ZeroAmount);
+        }
+        let mut preimage = Bytes::from_array(&env, &amount.to_be_bytes());
+        preimage.append(&salt.into());
+        if env.crypto().sha256(&preimage) != commitment {
+            return Err(Error::CommitmentMismatch);
+        }
+
+        let mut revealed = amount;
+        let mut sealed_left = 0u32;
+        for (i, m) in escrow.milestones.iter().enumerate() {
+            if i as u32 == milestone_index {
+                continue;
+            }
+            if m.amount == 0 {
+                sealed_left += 1;
+            }
+            revealed = revealed
+                .checked_add(m.amount)
+                .ok_or(Error::InvalidMilestoneAmount)?;
+        }
+        // Leave at least one unit for every milestone still sealed
+        let reserved = revealed
+            .checked_add(sealed_left as i128)
+            .ok_or(Error::InvalidMilestoneAmount)?;
+        if reserved > escrow.total_amount || (sealed_left == 0 && revealed != escrow.total_amount) {
+            return Err(Error::InvalidMilestoneAmount);
+        }
+
+        milestone.amount = amount;
+        escrow.milestones.set(milestone_index, milestone);
+        env.storage().persistent().set(&storage_key, &escrow);
+        env.storage()
+            .persistent()
+            .extend_ttl(&storage_key, 100, 2_000_000);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "MilestoneRevealed"),
+                escrow_id,
+                milestone_index,
+            ),
+            amount,
+        );
+
+        Ok(())
+    }
+
+    /// Recipient's signature over the terms hash set at creation, binding
+    /// both parties to the same off-chain document.
+    pub fn accept_terms(env: Env, escrow_id: u64, terms_hash: BytesN<32>) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        let escrow = Self::get_escrow(env.clone(), escrow_id)?;
+        escrow.authorize(escrow_id, &escrow.recipient);
+
+        let terms_key = terms_key(escrow_id);
+        let mut terms: Terms = env
+            .storage()
+            .persistent()
+            .get(&terms_key)
+            .ok_or(Error::TermsNotFound)?;
+        if terms.hash != terms_hash {
+            return Err(Error::TermsNotFound);
+        }
+        terms.accepted = true;
+        env.storage().persistent().set(&terms_key, &terms);
+        env.storage()
+            .persistent()
+            .extend_ttl(&terms_key, 100, 2_000_000);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "TermsAccepted"),
+                escrow_id,
+            ),
+            (escrow.recipient, terms_hash),
+        );
+
+        Ok(())
+    }
+
+    /// Whether `document_hash` is the terms both parties signed for the escrow
+    pub fn verify_terms(env: Env, escrow_id: u64, document_hash: BytesN<32>) -> bool {
+        let terms: Option<Terms> = env.storage().persistent().get(&terms_key(escrow_id));
+        terms.is_some_and(|t| t.accepted && t.hash == document_hash)
+    }
+
+    /// Locks the recipient's side of a swap escrow. Must happen before the
+    /// depositor funds, so an Active swap always holds both legs.
+    pub fn fund_counter_leg(
+        env: Env,
+        escrow_id: u64,
+        token_address: Address,
+        amounts: Vec<i128>,
+    ) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        ensure_not_paused(&env)?;
+        let escrow = Self::get_escrow(env.clone(), escrow_id)?;
+        escrow.authorize(escrow_id, &escrow.recipient);
+
+        if escrow.kind != EscrowKind::Swap {
+            return Err(Error::InvalidEscrowKind);
+        }
+        if escrow.status != EscrowStatus::Created {
+            return Err(Error::InvalidEscrowStatus);
+        }
+        let leg_key = counter_leg_key(escrow_id);
+        if env.storage().persistent().has(&leg_key) {
+            return Err(Error::EscrowAlreadyFunded);
+        }
+        if amounts.len() != escrow.milestones.len() {
+            return Err(Error::InvalidMilestoneAmount);
+        }
+        let total_amount = vaultix_core::validate_milestone_amounts(amounts.iter())?;
+
+        let token_client = token::Client::new(&env, &token_address);
+        token_client.transfer_from(
+            &env.current_contract_address(),
+            &escrow.recipient,
+            &env.current_contract_address(),
+            &total_amount,
+        );
+        record_flow(&env, &escrow.recipient, Flow::Deposited, total_amount);
+
+        env.storage().persistent().set(
+            &leg_key,
+            &CounterLeg {
+                token_address: token_address.clone(),
+                amounts,
+                total_amount,
+            },
+        );
+        env.storage()
+            .persistent()
+            .extend_ttl(&leg_key, 100, 2_000_000);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "CounterLegFunded"),
+                escrow_id,
+            ),
+            (token_address, total_amount),
+        );
+
+        Ok(())
+    }
+
+    /// Returns the counter leg to the recipient if the depositor never funded
+    pub fn withdraw_counter_leg(env: Env, escrow_id: u64) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        ensure_not_paused(&env)?;
+        let escrow = Self::get_escrow(env.clone(), escrow_id)?;
+        escrow.authorize(escrow_id, &escrow.recipient);
+
+        if escrow.status != EscrowStatus::Created {
+            return Err(Error::InvalidEscrowStatus);
+        }
+        let leg_key = counter_leg_key(escrow_id);
+        let leg: CounterLeg = env
+            .storage()
+            .persistent()
+            .get(&leg_key)
+            .ok_or(Error::CounterLegNotFunded)?;
+
+        let token_client = token::Client::new(&env, &leg.token_address);
+        token_client.transfer(
+            &env.current_contract_address(),
+            &escrow.recipient,
+            &leg.total_amount,
+        );
+        record_flow(&env, &escrow.recipient, Flow::Refunded, leg.total_amount);
+        env.storage().persistent().remove(&leg_key);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "CounterLegWithdrawn"),
+                escrow_id,
+            ),
+            leg.total_amount,
+        );
+
+        Ok(())
+    }
+
+    pub fn get_counter_leg(env: Env, escrow_id: u64) -> Result<CounterLeg, Error> {
+        env.storage()
+            .persistent()
+            .get(&counter_leg_key(escrow_id))
+            .ok_or(Error::CounterLegNotFunded)
+    }
+
+    pub fn deposit_funds(env: Env, escrow_id: u64) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        let storage_key = get_storage_key(escrow_id);
+        ensure_not_paused(&env)?;
+
+        let mut escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&storage_key)
+            .ok_or(Error::EscrowNotFound)?;
+        escrow.authorize(escrow_id, &escrow.depositor);
+
+        if escrow.status != EscrowStatus::Created {
+            return Err(Error::EscrowAlreadyFunded);
+        }
+        let terms: Option<Terms> = env.storage().persistent().get(&terms_key(escrow_id));
+        if terms.is_some_and(|t| !t.accepted) {
+            return Err(Error::TermsNotAccepted);
+        }
+        if escrow.kind == EscrowKind::Swap
+            && !env.storage().persistent().has(&counter_leg_key(escrow_id))
+        {
+            return Err(Error::CounterLegNotFunded);
+        }
+
+        fund_escrow(
+            &env,
+            escrow_id,
+            &mut escrow,
+            &env.current_contract_address(),
+        );
+        Ok(())
+    }
+
+    pub fn get_escrow(env: Env, escrow_id: u64) -> Result<Escrow, Error> {
+        let storage_key = get_storage_key(escrow_id);
+        env.storage()
+            .persistent()
+            .get(&storage_key)
+            .ok_or(Error::EscrowNotFound)
+    }
+
+    pub fn get_fee_split(env: Env, escrow_id: u64) -> Result<FeeSplit, Error> {
+        Self::get_escrow(env.clone(), escrow_id)?;
+        Ok(FeeSplit {
+            payer: fee_payer(&env, escrow_id),
+            depositor_reserve: fee_reserve(&env, escrow_id),
+        })
+    }
+
+    /// Receipt of the escrow's `index`-th settlement, numbered from zero,
+    /// while it is still retained
+    pub fn get_receipt(env: Env, escrow_id: u64, index: u32) -> Option<Receipt> {
+        env.storage()
+            .temporary()
+            .get(&settlement::receipt_key(escrow_id, index))
+    }
+
+    /// Settlements recorded for the escrow within the receipt retention
+    /// period; the latest receipt is at `count - 1`
+    pub fn get_receipt_count(env: Env, escrow_id: u64) -> u32 {
+        env.storage()
+            .temporary()
+            .get(&settlement::receipt_count_key(escrow_id))
+            .unwrap_or(0)
+    }
+
+    pub fn get_dispute_policy(env: Env, escrow_id: u64) -> Result<DisputePolicy, Error> {
+        Self::get_escrow(env.clone(), escrow_id)?;
+        Ok(dispute_policy(&env, escrow_id))
+    }
+
+    /// Platform fee (in bps) charged on this escrow's releases
+    pub fn get_escrow_fee(env: Env, escrow_id: u64) -> Result<i128, Error> {
+        Ok(Self::get_escrow(env, escrow_id)?.fee_bps)
+    }
+
+    pub fn get_state(env: Env, escrow_id: u64) -> Result<EscrowStatus, Error> {
+        let escrow = Self::get_escrow(env, escrow_id)?;
+        Ok(escrow.status)
+    }
+
+    /// Resolves a milestone index from its label (the milestone description),
+    /// which is unique within an escrow.
+    pub fn find_milestone(env: Env, escrow_id: u64, label: Symbol) -> Result<u32, Error> {
+        let escrow = Self::get_escrow(env, escrow_id)?;
+        escrow
+            .milestones
+            .iter()
+            .position(|m| m.description == label)
+            .map(|i| i as u32)
+            .ok_or(Error::MilestoneNotFound)
+    }
+
+    /// Moves `amount` of unreleased budget from one pending milestone to
+    /// another, keeping the escrow total. Both parties must sign; the source
+    /// milestone keeps a non-zero amount.
+    pub fn reallocate(
+        env: Env,
+        escrow_id: u64,
+        from_idx: u32,
+        to_idx: u32,
+        amount: i128,
+    ) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        ensure_not_paused(&env)?;
+        let storage_key = get_storage_key(escrow_id);
+        let mut escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&storage_key)
+            .ok_or(Error::EscrowNotFound)?;
+        escrow.authorize(escrow_id, &escrow.depositor);
+        escrow.authorize(escrow_id, &escrow.recipient);
+
+        if escrow.status != EscrowStatus::Created && escrow.status != EscrowStatus::Active {
+            return Err(Error::InvalidEscrowStatus);
+        }
+        if amount <= 0 {
+            return Err(Error::ZeroAmount);
+        }
+        if from_idx == to_idx {
+            return Err(Error::InvalidMilestoneAmount);
+        }
+        let mut from = escrow
+            .milestones
+            .get(from_idx)
+            .ok_or(Error::MilestoneNotFound)?;
+        let mut to = escrow
+            .milestones
+            .get(to_idx)
+            .ok_or(Error::MilestoneNotFound)?;
+        if from.status != MilestoneStatus::Pending || to.status != MilestoneStatus::Pending {
+            return Err(Error::MilestoneAlreadyReleased);
+        }
+        // A partial dispute freezes a slice of its milestone's amount
+        if has_disputed_milestone(&env, escrow_id, &escrow) {
+            return Err(Error::AlreadyInDispute);
+        }
+        // Sealed amounts are bound to their commitments until revealed
+        if from.amount == 0 || to.amount == 0 {
+            return Err(Error::MilestoneSealed);
+        }
+        if amount >= from.amount {
+            return Err(Error::InsufficientBalance);
+        }
+
+        let before = escrow.clone();
+        from.amount -= amount;
+        to.amount = to
+            .amount
+            .checked_add(amount)
+            .ok_or(Error::InvalidMilestoneAmount)?;
+        escrow.milestones.set(from_idx, from);
+        escrow.milestones.set(to_idx, to);
+        ensure_above_dust(&env, &escrow.token_address, &escrow.milestones)?;
+
+        forget_deal(&env, escrow_id, &before);
+        record_deal(&env, escrow_id, &escrow);
+        env.storage().persistent().set(&storage_key, &escrow);
+        env.storage()
+            .persistent()
+            .extend_ttl(&storage_key, 100, 2_000_000);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "BudgetReallocated"),
+                escrow_id,
+            ),
+            (from_idx, to_idx, amount),
+        );
+
+        Ok(())
+    }
+
+    pub fn release_milestone(env: Env, escrow_id: u64, milestone_index: u32) -> Result<(), Error> {
+        release(&env, escrow_id, milestone_index, false)
+    }
+
+    /// Pre-authorizes up to `max_releases` future milestone releases worth at
+    /// most `max_amount` in total, so the recipient or a keeper can trigger
+    /// them through `release_approved`. Passing zero for both revokes it.
+    pub fn approve_releases(
+        env: Env,
+        escrow_id: u64,
+        max_releases: u32,
+        max_amount: i128,
+    ) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        ensure_not_paused(&env)?;
+        let escrow = Self::get_escrow(env.clone(), escrow_id)?;
+        escrow.authorize(escrow_id, escrow.releaser());
+
+        if max_amount < 0 {
+            return Err(Error::InvalidMilestoneAmount);
+        }
+        let key = release_allowance_key(escrow_id);
+        if max_releases == 0 && max_amount == 0 {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(
+                &key,
+                &ReleaseAllowance {
+                    releases_left: max_releases,
+                    amount_left: max_amount,
+                },
+            );
+            env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+        }
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "ReleasesApproved"),
+                escrow_id,
+            ),
+            (max_releases, max_amount),
+        );
+
+        Ok(())
+    }
+
+    pub fn get_release_allowance(env: Env, escrow_id: u64) -> Option<ReleaseAllowance> {
+        env.storage()
+            .persistent()
+            .get(&release_allowance_key(escrow_id))
+    }
+
+    /// Releases a milestone against the allowance granted in
+    /// `approve_releases`; callable by anyone since the payout can only go
+    /// to the escrow's payee.
+    pub fn release_approved(env: Env, escrow_id: u64, milestone_index: u32) -> Result<(), Error> {
+        release(&env, escrow_id, milestone_index, true)
+    }
+
+    pub fn confirm_delivery(
+        env: Env,
+        escrow_id: u64,
+        milestone_index: u32,
+        buyer: Address,
+    ) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        let storage_key = get_storage_key(escrow_id);
+        ensure_not_paused(&env)?;
+
+        let mut escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&storage_key)
+            .ok_or(Error::EscrowNotFound)?;
+        if *escrow.releaser() != buyer {
+            return Err(Error::UnauthorizedAccess);
+        }
+        escrow.authorize(escrow_id, &buyer);
+        if escrow.status != EscrowStatus::Active {
+            return Err(Error::EscrowNotActive);
+        }
+        if escrow.kind == EscrowKind::Hourly
+            || expenses::is_expense_milestone(&env, escrow_id, milestone_index)
+        {
+            return Err(Error::InvalidEscrowKind);
+        }
+        if milestone_index >= escrow.milestones.len() {
+            return Err(Error::MilestoneNotFound);
+        }
+
+        let mut milestone = escrow
+            .milestones
+            .get(milestone_index)
+            .ok_or(Error::MilestoneNotFound)?;
+        if milestone.status != MilestoneStatus::Pending {
+            return Err(Error::MilestoneAlreadyReleased);
+        }
+        if milestone.amount == 0 {
+            return Err(Error::MilestoneSealed);
+        }
+        ensure_cliff_reached(&env, escrow_id, milestone_index)?;
+
+        let (release_amount, next_status) =
+            releasable_amount(&env, escrow_id, milestone_index, &milestone)?;
+
+        let window = Self::get_chargeback_window(env.clone());
+        if window == 0 {
+            return pay_confirmed(
+                &env,
+                escrow_id,
+                &mut escrow,
+                milestone_index,
+                release_amount,
+                next_status,
+            );
+        }
+
+        // Hold the payout so the depositor can still dispute it
+        let claimable_at = env.ledger().timestamp().saturating_add(window);
+        let held_key = held_release_key(escrow_id, milestone_index);
+        let held = HeldRelease {
+            amount: release_amount,
+            next_status,
+            claimable_at,
+        };
+        env.storage().persistent().set(&held_key, &held);
+        env.storage()
+            .persistent()
+            .extend_ttl(&held_key, 100, 2_000_000);
+
+        milestone.status = MilestoneStatus::Held;
+        escrow.milestones.set(milestone_index, milestone);
+        env.storage().persistent().set(&storage_key, &escrow);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "ReleaseHeld"),
+                escrow_id,
+                milestone_index,
+            ),
+            (release_amount, claimable_at),
+        );
+
+        Ok(())
+    }
+
+    /// Pays out a confirmed milestone once its chargeback window has passed.
+    /// Anyone may call this; the funds always go to the payee.
+    pub fn claim_held_release(env: Env, escrow_id: u64, milestone_index: u32) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        ensure_not_paused(&env)?;
+        let mut escrow = Self::get_escrow(env.clone(), escrow_id)?;
+        if escrow.status != EscrowStatus::Active {
+            return Err(Error::EscrowNotActive);
+        }
+        let milestone = escrow
+            .milestones
+            .get(milestone_index)
+            .ok_or(Error::MilestoneNotFound)?;
+        if milestone.status != MilestoneStatus::Held {
+            return Err(Error::InvalidEscrowStatus);
+        }
+
+        let held_key = held_release_key(escrow_id, milestone_index);
+        let held: HeldRelease = env
+            .storage()
+            .persistent()
+            .get(&held_key)
+            .ok_or(Error::InvalidEscrowStatus)?;
+        if env.ledger().timestamp() < held.claimable_at {
+            return Err(Error::ChargebackWindowOpen);
+        }
+        env.storage().persistent().remove(&held_key);
+
+        pay_confirmed(
+            &env,
+            escrow_id,
+            &mut escrow,
+            milestone_index,
+            held.amount,
+            held.next_status,
+        )
+    }
+
+    /// Pays every tranche of a milestone payout that has fallen due.
+    /// Permissionless, like `claim_held_release`: the funds can only go to
+    /// the payee recorded at release. Returns the amount paid.
+    pub fn claim_tranches(env: Env, escrow_id: u64, milestone_index: u32) -> Result<i128, Error> {
+        extend_instance_ttl(&env);
+        ensure_not_paused(&env)?;
+        let escrow = Self::get_escrow(env.clone(), escrow_id)?;
+
+        let key = tranche_release_key(escrow_id, milestone_index);
+        let mut tranches: TrancheRelease = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(Error::InvalidEscrowStatus)?;
+        let now = env.ledger().timestamp();
+        if now < tranches.next_claimable_at {
+            return Err(Error::TrancheNotDue);
+        }
+
+        let elapsed = (now - tranches.next_claimable_at) / tranches.interval;
+        let due = (elapsed.saturating_add(1)).min(tranches.tranches_left as u64) as u32;
+        let amount = if due == tranches.tranches_left {
+            tranches.remaining
+        } else {
+            tranches.per_tranche * due as i128
+        };
+
+        settlement::deliver(&env, escrow_id, &escrow, &tranches.payee, amount);
+
+        tranches.remaining -= amount;
+        tranches.tranches_left -= due;
+        if tranches.tranches_left == 0 {
+            env.storage().persistent().remove(&key);
+        } else {
+            tranches.next_claimable_at += tranches.interval * due as u64;
+            env.storage().persistent().set(&key, &tranches);
+            env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+        }
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "TranchesClaimed"),
+                escrow_id,
+                milestone_index,
+            ),
+            (amount, tranches.tranches_left),
+        );
+
+        Ok(amount)
+    }
+
+    pub fn get_tranche_release(
+        env: Env,
+        escrow_id: u64,
+        milestone_index: u32,
+    ) -> Option<TrancheRelease> {
+        env.storage()
+            .persistent()
+            .get(&tranche_release_key(escrow_id, milestone_index))
+    }
+
+    /// Holds confirmed releases for `window` seconds, during which the
+    /// depositor can still raise a dispute. `0` pays out on confirmation.
+    pub fn set_chargeback_window(env: Env, window: u64) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+
+        env.storage()
+            .instance()
+            .set(&symbol_short!("chrgback"), &window);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "ChargebackWindowUpdated"),
+            ),
+            window,
+        );
+
+        Ok(())
+    }
+
+    pub fn get_chargeback_window(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("chrgback"))
+            .unwrap_or(0)
+    }
+
+    pub fn cancel_escrow(env: Env, escrow_id: u64) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        let storage_key = get_storage_key(escrow_id);
+        ensure_not_paused(&env)?;
+
+        let mut escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&storage_key)
+            .ok_or(Error::EscrowNotFound)?;
+        escrow.authorize(escrow_id, &escrow.depositor);
+
+        ensure_cancellable(&env, escrow_id, &escrow)?;
+        // A funded guarantee can only be withdrawn with the buyer's consent
+        if escrow.kind == EscrowKind::RefundGuarantee && escrow.status == EscrowStatus::Active {
+            escrow.authorize(escrow_id, &escrow.recipient);
+        }
+
+        if escrow.status == EscrowStatus::Active {
+            let refund = escrow.total_amount - escrow.total_released;
+            let sent = settle(&env, escrow_id, &mut escrow, None, refund, Outcome::Refund)?;
+            // A quarantined refund is announced by `TransferQuarantined` instead
+            if sent && refund > 0 {
+                env.events().publish(
+                    (
+                        Symbol::new(&env, "Vaultix"),
+                        Symbol::new(&env, "EscrowRefunded"),
+                        escrow_id,
+                    ),
+                    (refund_address(&env, escrow_id, &escrow), refund),
+                );
+            }
+        }
+        let leg: Option<CounterLeg> = env.storage().persistent().get(&counter_leg_key(escrow_id));
+        if let Some(leg) = leg {
+            if quarantine::transfer_or_quarantine(
+                &env,
+                escrow_id,
+                TransferLeg::CounterLeg,
+                &leg.token_address,
+                &escrow.recipient,
+                leg.total_amount,
+            ) {
+                record_flow(&env, &escrow.recipient, Flow::Refunded, leg.total_amount);
+            }
+        }
+
+        set_status(&env, escrow_id, &mut escrow, EscrowStatus::Cancelled);
+        env.storage().persistent().set(&storage_key, &escrow);
+        env.storage()
+            .persistent()
+            .extend_ttl(&storage_key, 100, 2_000_000);
+
+        // Standardized Event
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "EscrowCancelled"),
+                escrow_id,
+            ),
+            escrow.depositor.clone(), // cancelled_by
+        );
+
+        Ok(())
+    }
+
+    pub fn complete_escrow(env: Env, escrow_id: u64) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        let storage_key = get_storage_key(escrow_id);
+        ensure_not_paused(&env)?;
+
+        let mut escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&storage_key)
+            .ok_or(Error::EscrowNotFound)?;
+        escrow.authorize(escrow_id, &escrow.depositor);
+
+        if escrow.status != EscrowStatus::Active {
+            return Err(Error::InvalidEscrowStatus);
+        }
+        if has_disputed_milestone(&env, escrow_id, &escrow) {
+            return Err(Error::AlreadyInDispute);
+        }
+        if !verify_all_released(&escrow.milestones) {
+            return Err(Error::EscrowNotActive);
+        }
+
+        set_status(&env, escrow_id, &mut escrow, EscrowStatus::Completed);
+        env.storage().persistent().set(&storage_key, &escrow);
+        env.storage()
+            .persistent()
+            .extend_ttl(&storage_key, 100, 2_000_000);
+
+        // Standardized Event
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "EscrowCompleted"),
+                escrow_id,
+            ),
+            (),
+        );
+
+        Ok(())
+    }
+
+    /// Pays the outstanding refund guarantee to the buyer once the deadline
+    /// has passed without every milestone being confirmed as delivered.
+    pub fn claim_guarantee(env: Env, escrow_id: u64) -> Result<i128, Error> {
+        extend_instance_ttl(&env);
+        let storage_key = get_storage_key(escrow_id);
+        ensure_not_paused(&env)?;
 
         let mut escrow: Escrow = env
             .storage()
             .persistent()
             .get(&storage_key)
             .ok_or(Error::EscrowNotFound)?;
-        escrow.depositor.require_auth();
+        escrow.authorize(escrow_id, &escrow.recipient);
+
+        if escrow.kind != EscrowKind::RefundGuarantee {
+            return Err(Error::InvalidEscrowKind);
+        }
+        if escrow.status != EscrowStatus::Active {
+            return Err(Error::InvalidEscrowStatus);
+        }
+        if env.ledger().timestamp() <= escrow.deadline {
+            return Err(Error::DeadlineNotReached);
+        }
+
+        let claimed = escrow
+            .total_amount
+            .checked_sub(escrow.total_released)
+            .ok_or(Error::InvalidMilestoneAmount)?;
+        settle(
+            &env,
+            escrow_id,
+            &mut escrow,
+            None,
+            claimed,
+            Outcome::Award { charge_fee: false },
+        )?;
+
+        let mut milestones = escrow.milestones.clone();
+        for i in 0..milestones.len() {
+            let mut milestone = milestones.get(i).ok_or(Error::MilestoneNotFound)?;
+            if milestone.status == MilestoneStatus::Pending {
+                milestone.status = MilestoneStatus::Released;
+                milestones.set(i, milestone);
+            }
+        }
+        escrow.milestones = milestones;
+        set_status(&env, escrow_id, &mut escrow, EscrowStatus::Completed);
+        env.storage().persistent().set(&storage_key, &escrow);
+        env.storage()
+            .persistent()
+            .extend_ttl(&storage_key, 100, 2_000_000);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "GuaranteeClaimed"),
+                escrow_id,
+            ),
+            (escrow.recipient.clone(), claimed),
+        );
+
+        Ok(claimed)
+    }
+}
+
+/// Validates and stores a new escrow in `Created` status. Callers
+/// authorize the creation first.
+fn open_escrow(
+    env: &Env,
+    escrow_id: u64,
+    depositor: Address,
+    recipient: Address,
+    token_address: Address,
+    milestones: Vec<Milestone>,
+    deadline: u64,
+    external_ref: Option<BytesN<32>>,
+    terms_hash: Option<BytesN<32>>,
+    expected_total: i128,
+    options: EscrowOptions,
+) -> Result<(), Error> {
+    let EscrowOptions {
+        kind,
+        cliff_until,
+        refund_to,
+        referral,
+        lock_recipient,
+        auth_mode,
+        release_schedule,
+        dispute_policy,
+        allow_duplicate,
+        fee_payer,
+        promo,
+    } = options;
+    ensure_not_paused(env)?;
+
+    if depositor == recipient {
+        return Err(Error::SelfDealing);
+    }
+    participants::ensure_participant(env, &depositor)?;
+    participants::ensure_participant(env, &recipient)?;
+
+    let storage_key = get_storage_key(escrow_id);
+    if env.storage().persistent().has(&storage_key)
+        || env.storage().persistent().has(&archive_key(escrow_id))
+    {
+        return Err(Error::EscrowAlreadyExists);
+    }
+    if let Some(ref external_ref) = external_ref {
+        if env
+            .storage()
+            .persistent()
+            .has(&external_ref_key(external_ref))
+        {
+            return Err(Error::ExternalRefAlreadyExists);
+        }
+    }
+
+    let total_amount = validate_milestones(&milestones)?;
+    ensure_above_dust(env, &token_address, &milestones)?;
+    // The depositor signs for the total they reviewed, not just the list
+    if total_amount != expected_total {
+        return Err(Error::TotalAmountMismatch);
+    }
+    ensure_unique_labels(&milestones)?;
+    ensure_below_active_cap(env, &depositor)?;
+    validate_auth_mode(&depositor, &auth_mode)?;
+    if let ReleaseSchedule::Tranches(count, interval) = release_schedule {
+        if count == 0 || count > MAX_TRANCHES || interval == 0 {
+            return Err(Error::InvalidTrancheSchedule);
+        }
+    }
+    // An hourly pool is released piecemeal, one approved work log at a time
+    if kind == EscrowKind::Hourly
+        && (milestones.len() != 1 || release_schedule != ReleaseSchedule::Immediate)
+    {
+        return Err(Error::InvalidEscrowKind);
+    }
+    ensure_policy_allows(
+        env,
+        PolicyAction::Create,
+        &depositor,
+        &recipient,
+        &token_address,
+        total_amount,
+    )?;
+    lock_value(env, &token_address, total_amount)?;
+    if let Referral::Code(ref code) = referral {
+        referrals::redeem_referral_code(env, escrow_id, code, &depositor, &recipient)?;
+    }
+    let fee_bps = match promo {
+        Promo::None => current_fee_bps(env),
+        Promo::Code(ref code) => promos::redeem_promo(env, code, current_fee_bps(env))?,
+    };
+
+    let mut initialized_milestones = Vec::new(env);
+    for milestone in milestones.iter() {
+        let mut m = milestone.clone();
+        m.status = MilestoneStatus::Pending;
+        initialized_milestones.push_back(m);
+    }
+    let deal_key = deal_key(
+        env,
+        &depositor,
+        &recipient,
+        &token_address,
+        &initialized_milestones,
+    );
+    let duplicates_open = open_deal(env, &deal_key);
+    if duplicates_open && !allow_duplicate {
+        return Err(Error::EscrowAlreadyExists);
+    }
+
+    let escrow = Escrow {
+        depositor,
+        recipient,
+        token_address,
+        total_amount,
+        total_released: 0,
+        milestones: initialized_milestones,
+        status: EscrowStatus::Created,
+        deadline,
+        resolution: Resolution::None,
+        kind,
+        fee_bps,
+        auth_mode,
+    };
+
+    store_new_escrow(env, escrow_id, &escrow, external_ref, terms_hash);
+    // An allowed duplicate leaves the key with the escrow it duplicates,
+    // which stays guarded until it closes
+    if !duplicates_open {
+        env.storage().persistent().set(&deal_key, &escrow_id);
+        env.storage()
+            .persistent()
+            .extend_ttl(&deal_key, 100, 2_000_000);
+    }
+    if let Some(cliff_until) = cliff_until {
+        let cliff_key = cliff_key(escrow_id);
+        env.storage().persistent().set(&cliff_key, &cliff_until);
+        env.storage()
+            .persistent()
+            .extend_ttl(&cliff_key, 100, 2_000_000);
+    }
+    if let RefundTo::Address(refund_to) = refund_to {
+        store_refund_address(env, escrow_id, &escrow, Some(refund_to));
+    }
+    if release_schedule != ReleaseSchedule::Immediate {
+        let schedule_key = release_schedule_key(escrow_id);
+        env.storage()
+            .persistent()
+            .set(&schedule_key, &release_schedule);
+        env.storage()
+            .persistent()
+            .extend_ttl(&schedule_key, 100, 2_000_000);
+    }
+    if dispute_policy == DisputePolicy::FreezeAll {
+        let policy_key = dispute_policy_key(escrow_id);
+        env.storage().persistent().set(&policy_key, &dispute_policy);
+        env.storage()
+            .persistent()
+            .extend_ttl(&policy_key, 100, 2_000_000);
+    }
+    if fee_payer != FeePayer::Recipient {
+        let mut reserve = 0i128;
+        for milestone in escrow.milestones.iter() {
+            let fee = calculate_fee(milestone.amount, escrow.fee_bps)?;
+            reserve += depositor_fee_share(fee_payer, fee);
+        }
+        let payer_key = fee_payer_key(escrow_id);
+        env.storage().persistent().set(&payer_key, &fee_payer);
+        env.storage()
+            .persistent()
+            .extend_ttl(&payer_key, 100, 2_000_000);
+        set_fee_reserve(env, escrow_id, reserve);
+    }
+    if lock_recipient {
+        let lock_key = recipient_lock_key(escrow_id);
+        env.storage().persistent().set(&lock_key, &true);
+        env.storage()
+            .persistent()
+            .extend_ttl(&lock_key, 100, 2_000_000);
+    }
+    Ok(())
+}
+
+/// Pulls the escrow's total, plus any fee reserve, from the depositor
+/// through `spender`'s allowance and activates it
+fn fund_escrow(env: &Env, escrow_id: u64, escrow: &mut Escrow, spender: &Address) {
+    let deposit = escrow.total_amount + fee_reserve(env, escrow_id);
+    let token_client = token::Client::new(env, &escrow.token_address);
+    token_client.transfer_from(
+        spender,
+        &escrow.depositor,
+        &env.current_contract_address(),
+        &deposit,
+    );
+    record_escrow_flow(env, escrow_id, &escrow.depositor, Flow::Deposited, deposit);
+
+    set_status(env, escrow_id, escrow, EscrowStatus::Active);
+    let storage_key = get_storage_key(escrow_id);
+    env.storage().persistent().set(&storage_key, escrow);
+    env.storage()
+        .persistent()
+        .extend_ttl(&storage_key, 100, 2_000_000);
+
+    // Standardized Event
+    env.events().publish(
+        (
+            Symbol::new(env, "Vaultix"),
+            Symbol::new(env, "EscrowFunded"),
+            escrow_id,
+        ),
+        escrow.total_amount,
+    );
+}
+
+/// Persists a freshly validated escrow with its lookup keys and announces it
+fn store_new_escrow(
+    env: &Env,
+    escrow_id: u64,
+    escrow: &Escrow,
+    external_ref: Option<BytesN<32>>,
+    terms_hash: Option<BytesN<32>>,
+) {
+    let storage_key = get_storage_key(escrow_id);
+    env.storage().persistent().set(&storage_key, escrow);
+    env.storage()
+        .persistent()
+        .extend_ttl(&storage_key, 100, 2_000_000);
+    add_to_recipient_index(env, &escrow.recipient, escrow_id);
+    track_active_escrow(env, &escrow.depositor, true);
+    shift_status_count(env, &escrow.depositor, None, Some(escrow.status));
+    shift_status_count(env, &escrow.recipient, None, Some(escrow.status));
+    if let Some(hash) = terms_hash {
+        let terms_key = terms_key(escrow_id);
+        env.storage().persistent().set(
+            &terms_key,
+            &Terms {
+                hash,
+                accepted: false,
+            },
+        );
+        env.storage()
+            .persistent()
+            .extend_ttl(&terms_key, 100, 2_000_000);
+    }
+    if let Some(ref external_ref) = external_ref {
+        let ref_key = external_ref_key(external_ref);
+        env.storage().persistent().set(&ref_key, &escrow_id);
+        env.storage()
+            .persistent()
+            .extend_ttl(&ref_key, 100, 2_000_000);
+    }
+
+    // Standardized Event
+    env.events().publish(
+        (
+            Symbol::new(env, "Vaultix"),
+            Symbol::new(env, "EscrowCreated"),
+            escrow_id,
+        ),
+        (
+            escrow.depositor.clone(),
+            escrow.recipient.clone(),
+            escrow.token_address.clone(),
+            escrow.total_amount,
+            escrow.deadline,
+        ),
+    );
+}
+
+fn get_storage_key(escrow_id: u64) -> (Symbol, u64) {
+    (symbol_short!("escrow"), escrow_id)
+}
+
+fn terms_key(escrow_id: u64) -> (Symbol, u64) {
+    (symbol_short!("terms"), escrow_id)
+}
+
+fn commitments_key(escrow_id: u64) -> (Symbol, u64) {
+    (symbol_short!("commit"), escrow_id)
+}
+
+fn counter_leg_key(escrow_id: u64) -> (Symbol, u64) {
+    (symbol_short!("swap"), escrow_id)
+}
+
+/// Shared release path. Outside an allowance the releaser signs the call;
+/// with one, the pre-approved budget stands in for their signature.
+fn release(
+    env: &Env,
+    escrow_id: u64,
+    milestone_index: u32,
+    via_allowance: bool,
+) -> Result<(), Error> {
+    extend_instance_ttl(env);
+    let storage_key = get_storage_key(escrow_id);
+    ensure_not_paused(env)?;
+
+    let mut escrow: Escrow = env
+        .storage()
+        .persistent()
+        .get(&storage_key)
+        .ok_or(Error::EscrowNotFound)?;
+    if !via_allowance {
+        escrow.authorize(escrow_id, escrow.releaser());
+    }
+
+    let (release_amount, next_status) =
+        ensure_releasable(env, escrow_id, &escrow, milestone_index)?;
+    let mut milestone = escrow.milestones.get_unchecked(milestone_index);
+    if via_allowance {
+        consume_release_allowance(env, escrow_id, release_amount)?;
+    }
+
+    settle(
+        env,
+        escrow_id,
+        &mut escrow,
+        Some(milestone_index),
+        release_amount,
+        Outcome::Release,
+    )?;
+    milestone.status = next_status;
+    escrow.milestones.set(milestone_index, milestone);
+
+    env.storage().persistent().set(&storage_key, &escrow);
+    env.storage()
+        .persistent()
+        .extend_ttl(&storage_key, 100, 2_000_000);
+
+    Ok(())
+}
+
+/// Checks `release_milestone` applies before settling, returning the amount
+/// releasable now and the status the milestone moves to
+fn ensure_releasable(
+    env: &Env,
+    escrow_id: u64,
+    escrow: &Escrow,
+    milestone_index: u32,
+) -> Result<(i128, MilestoneStatus), Error> {
+    if escrow.status != EscrowStatus::Active {
+        return Err(Error::EscrowNotActive);
+    }
+    // Hourly pools pay out through approved work logs only
+    if escrow.kind == EscrowKind::Hourly {
+        return Err(Error::InvalidEscrowKind);
+    }
+    let milestone = escrow
+        .milestones
+        .get(milestone_index)
+        .ok_or(Error::MilestoneNotFound)?;
+    // Expense milestones pay out through approved claims only
+    if expenses::is_expense_milestone(env, escrow_id, milestone_index) {
+        return Err(Error::InvalidEscrowKind);
+    }
+    if milestone.status != MilestoneStatus::Pending {
+        return Err(Error::MilestoneAlreadyReleased);
+    }
+    if milestone.amount == 0 {
+        return Err(Error::MilestoneSealed);
+    }
+    ensure_cliff_reached(env, escrow_id, milestone_index)?;
+    if dispute_policy(env, escrow_id) == DisputePolicy::FreezeAll
+        && has_disputed_milestone(env, escrow_id, escrow)
+    {
+        return Err(Error::AlreadyInDispute);
+    }
+    releasable_amount(env, escrow_id, milestone_index, &milestone)
+}
+
+/// Checks `cancel_escrow` applies before refunding, bar the buyer's consent
+/// a funded refund guarantee also needs
+fn ensure_cancellable(env: &Env, escrow_id: u64, escrow: &Escrow) -> Result<(), Error> {
+    if escrow.status != EscrowStatus::Active && escrow.status != EscrowStatus::Created {
+        return Err(Error::InvalidEscrowStatus);
+    }
+    if has_disputed_milestone(env, escrow_id, escrow) {
+        return Err(Error::AlreadyInDispute);
+    }
+    // Held releases are confirmed; only a dispute can claw them back.
+    // An hourly engagement may end early, refunding the unspent pool;
+    // logged hours not yet approved must be disputed before then.
+    if (escrow.total_released > 0 && escrow.kind != EscrowKind::Hourly)
+        || escrow
+            .milestones
+            .iter()
+            .any(|m| m.status == MilestoneStatus::Held)
+    {
+        return Err(Error::MilestoneAlreadyReleased);
+    }
+    Ok(())
+}
+
+/// Checks `raise_dispute` applies for `caller`, one of the escrow's parties
+#[cfg_attr(
+    not(any(feature = "disputes", feature = "views-extended")),
+    allow(dead_code)
+)]
+fn ensure_disputable(escrow: &Escrow, caller: &Address) -> Result<(), Error> {
+    if *caller != escrow.depositor && *caller != escrow.recipient {
+        return Err(Error::UnauthorizedAccess);
+    }
+    // Settling a swap would need a split of both legs; cancel it instead
+    if escrow.kind == EscrowKind::Swap {
+        return Err(Error::InvalidEscrowKind);
+    }
+    if escrow.status == EscrowStatus::Disputed {
+        return Err(Error::AlreadyInDispute);
+    }
+    // A ruling settles the deposited funds, so there must be some
+    if escrow.status == EscrowStatus::Created {
+        return Err(Error::EscrowNotActive);
+    }
+    if escrow.status != EscrowStatus::Active {
+        return Err(Error::InvalidEscrowStatus);
+    }
+    Ok(())
+}
+
+fn validate_auth_mode(depositor: &Address, auth_mode: &AuthMode) -> Result<(), Error> {
+    let valid = match auth_mode {
+        AuthMode::Strict => true,
+        AuthMode::Delegated(operator) => operator != depositor,
+        AuthMode::Multisig(co_signers) => {
+            !co_signers.is_empty()
+                && co_signers.len() <= MAX_CO_SIGNERS
+                && !co_signers.contains(depositor)
+        }
+    };
+    if !valid {
+        return Err(Error::InvalidAuthMode);
+    }
+    Ok(())
+}
+
+fn cliff_key(escrow_id: u64) -> (Symbol, u64) {
+    (symbol_short!("cliff"), escrow_id)
+}
+
+/// Milestone 0 of an escrow with a cliff stays locked until the cliff time
+fn ensure_cliff_reached(env: &Env, escrow_id: u64, milestone_index: u32) -> Result<(), Error> {
+    if milestone_index != 0 {
+        return Ok(());
+    }
+    let cliff_until: Option<u64> = env.storage().persistent().get(&cliff_key(escrow_id));
+    if cliff_until.is_some_and(|t| env.ledger().timestamp() < t) {
+        return Err(Error::CliffNotReached);
+    }
+    Ok(())
+}
+
+fn release_allowance_key(escrow_id: u64) -> (Symbol, u64) {
+    (symbol_short!("allow"), escrow_id)
+}
+
+/// Deducts one release of `amount` from the escrow's release allowance
+fn consume_release_allowance(env: &Env, escrow_id: u64, amount: i128) -> Result<(), Error> {
+    let key = release_allowance_key(escrow_id);
+    let mut allowance: ReleaseAllowance = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .ok_or(Error::AllowanceExceeded)?;
+    if allowance.releases_left == 0 || allowance.amount_left < amount {
+        return Err(Error::AllowanceExceeded);
+    }
+    allowance.releases_left -= 1;
+    allowance.amount_left -= amount;
+    env.storage().persistent().set(&key, &allowance);
+    Ok(())
+}
+
+/// Pays the depositor the counter-leg share of a released swap milestone.
+/// Both parties authorize a swap release, since assets move both ways.
+fn release_counter_leg(
+    env: &Env,
+    escrow: &Escrow,
+    escrow_id: u64,
+    milestone_index: u32,
+) -> Result<(), Error> {
+    if escrow.kind != EscrowKind::Swap {
+        return Ok(());
+    }
+    escrow.authorize(escrow_id, &escrow.recipient);
+
+    let leg: CounterLeg = env
+        .storage()
+        .persistent()
+        .get(&counter_leg_key(escrow_id))
+        .ok_or(Error::CounterLegNotFunded)?;
+    let amount = leg
+        .amounts
+        .get(milestone_index)
+        .ok_or(Error::MilestoneNotFound)?;
+
+    if quarantine::transfer_or_quarantine(
+        env,
+        escrow_id,
+        TransferLeg::CounterLeg,
+        &leg.token_address,
+        &payout_address(env, &escrow.depositor),
+        amount,
+    ) {
+        record_flow(env, &escrow.depositor, Flow::Received, amount);
+    }
+    Ok(())
+}
+
+fn external_ref_key(external_ref: &BytesN<32>) -> (Symbol, BytesN<32>) {
+    (symbol_short!("ext_ref"), external_ref.clone())
+}
+
+fn burned_total_key(token: &Address) -> (Symbol, Address) {
+    (symbol_short!("burned"), token.clone())
+}
+
+/// Pays a collected fee to the treasury, burning the configured share of it
+/// and passing the referral share of the rest to the escrow's referrer
+fn collect_fee(
+    env: &Env,
+    escrow_id: u64,
+    token_client: &token::Client,
+    token_address: &Address,
+    treasury: &Address,
+    fee: i128,
+) -> Result<(), Error> {
+    let burn_bps: i128 = env
+        .storage()
+        .instance()
+        .get(&symbol_short!("burn_bps"))
+        .unwrap_or(0);
+    let burn_amount = calculate_fee(fee, burn_bps)?;
+    let mut treasury_amount = fee
+        .checked_sub(burn_amount)
+        .ok_or(Error::InvalidMilestoneAmount)?;
+
+    let contract_address = env.current_contract_address();
+    if let Some(referrer) = referrals::escrow_referrer(env, escrow_id) {
+        let referral_amount = calculate_fee(treasury_amount, referrals::referral_share_bps(env))?;
+        if referral_amount > 0 {
+            quarantine::transfer_or_quarantine(
+                env,
+                escrow_id,
+                TransferLeg::Referral,
+                token_address,
+                &referrer,
+                referral_amount,
+            );
+            treasury_amount -= referral_amount;
+        }
+    }
+    if treasury_amount > 0 {
+        quarantine::transfer_or_quarantine(
+            env,
+            escrow_id,
+            TransferLeg::Treasury,
+            token_address,
+            treasury,
+            treasury_amount,
+        );
+    }
+    if burn_amount > 0 {
+        let burn_address: Option<Address> =
+            env.storage().instance().get(&symbol_short!("burn_addr"));
+        match burn_address {
+            Some(addr) => {
+                quarantine::transfer_or_quarantine(
+                    env,
+                    escrow_id,
+                    TransferLeg::Burn,
+                    token_address,
+                    &addr,
+                    burn_amount,
+                );
+            }
+            None => token_client.burn(&contract_address, &burn_amount),
+        }
+
+        let key = burned_total_key(token_address);
+        let burned: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+        let burned = burned
+            .checked_add(burn_amount)
+            .ok_or(Error::InvalidMilestoneAmount)?;
+        env.storage().persistent().set(&key, &burned);
+        env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+    }
+    Ok(())
+}
+
+/// Number of entries in `recipient`'s escrow index. Each entry has its own
+/// key so the index grows without rewriting a single value.
+fn recipient_index_len_key(recipient: &Address) -> (Symbol, Address) {
+    (symbol_short!("rcpt_len"), recipient.clone())
+}
+
+fn recipient_index_key(recipient: &Address, slot: u32) -> (Symbol, Address, u32) {
+    (symbol_short!("rcpt_idx"), recipient.clone(), slot)
+}
+
+fn recipient_index_len(env: &Env, recipient: &Address) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&recipient_index_len_key(recipient))
+        .unwrap_or(0)
+}
+
+fn add_to_recipient_index(env: &Env, recipient: &Address, escrow_id: u64) {
+    let slot = recipient_index_len(env, recipient);
+    let key = recipient_index_key(recipient, slot);
+    env.storage().persistent().set(&key, &escrow_id);
+    env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+    let len_key = recipient_index_len_key(recipient);
+    env.storage().persistent().set(&len_key, &(slot + 1));
+    env.storage()
+        .persistent()
+        .extend_ttl(&len_key, 100, 2_000_000);
+}
+
+/// Key of the open escrow for a deal: a hash of its parties, token and
+/// milestone terms, so retried creations are recognized whatever their ID.
+/// Milestones are hashed as created, whatever their progress since.
+fn deal_key(
+    env: &Env,
+    depositor: &Address,
+    recipient: &Address,
+    token: &Address,
+    milestones: &Vec<Milestone>,
+) -> (Symbol, BytesN<32>) {
+    let mut terms = Vec::new(env);
+    for mut milestone in milestones.iter() {
+        milestone.status = MilestoneStatus::Pending;
+        terms.push_back(milestone);
+    }
+    let deal = (depositor.clone(), recipient.clone(), token.clone(), terms);
+    let hash = env.crypto().sha256(&deal.to_xdr(env));
+    (symbol_short!("deal"), hash)
+}
+
+fn escrow_deal_key(env: &Env, escrow: &Escrow) -> (Symbol, BytesN<32>) {
+    deal_key(
+        env,
+        &escrow.depositor,
+        &escrow.recipient,
+        &escrow.token_address,
+        &escrow.milestones,
+    )
+}
+
+/// Frees `escrow`'s deal when its parties, token or milestones are about to
+/// change or it is merged away, unless the deal is recorded for another
+/// escrow
+fn forget_deal(env: &Env, escrow_id: u64, escrow: &Escrow) {
+    let key = escrow_deal_key(env, escrow);
+    let recorded: Option<u64> = env.storage().persistent().get(&key);
+    if recorded == Some(escrow_id) {
+        env.storage().persistent().remove(&key);
+    }
+}
+
+/// Records `escrow` as the open escrow for its changed deal, unless
+/// another open escrow already holds it
+fn record_deal(env: &Env, escrow_id: u64, escrow: &Escrow) {
+    let key = escrow_deal_key(env, escrow);
+    if !open_deal(env, &key) {
+        env.storage().persistent().set(&key, &escrow_id);
+        env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+    }
+}
+
+/// Whether the escrow recorded for a deal is still created or active.
+/// Closed and archived escrows free the deal without cleanup.
+fn open_deal(env: &Env, deal_key: &(Symbol, BytesN<32>)) -> bool {
+    let escrow_id: Option<u64> = env.storage().persistent().get(deal_key);
+    let escrow: Option<Escrow> =
+        escrow_id.and_then(|id| env.storage().persistent().get(&get_storage_key(id)));
+    escrow.is_some_and(|e| matches!(e.status, EscrowStatus::Created | EscrowStatus::Active))
+}
+
+fn decimals_key(token: &Address) -> (Symbol, Address) {
+    (symbol_short!("decimals"), token.clone())
+}
+
+/// Decimals of `token`, asked of the token contract once and cached since
+/// they never change
+fn token_decimals(env: &Env, token: &Address) -> u32 {
+    let key = decimals_key(token);
+    let storage = env.storage().persistent();
+    let decimals = match storage.get(&key) {
+        Some(decimals) => decimals,
+        None => {
+            let decimals = token::Client::new(env, token).decimals();
+            storage.set(&key, &decimals);
+            decimals
+        }
+    };
+    storage.extend_ttl(&key, 100, 2_000_000);
+    decimals
+}
+
+/// `10^decimals` of `token`, the raw amount of one whole display unit
+fn token_unit(env: &Env, token: &Address) -> Result<i128, Error> {
+    10i128
+        .checked_pow(token_decimals(env, token))
+        .ok_or(Error::InvalidMilestoneAmount)
+}
+
+fn ensure_above_dust(env: &Env, token: &Address, milestones: &Vec<Milestone>) -> Result<(), Error> {
+    let min_units: i128 = env
+        .storage()
+        .instance()
+        .get(&symbol_short!("min_units"))
+        .unwrap_or(0);
+    if min_units == 0 {
+        return Ok(());
+    }
+    let min_amount = min_units
+        .checked_mul(token_unit(env, token)?)
+        .ok_or(Error::InvalidMilestoneAmount)?;
+    if milestones.iter().any(|m| m.amount < min_amount) {
+        return Err(Error::InvalidMilestoneAmount);
+    }
+    Ok(())
+}
+
+fn tvl_key(token: &Option<Address>) -> (Symbol, Option<Address>) {
+    (symbol_short!("tvl"), token.clone())
+}
 
-        if escrow.status != EscrowStatus::Active && escrow.status != EscrowStatus::Created {
-            return Err(Error::InvalidEscrowStatus);
-        }
-        if escrow.total_released > 0 {
-            return Err(Error::MilestoneAlreadyReleased);
-        }
+fn tvl_cap_key(token: &Option<Address>) -> (Symbol, Option<Address>) {
+    (symbol_short!("tvl_cap"), token.clone())
+}
 
-        if escrow.status == EscrowStatus::Active {
-            let token_client = token::Client::new(&env, &escrow.token_address);
-            token_client.transfer(
-                &env.current_contract_address(),
-                &escrow.depositor,
-                &escrow.total_amount,
-            );
+/// Adds a new escrow's total to the global and per-token locked value,
+/// failing if either would go over its cap.
+fn lock_value(env: &Env, token: &Address, amount: i128) -> Result<(), Error> {
+    for scope in [None, Some(token.clone())] {
+        let storage = env.storage().instance();
+        let locked: i128 = storage.get(&tvl_key(&scope)).unwrap_or(0);
+        let cap: i128 = storage.get(&tvl_cap_key(&scope)).unwrap_or(0);
+        let locked = locked
+            .checked_add(amount)
+            .ok_or(Error::InvalidMilestoneAmount)?;
+        if cap > 0 && locked > cap {
+            return Err(Error::TvlCapExceeded);
         }
+        storage.set(&tvl_key(&scope), &locked);
+    }
+    Ok(())
+}
 
-        escrow.status = EscrowStatus::Cancelled;
-        env.storage().persistent().set(&storage_key, &escrow);
-        env.storage()
-            .persistent()
-            .extend_ttl(&storage_key, 100, 2_000_000);
+/// Frees a closed escrow's total. Escrows created before value was tracked
+/// never added to it, so the tally floors at zero.
+fn unlock_value(env: &Env, token: &Address, amount: i128) {
+    for scope in [None, Some(token.clone())] {
+        let storage = env.storage().instance();
+        let locked: i128 = storage.get(&tvl_key(&scope)).unwrap_or(0);
+        storage.set(&tvl_key(&scope), &(locked - amount).max(0));
+    }
+}
 
-        // Standardized Event
-        env.events().publish(
-            (
-                Symbol::new(&env, "Vaultix"),
-                Symbol::new(&env, "EscrowCancelled"),
-                escrow_id,
-            ),
-            escrow.depositor.clone(), // cancelled_by
-        );
+/// Rejects a new escrow once the depositor holds the configured number of
+/// escrows that are not yet finished (created, funded or disputed).
+fn ensure_below_active_cap(env: &Env, depositor: &Address) -> Result<(), Error> {
+    let max_active: u32 = env
+        .storage()
+        .instance()
+        .get(&symbol_short!("max_activ"))
+        .unwrap_or(0);
+    if max_active == 0 {
+        return Ok(());
+    }
 
-        Ok(())
+    if active_escrow_count(env, depositor) >= max_active {
+        return Err(Error::TooManyActiveEscrows);
     }
+    Ok(())
+}
 
-    pub fn complete_escrow(env: Env, escrow_id: u64) -> Result<(), Error> {
-        let storage_key = get_storage_key(escrow_id);
-        ensure_not_paused(&env)?;
+fn active_escrows_key(depositor: &Address) -> (Symbol, Address) {
+    (symbol_short!("dep_activ"), depositor.clone())
+}
 
-        let mut escrow: Escrow = env
-            .storage()
-            .persistent()
-            .get(&storage_key)
-            .ok_or(Error::EscrowNotFound)?;
-        escrow.depositor.require_auth();
+fn active_escrow_count(env: &Env, depositor: &Address) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&active_escrows_key(depositor))
+        .unwrap_or(0)
+}
 
-        if escrow.status != EscrowStatus::Active {
-            return Err(Error::InvalidEscrowStatus);
-        }
-        if !verify_all_released(&escrow.milestones) {
-            return Err(Error::EscrowNotActive);
-        }
+/// Counts an escrow in or out of the depositor's unfinished escrows as it
+/// is created, reaches a terminal status or is merged away
+fn track_active_escrow(env: &Env, depositor: &Address, opened: bool) {
+    let key = active_escrows_key(depositor);
+    let active = active_escrow_count(env, depositor);
+    let active = if opened {
+        active + 1
+    } else {
+        active.saturating_sub(1)
+    };
+    env.storage().persistent().set(&key, &active);
+    env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+}
 
-        escrow.status = EscrowStatus::Completed;
-        env.storage().persistent().set(&storage_key, &escrow);
+/// Money flow categories tracked per address for `get_address_stats`, and
+/// per escrow for `get_escrow_accounting`
+#[derive(Clone, Copy)]
+enum Flow {
+    Deposited,
+    Received,
+    Refunded,
+    FeesPaid,
+}
+
+fn address_totals_key(party: &Address) -> (Symbol, Address) {
+    (symbol_short!("totals"), party.clone())
+}
+
+fn get_address_totals(env: &Env, party: &Address) -> AddressTotals {
+    env.storage()
+        .persistent()
+        .get(&address_totals_key(party))
+        .unwrap_or(AddressTotals {
+            deposited: 0,
+            received: 0,
+            refunded: 0,
+            fees_paid: 0,
+        })
+}
+
+/// Adds `amount` to one of `party`'s running totals. Amounts are summed in
+/// raw token units across all tokens.
+fn record_flow(env: &Env, party: &Address, flow: Flow, amount: i128) {
+    if amount == 0 {
+        return;
+    }
+    let mut totals = get_address_totals(env, party);
+    add_flow(&mut totals, flow, amount);
+
+    let key = address_totals_key(party);
+    env.storage().persistent().set(&key, &totals);
+    env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+}
+
+/// `record_flow` for movements of the escrow's own token, which also count
+/// towards the escrow's accounting. Counter-leg flows of a swap are in
+/// another token and only go through `record_flow`.
+fn record_escrow_flow(env: &Env, escrow_id: u64, party: &Address, flow: Flow, amount: i128) {
+    if amount == 0 {
+        return;
+    }
+    record_flow(env, party, flow, amount);
+
+    let key = escrow_totals_key(escrow_id);
+    let mut totals = get_escrow_totals(env, escrow_id);
+    add_flow(&mut totals, flow, amount);
+    env.storage().persistent().set(&key, &totals);
+    env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+}
+
+fn add_flow(totals: &mut AddressTotals, flow: Flow, amount: i128) {
+    let total = match flow {
+        Flow::Deposited => &mut totals.deposited,
+        Flow::Received => &mut totals.received,
+        Flow::Refunded => &mut totals.refunded,
+        Flow::FeesPaid => &mut totals.fees_paid,
+    };
+    *total = total.saturating_add(amount);
+}
+
+fn escrow_totals_key(escrow_id: u64) -> (Symbol, u64) {
+    (symbol_short!("esc_tot"), escrow_id)
+}
+
+/// Flows of one escrow's token, kept in the same shape as an address's
+fn get_escrow_totals(env: &Env, escrow_id: u64) -> AddressTotals {
+    env.storage()
+        .persistent()
+        .get(&escrow_totals_key(escrow_id))
+        .unwrap_or(AddressTotals {
+            deposited: 0,
+            received: 0,
+            refunded: 0,
+            fees_paid: 0,
+        })
+}
+
+fn status_counts_key(party: &Address) -> (Symbol, Address) {
+    (symbol_short!("st_counts"), party.clone())
+}
+
+fn get_status_counts(env: &Env, party: &Address) -> StatusCounts {
+    env.storage()
+        .persistent()
+        .get(&status_counts_key(party))
+        .unwrap_or(StatusCounts {
+            created: 0,
+            active: 0,
+            completed: 0,
+            cancelled: 0,
+            disputed: 0,
+            resolved: 0,
+        })
+}
+
+/// Moves one of `party`'s escrows between status counts as it is created
+/// (`from` is `None`), changes status, or changes hands or is merged away
+/// (`to` is `None`)
+fn shift_status_count(
+    env: &Env,
+    party: &Address,
+    from: Option<EscrowStatus>,
+    to: Option<EscrowStatus>,
+) {
+    let mut counts = get_status_counts(env, party);
+    if let Some(status) = from {
+        let count = counts.of(status);
+        *count = count.saturating_sub(1);
+    }
+    if let Some(status) = to {
+        *counts.of(status) += 1;
+    }
+    let key = status_counts_key(party);
+    env.storage().persistent().set(&key, &counts);
+    env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+}
+
+/// Updates the escrow's status, noting when it reached a terminal one so
+/// `archive_escrow` can enforce the retention window.
+fn set_status(env: &Env, escrow_id: u64, escrow: &mut Escrow, status: EscrowStatus) {
+    let is_terminal = |s: EscrowStatus| {
+        matches!(
+            s,
+            EscrowStatus::Completed | EscrowStatus::Cancelled | EscrowStatus::Resolved
+        )
+    };
+    if is_terminal(status) && !is_terminal(escrow.status) {
+        unlock_value(env, &escrow.token_address, escrow.total_amount);
+        return_fee_reserve(env, escrow_id, escrow);
+        track_active_escrow(env, &escrow.depositor, false);
+    }
+    if status != escrow.status {
+        shift_status_count(env, &escrow.depositor, Some(escrow.status), Some(status));
+        shift_status_count(env, &escrow.recipient, Some(escrow.status), Some(status));
+    }
+    escrow.status = status;
+
+    if is_terminal(status) {
+        let key = closed_at_key(escrow_id);
         env.storage()
             .persistent()
-            .extend_ttl(&storage_key, 100, 2_000_000);
+            .set(&key, &env.ledger().timestamp());
+        env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+    }
+}
 
-        // Standardized Event
-        env.events().publish(
-            (
-                Symbol::new(&env, "Vaultix"),
-                Symbol::new(&env, "EscrowCompleted"),
-                escrow_id,
-            ),
-            (),
-        );
+fn closed_at_key(escrow_id: u64) -> (Symbol, u64) {
+    (symbol_short!("closed_at"), escrow_id)
+}
 
-        Ok(())
+fn archive_key(escrow_id: u64) -> (Symbol, u64) {
+    (symbol_short!("archive"), escrow_id)
+}
+
+fn recipient_lock_key(escrow_id: u64) -> (Symbol, u64) {
+    (symbol_short!("rcpt_lock"), escrow_id)
+}
+
+/// Checks that the escrow's recipient may still be replaced and requires
+/// the depositor's consent. Locked escrows never allow it, and nobody may
+/// swap the payee while a confirmed or disputed payment is outstanding.
+fn ensure_recipient_changeable(env: &Env, escrow_id: u64, escrow: &Escrow) -> Result<(), Error> {
+    if env
+        .storage()
+        .persistent()
+        .has(&recipient_lock_key(escrow_id))
+    {
+        return Err(Error::RecipientLocked);
+    }
+    let in_flight = escrow
+        .milestones
+        .iter()
+        .any(|m| m.status == MilestoneStatus::Held || m.status == MilestoneStatus::Disputed);
+    let undelivered = unclaimed::unclaimed_total(env, escrow_id) > 0;
+    if escrow.status == EscrowStatus::Disputed || in_flight || undelivered {
+        return Err(Error::InvalidEscrowStatus);
     }
+    escrow.authorize(escrow_id, &escrow.depositor);
+    Ok(())
 }
 
-fn get_storage_key(escrow_id: u64) -> (Symbol, u64) {
-    (symbol_short!("escrow"), escrow_id)
+fn refund_address_key(escrow_id: u64) -> (Symbol, u64) {
+    (symbol_short!("refund_to"), escrow_id)
+}
+
+fn store_refund_address(env: &Env, escrow_id: u64, escrow: &Escrow, refund_to: Option<Address>) {
+    let key = refund_address_key(escrow_id);
+    match refund_to {
+        Some(ref refund_to) if *refund_to != escrow.depositor => {
+            env.storage().persistent().set(&key, refund_to);
+            env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+        }
+        _ => env.storage().persistent().remove(&key),
+    }
+}
+
+/// Address the escrow's refunds are paid to: the override set by the
+/// depositor, or the depositor itself
+fn refund_address(env: &Env, escrow_id: u64, escrow: &Escrow) -> Address {
+    env.storage()
+        .persistent()
+        .get(&refund_address_key(escrow_id))
+        .unwrap_or(escrow.depositor.clone())
+}
+
+fn payout_address_key(party: &Address) -> (Symbol, Address) {
+    (symbol_short!("payout"), party.clone())
+}
+
+/// Where release payouts for `party` are sent: its registered payout address,
+/// or the party itself. Refunds always go back to the party's own address.
+fn payout_address(env: &Env, party: &Address) -> Address {
+    env.storage()
+        .persistent()
+        .get(&payout_address_key(party))
+        .unwrap_or(party.clone())
 }
 
 fn ensure_not_paused(env: &Env) -> Result<(), Error> {
@@ -719,6 +3626,242 @@ fn admin_storage_key() -> Symbol {
     symbol_short!("admin")
 }
 
+fn attestor_storage_key() -> Symbol {
+    symbol_short!("attestor")
+}
+
+fn attestation_key(escrow_id: u64, milestone_index: u32) -> (Symbol, u64, u32) {
+    (symbol_short!("attest"), escrow_id, milestone_index)
+}
+
+fn partial_dispute_key(escrow_id: u64, milestone_index: u32) -> (Symbol, u64, u32) {
+    (symbol_short!("pdispute"), escrow_id, milestone_index)
+}
+
+fn fee_payer_key(escrow_id: u64) -> (Symbol, u64) {
+    (symbol_short!("fee_payer"), escrow_id)
+}
+
+fn fee_payer(env: &Env, escrow_id: u64) -> FeePayer {
+    env.storage()
+        .persistent()
+        .get(&fee_payer_key(escrow_id))
+        .unwrap_or(FeePayer::Recipient)
+}
+
+/// Part of a `fee` the depositor bears under `payer`
+fn depositor_fee_share(payer: FeePayer, fee: i128) -> i128 {
+    match payer {
+        FeePayer::Recipient => 0,
+        FeePayer::Depositor => fee,
+        FeePayer::Split => fee / 2,
+    }
+}
+
+fn fee_reserve_key(escrow_id: u64) -> (Symbol, u64) {
+    (symbol_short!("fee_rsv"), escrow_id)
+}
+
+/// Unspent part of the depositor's fee reserve
+fn fee_reserve(env: &Env, escrow_id: u64) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&fee_reserve_key(escrow_id))
+        .unwrap_or(0)
+}
+
+fn set_fee_reserve(env: &Env, escrow_id: u64, reserve: i128) {
+    let key = fee_reserve_key(escrow_id);
+    if reserve == 0 {
+        env.storage().persistent().remove(&key);
+        return;
+    }
+    env.storage().persistent().set(&key, &reserve);
+    env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+}
+
+/// Refunds what is left of the fee reserve as the escrow closes. An escrow
+/// closing without ever being funded never paid its reserve in, whatever
+/// status it reached.
+fn return_fee_reserve(env: &Env, escrow_id: u64, escrow: &Escrow) {
+    let reserve = fee_reserve(env, escrow_id);
+    set_fee_reserve(env, escrow_id, 0);
+    if reserve == 0 || get_escrow_totals(env, escrow_id).deposited == 0 {
+        return;
+    }
+    if quarantine::transfer_or_quarantine(
+        env,
+        escrow_id,
+        TransferLeg::FeeReserve,
+        &escrow.token_address,
+        &refund_address(env, escrow_id, escrow),
+        reserve,
+    ) {
+        record_escrow_flow(env, escrow_id, &escrow.depositor, Flow::Refunded, reserve);
+    }
+}
+
+fn dispute_policy_key(escrow_id: u64) -> (Symbol, u64) {
+    (symbol_short!("disp_pol"), escrow_id)
+}
+
+fn dispute_policy(env: &Env, escrow_id: u64) -> DisputePolicy {
+    env.storage()
+        .persistent()
+        .get(&dispute_policy_key(escrow_id))
+        .unwrap_or(DisputePolicy::ContinueOthers)
+}
+
+/// Whether any milestone is disputed, either outright or through an open
+/// partial dispute on a slice of it
+fn has_disputed_milestone(env: &Env, escrow_id: u64, escrow: &Escrow) -> bool {
+    escrow.milestones.iter().enumerate().any(|(i, m)| {
+        m.status == MilestoneStatus::Disputed
+            || env
+                .storage()
+                .persistent()
+                .has(&partial_dispute_key(escrow_id, i as u32))
+    })
+}
+
+/// Pays a confirmed milestone to the payee (no platform fee) and marks it
+/// `next_status`
+fn pay_confirmed(
+    env: &Env,
+    escrow_id: u64,
+    escrow: &mut Escrow,
+    milestone_index: u32,
+    release_amount: i128,
+    next_status: MilestoneStatus,
+) -> Result<(), Error> {
+    let mut milestone = escrow
+        .milestones
+        .get(milestone_index)
+        .ok_or(Error::MilestoneNotFound)?;
+    settle(
+        env,
+        escrow_id,
+        escrow,
+        Some(milestone_index),
+        release_amount,
+        Outcome::Confirmed,
+    )?;
+    milestone.status = next_status;
+    escrow.milestones.set(milestone_index, milestone);
+
+    env.storage()
+        .persistent()
+        .set(&get_storage_key(escrow_id), escrow);
+
+    Ok(())
+}
+
+fn held_release_key(escrow_id: u64, milestone_index: u32) -> (Symbol, u64, u32) {
+    (symbol_short!("held"), escrow_id, milestone_index)
+}
+
+fn stablecoin_guard_key(token: &Address) -> (Symbol, Address) {
+    (symbol_short!("stable"), token.clone())
+}
+
+fn depeg_override_key(escrow_id: u64, milestone_index: u32) -> (Symbol, u64, u32) {
+    (symbol_short!("depeg_ok"), escrow_id, milestone_index)
+}
+
+/// Refuses a payout in a guarded stablecoin that trades outside its band,
+/// unless both parties overrode the guard for this milestone. An override
+/// is spent by the payout it lets through.
+fn ensure_pegged(
+    env: &Env,
+    escrow_id: u64,
+    escrow: &Escrow,
+    milestone_index: u32,
+) -> Result<(), Error> {
+    let guard: Option<StablecoinGuard> = env
+        .storage()
+        .instance()
+        .get(&stablecoin_guard_key(&escrow.token_address));
+    let Some(guard) = guard else {
+        return Ok(());
+    };
+    let override_key = depeg_override_key(escrow_id, milestone_index);
+    if env.storage().persistent().has(&override_key) {
+        env.storage().persistent().remove(&override_key);
+        return Ok(());
+    }
+
+    let price = PriceOracleClient::new(env, &guard.oracle).price(&escrow.token_address);
+    let deviation = (price - guard.peg).abs();
+    if price <= 0 || deviation.saturating_mul(10_000) > guard.band_bps.saturating_mul(guard.peg) {
+        return Err(Error::DepegDetected);
+    }
+    Ok(())
+}
+
+fn release_schedule_key(escrow_id: u64) -> (Symbol, u64) {
+    (symbol_short!("rel_sched"), escrow_id)
+}
+
+fn tranche_release_key(escrow_id: u64, milestone_index: u32) -> (Symbol, u64, u32) {
+    (symbol_short!("tranche"), escrow_id, milestone_index)
+}
+
+/// Whether any milestone of the escrow still has unclaimed tranches
+fn has_pending_tranches(env: &Env, escrow_id: u64, escrow: &Escrow) -> bool {
+    (0..escrow.milestones.len()).any(|i| {
+        env.storage()
+            .persistent()
+            .has(&tranche_release_key(escrow_id, i))
+    })
+}
+
+/// Amount of a pending milestone that may be released now, and the status the
+/// milestone moves to. A partially disputed milestone releases only its
+/// undisputed remainder and stays `Disputed` until the slice is resolved.
+fn releasable_amount(
+    env: &Env,
+    escrow_id: u64,
+    milestone_index: u32,
+    milestone: &Milestone,
+) -> Result<(i128, MilestoneStatus), Error> {
+    let disputed: Option<i128> = env
+        .storage()
+        .persistent()
+        .get(&partial_dispute_key(escrow_id, milestone_index));
+    match disputed {
+        None => Ok((milestone.amount, MilestoneStatus::Released)),
+        Some(disputed) => {
+            let remainder = milestone
+                .amount
+                .checked_sub(disputed)
+                .ok_or(Error::InvalidMilestoneAmount)?;
+            if remainder <= 0 {
+                return Err(Error::AlreadyInDispute);
+            }
+            Ok((remainder, MilestoneStatus::Disputed))
+        }
+    }
+}
+
+fn get_instance_ttl_config(env: &Env) -> InstanceTtl {
+    env.storage()
+        .instance()
+        .get(&symbol_short!("ttl_cfg"))
+        .unwrap_or(InstanceTtl {
+            threshold: DEFAULT_INSTANCE_TTL_THRESHOLD,
+            extend_to: DEFAULT_INSTANCE_TTL_EXTEND_TO,
+        })
+}
+
+/// Keeps the instance entry (treasury, fee config, pause state) alive.
+/// Called at the top of every entrypoint that writes state.
+fn extend_instance_ttl(env: &Env) {
+    let config = get_instance_ttl_config(env);
+    env.storage()
+        .instance()
+        .extend_ttl(config.threshold, config.extend_to);
+}
+
 fn get_admin(env: &Env) -> Result<Address, Error> {
     env.storage()
         .persistent()
@@ -727,39 +3870,167 @@ fn get_admin(env: &Env) -> Result<Address, Error> {
 }
 
 fn validate_milestones(milestones: &Vec<Milestone>) -> Result<i128, Error> {
-    if milestones.len() > 20 {
-        return Err(Error::VectorTooLarge);
+    vaultix_core::validate_milestone_amounts(milestones.iter().map(|m| m.amount))
+        .map_err(Error::from)
+}
+
+/// Builds milestones from basis-point weights of `total_amount`, assigning
+/// the rounding remainder to the last milestone.
+fn weighted_milestones(
+    env: &Env,
+    total_amount: i128,
+    labels: &Vec<Symbol>,
+    weights_bps: &Vec<u32>,
+) -> Result<Vec<Milestone>, Error> {
+    if labels.len() != weights_bps.len() || labels.is_empty() {
+        return Err(Error::InvalidMilestoneWeights);
     }
-    let mut total: i128 = 0;
-    for milestone in milestones.iter() {
-        if milestone.amount <= 0 {
-            return Err(Error::ZeroAmount);
-        }
-        total = total
-            .checked_add(milestone.amount)
+    let weight_sum: u64 = weights_bps.iter().map(u64::from).sum();
+    if weight_sum != vaultix_core::fee::BPS_DENOMINATOR as u64 {
+        return Err(Error::InvalidMilestoneWeights);
+    }
+
+    let mut milestones = Vec::new(env);
+    let mut assigned: i128 = 0;
+    let last = labels.len() - 1;
+    for (i, description) in labels.iter().enumerate() {
+        let amount = if i as u32 == last {
+            total_amount
+                .checked_sub(assigned)
+                .ok_or(Error::InvalidMilestoneAmount)?
+        } else {
+            calculate_fee(total_amount, weights_bps.get_unchecked(i as u32) as i128)?
+        };
+        assigned = assigned
+            .checked_add(amount)
             .ok_or(Error::InvalidMilestoneAmount)?;
+        milestones.push_back(Milestone {
+            amount,
+            status: MilestoneStatus::Pending,
+            description,
+        });
+    }
+    Ok(milestones)
+}
+
+fn ensure_unique_labels(milestones: &Vec<Milestone>) -> Result<(), Error> {
+    for (i, milestone) in milestones.iter().enumerate() {
+        for other in milestones.iter().skip(i + 1) {
+            if milestone.description == other.description {
+                return Err(Error::DuplicateMilestoneLabel);
+            }
+        }
     }
-    Ok(total)
+    Ok(())
 }
 
 fn verify_all_released(milestones: &Vec<Milestone>) -> bool {
     for milestone in milestones.iter() {
-        if milestone.status != MilestoneStatus::Released {
+        if milestone.status != MilestoneStatus::Released
+            && milestone.status != MilestoneStatus::SettledOffChain
+        {
             return false;
         }
     }
     true
 }
 
-fn calculate_fee(amount: i128, fee_bps: i128) -> Result<i128, Error> {
-    let fee_numerator = amount
-        .checked_mul(fee_bps)
-        .ok_or(Error::InvalidMilestoneAmount)?;
-    let fee = fee_numerator
-        .checked_div(BPS_DENOMINATOR)
-        .ok_or(Error::InvalidMilestoneAmount)?;
+/// Fee owed on releasing `amount` of `milestone`. An external strategy, when
+/// configured, is quoted with the milestone as it is being released.
+fn quote_fee(
+    env: &Env,
+    escrow: &Escrow,
+    milestone: &Milestone,
+    amount: i128,
+    fee_bps: i128,
+) -> Result<i128, Error> {
+    let strategy: Option<Address> = env.storage().instance().get(&symbol_short!("fee_strat"));
+    let Some(strategy) = strategy else {
+        return calculate_fee(amount, fee_bps);
+    };
+
+    let mut releasing = milestone.clone();
+    releasing.amount = amount;
+    let fee = FeeStrategyClient::new(env, &strategy).quote(escrow, &releasing);
+    if !(0..=amount).contains(&fee) {
+        return Err(Error::InvalidFeeConfiguration);
+    }
     Ok(fee)
 }
 
+fn policy_key() -> Symbol {
+    symbol_short!("policy")
+}
+
+fn notifier_key_key() -> Symbol {
+    symbol_short!("notifier")
+}
+
+/// Asks the configured policy engine, if any, to approve `action`
+fn ensure_policy_allows(
+    env: &Env,
+    action: PolicyAction,
+    depositor: &Address,
+    recipient: &Address,
+    token: &Address,
+    amount: i128,
+) -> Result<(), Error> {
+    let Some(policy) = VaultixEscrow::get_policy(env.clone()) else {
+        return Ok(());
+    };
+    let parties = vec![env, depositor.clone(), recipient.clone()];
+    if !PolicyEngineClient::new(env, &policy).check(&action, &parties, token, &amount) {
+        return Err(Error::UnauthorizedAccess);
+    }
+    Ok(())
+}
+
+fn fee_change_key(index: u32) -> (Symbol, u32) {
+    (symbol_short!("fee_chg"), index)
+}
+
+fn fee_change_count_key() -> Symbol {
+    symbol_short!("fee_count")
+}
+
+/// Appends a platform fee change to the append-only fee history
+fn record_fee_change(env: &Env, fee_bps: i128) {
+    let index: u32 = env
+        .storage()
+        .instance()
+        .get(&fee_change_count_key())
+        .unwrap_or(0);
+    env.storage()
+        .instance()
+        .set(&fee_change_count_key(), &(index + 1));
+    let key = fee_change_key(index);
+    env.storage().persistent().set(
+        &key,
+        &FeeChange {
+            fee_bps,
+            effective_ledger: env.ledger().sequence(),
+        },
+    );
+    env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+}
+
+/// Platform fee currently configured, snapshotted into new escrows
+fn current_fee_bps(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&symbol_short!("fee_bps"))
+        .unwrap_or(DEFAULT_FEE_BPS)
+}
+
+fn calculate_fee(amount: i128, fee_bps: i128) -> Result<i128, Error> {
+    vaultix_core::fee::calculate_fee(amount, fee_bps).ok_or(Error::InvalidMilestoneAmount)
+}
+
+#[cfg(test)]
+mod auth_test;
+#[cfg(test)]
+mod regression_test;
 #[cfg(test)]
 mod test;
+#[cfg(test)]
+mod vectors_test;
@@ -0,0 +1,232 @@
+use crate::criteria::criteria_key;
+use crate::expenses;
+use crate::{
+    cliff_key, commitments_key, depeg_override_key, dispute_policy, dispute_policy_key,
+    ensure_not_paused, ensure_unique_labels, escrow_totals_key, extend_instance_ttl, fee_payer,
+    fee_payer_key, fee_reserve, forget_deal, get_escrow_totals, get_storage_key,
+    has_disputed_milestone, recipient_lock_key, record_deal, referrals, refund_address,
+    refund_address_key, release_allowance_key, release_schedule_key, set_fee_reserve,
+    shift_status_count, track_active_escrow, Criterion, DisputePolicy, Error, Escrow, EscrowKind,
+    EscrowStatus, FeePayer, MilestoneStatus, ReleaseSchedule, VaultixEscrow, VaultixEscrowClient,
+};
+use soroban_sdk::{contractimpl, symbol_short, Address, Env, Symbol, Vec};
+
+#[cfg(any(test, feature = "testutils"))]
+use crate::__VaultixEscrow_fn_set_registry;
+
+#[contractimpl]
+impl VaultixEscrow {
+    /// Folds the escrows in `escrow_ids` into the first one, appending their
+    /// milestones and totals. All must be untouched standard escrows between
+    /// the same parties and token, in the same status and with the same
+    /// settings; both parties sign. The merged-away records are removed,
+    /// leaving a pointer for `get_merged_into`.
+    pub fn merge_escrows(env: Env, escrow_ids: Vec<u64>) -> Result<u64, Error> {
+        extend_instance_ttl(&env);
+        ensure_not_paused(&env)?;
+        if escrow_ids.len() < 2 {
+            return Err(Error::InvalidEscrowStatus);
+        }
+
+        let target_id = escrow_ids.get_unchecked(0);
+        let target_key = get_storage_key(target_id);
+        let mut target: Escrow = env
+            .storage()
+            .persistent()
+            .get(&target_key)
+            .ok_or(Error::EscrowNotFound)?;
+        target.authorize(target_id, &target.depositor);
+        target.authorize(target_id, &target.recipient);
+        ensure_mergeable(&env, target_id, &target)?;
+        let target_settings = settings(&env, target_id, &target);
+
+        let mut sources = Vec::new(&env);
+        for (i, escrow_id) in escrow_ids.iter().enumerate().skip(1) {
+            if escrow_ids.first_index_of(escrow_id) != Some(i as u32) {
+                return Err(Error::InvalidEscrowStatus);
+            }
+            let source: Escrow = env
+                .storage()
+                .persistent()
+                .get(&get_storage_key(escrow_id))
+                .ok_or(Error::EscrowNotFound)?;
+            ensure_mergeable(&env, escrow_id, &source)?;
+            if source.depositor != target.depositor || source.recipient != target.recipient {
+                return Err(Error::UnauthorizedAccess);
+            }
+            if source.token_address != target.token_address {
+                return Err(Error::TokenMismatch);
+            }
+            if source.auth_mode != target.auth_mode {
+                return Err(Error::InvalidAuthMode);
+            }
+            if source.status != target.status {
+                return Err(Error::InvalidEscrowStatus);
+            }
+            if source.fee_bps != target.fee_bps
+                || settings(&env, escrow_id, &source) != target_settings
+            {
+                return Err(Error::InvalidEscrowKind);
+            }
+            sources.push_back((escrow_id, source));
+        }
+
+        let before = target.clone();
+        let mut reserve = fee_reserve(&env, target_id);
+        let mut totals = get_escrow_totals(&env, target_id);
+        for (escrow_id, source) in sources.iter() {
+            let offset = target.milestones.len();
+            for (i, milestone) in source.milestones.iter().enumerate() {
+                let from = criteria_key(escrow_id, i as u32);
+                let criteria: Option<Vec<Criterion>> = env.storage().persistent().get(&from);
+                if let Some(criteria) = criteria {
+                    let to = criteria_key(target_id, offset + i as u32);
+                    env.storage().persistent().set(&to, &criteria);
+                    env.storage().persistent().extend_ttl(&to, 100, 2_000_000);
+                }
+                target.milestones.push_back(milestone);
+            }
+            target.total_amount = target
+                .total_amount
+                .checked_add(source.total_amount)
+                .ok_or(Error::InvalidMilestoneAmount)?;
+            target.deadline = target.deadline.max(source.deadline);
+
+            reserve += fee_reserve(&env, escrow_id);
+            let moved = get_escrow_totals(&env, escrow_id);
+            totals.deposited += moved.deposited;
+            totals.received += moved.received;
+            totals.refunded += moved.refunded;
+            totals.fees_paid += moved.fees_paid;
+        }
+        if target.milestones.len() > vaultix_core::MAX_MILESTONES {
+            return Err(Error::VectorTooLarge);
+        }
+        ensure_unique_labels(&target.milestones)?;
+
+        for (escrow_id, source) in sources.iter() {
+            forget_deal(&env, escrow_id, &source);
+            remove_merged(&env, escrow_id, &source);
+            track_active_escrow(&env, &source.depositor, false);
+            shift_status_count(&env, &source.depositor, Some(source.status), None);
+            shift_status_count(&env, &source.recipient, Some(source.status), None);
+            let key = merged_into_key(escrow_id);
+            env.storage().persistent().set(&key, &target_id);
+            env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+
+            env.events().publish(
+                (
+                    Symbol::new(&env, "Vaultix"),
+                    Symbol::new(&env, "EscrowMerged"),
+                    escrow_id,
+                ),
+                (target_id, source.total_amount),
+            );
+        }
+
+        set_fee_reserve(&env, target_id, reserve);
+        if totals.deposited != 0 {
+            let totals_key = escrow_totals_key(target_id);
+            env.storage().persistent().set(&totals_key, &totals);
+            env.storage()
+                .persistent()
+                .extend_ttl(&totals_key, 100, 2_000_000);
+        }
+        forget_deal(&env, target_id, &before);
+        record_deal(&env, target_id, &target);
+        env.storage().persistent().set(&target_key, &target);
+        env.storage()
+            .persistent()
+            .extend_ttl(&target_key, 100, 2_000_000);
+
+        Ok(target_id)
+    }
+
+    /// Escrow an escrow was merged into, if it was merged away
+    pub fn get_merged_into(env: Env, escrow_id: u64) -> Option<u64> {
+        env.storage().persistent().get(&merged_into_key(escrow_id))
+    }
+}
+
+fn merged_into_key(escrow_id: u64) -> (Symbol, u64) {
+    (symbol_short!("merged"), escrow_id)
+}
+
+/// Settings that shape how an escrow pays out, which merged escrows must
+/// share so the combined schedule behaves like each of its parts
+fn settings(
+    env: &Env,
+    escrow_id: u64,
+    escrow: &Escrow,
+) -> (
+    FeePayer,
+    DisputePolicy,
+    Address,
+    bool,
+    Option<Symbol>,
+    Option<u64>,
+    Option<ReleaseSchedule>,
+) {
+    let storage = env.storage().persistent();
+    (
+        fee_payer(env, escrow_id),
+        dispute_policy(env, escrow_id),
+        refund_address(env, escrow_id, escrow),
+        storage.has(&recipient_lock_key(escrow_id)),
+        storage.get(&referrals::escrow_referral_key(escrow_id)),
+        storage.get(&cliff_key(escrow_id)),
+        storage.get(&release_schedule_key(escrow_id)),
+    )
+}
+
+/// Only escrows with nothing released or disputed and every amount revealed
+/// can be merged
+fn ensure_mergeable(env: &Env, escrow_id: u64, escrow: &Escrow) -> Result<(), Error> {
+    if escrow.kind != EscrowKind::Standard {
+        return Err(Error::InvalidEscrowKind);
+    }
+    if escrow.status != EscrowStatus::Created && escrow.status != EscrowStatus::Active {
+        return Err(Error::InvalidEscrowStatus);
+    }
+    if has_disputed_milestone(env, escrow_id, escrow) {
+        return Err(Error::AlreadyInDispute);
+    }
+    if escrow.total_released > 0
+        || escrow
+            .milestones
+            .iter()
+            .any(|m| m.status != MilestoneStatus::Pending)
+    {
+        return Err(Error::MilestoneAlreadyReleased);
+    }
+    if escrow.milestones.iter().any(|m| m.amount == 0) {
+        return Err(Error::MilestoneSealed);
+    }
+    // Expense claims are filed against their milestone's position
+    if (0..escrow.milestones.len()).any(|i| expenses::is_expense_milestone(env, escrow_id, i)) {
+        return Err(Error::InvalidEscrowKind);
+    }
+    Ok(())
+}
+
+/// Drops a merged-away escrow and its per-escrow state. Terms hashes are
+/// kept as evidence, as on archiving.
+fn remove_merged(env: &Env, escrow_id: u64, escrow: &Escrow) {
+    let storage = env.storage().persistent();
+    storage.remove(&get_storage_key(escrow_id));
+    storage.remove(&commitments_key(escrow_id));
+    storage.remove(&cliff_key(escrow_id));
+    storage.remove(&release_allowance_key(escrow_id));
+    storage.remove(&refund_address_key(escrow_id));
+    storage.remove(&recipient_lock_key(escrow_id));
+    storage.remove(&release_schedule_key(escrow_id));
+    storage.remove(&dispute_policy_key(escrow_id));
+    storage.remove(&fee_payer_key(escrow_id));
+    set_fee_reserve(env, escrow_id, 0);
+    storage.remove(&escrow_totals_key(escrow_id));
+    for milestone_index in 0..escrow.milestones.len() {
+        storage.remove(&criteria_key(escrow_id, milestone_index));
+        storage.remove(&depeg_override_key(escrow_id, milestone_index));
+    }
+    storage.remove(&referrals::escrow_referral_key(escrow_id));
+}
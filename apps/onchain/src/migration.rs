@@ -0,0 +1,212 @@
+use crate::{
+    ensure_not_paused, extend_instance_ttl, fee_reserve, forget_deal, get_admin, get_storage_key,
+    has_disputed_milestone, has_pending_tranches, lock_value, record_deal, refund_address,
+    release_allowance_key, unclaimed, unlock_value, Error, EscrowStatus, MilestoneStatus,
+    ReleaseAllowance, TokenConverterClient, VaultixEscrow, VaultixEscrowClient,
+};
+use soroban_sdk::{contractimpl, symbol_short, token, Address, Env, Symbol};
+
+#[cfg(any(test, feature = "testutils"))]
+use crate::__VaultixEscrow_fn_set_registry;
+
+/// Fixed-point scale of migration rates: `RATE_SCALE` converts one unit of
+/// the old token into one unit of the new one
+pub const RATE_SCALE: i128 = 10_000_000;
+
+#[contractimpl]
+impl VaultixEscrow {
+    /// Allowlists `converter` for swapping `old_token` into `new_token` when
+    /// an issuer migrates contracts, or removes it when `None`
+    pub fn set_token_converter(
+        env: Env,
+        old_token: Address,
+        new_token: Address,
+        converter: Option<Address>,
+    ) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+
+        let key = converter_key(&old_token, &new_token);
+        match converter {
+            Some(ref converter) => env.storage().instance().set(&key, converter),
+            None => env.storage().instance().remove(&key),
+        }
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "TokenConverterUpdated"),
+                old_token,
+                new_token,
+            ),
+            converter,
+        );
+
+        Ok(())
+    }
+
+    pub fn get_token_converter(
+        env: Env,
+        old_token: Address,
+        new_token: Address,
+    ) -> Option<Address> {
+        env.storage()
+            .instance()
+            .get(&converter_key(&old_token, &new_token))
+    }
+
+    /// Moves an escrow to `new_token` after its issuer migrated contracts.
+    /// The locked balance is swapped through the allowlisted converter and
+    /// every milestone is rescaled by `rate` (see `RATE_SCALE`). The admin
+    /// and both parties must sign. The converter must deliver at least the
+    /// rescaled unreleased amount; any rounding surplus is refunded.
+    pub fn migrate_escrow_token(
+        env: Env,
+        escrow_id: u64,
+        old_token: Address,
+        new_token: Address,
+        rate: i128,
+    ) -> Result<i128, Error> {
+        extend_instance_ttl(&env);
+        ensure_not_paused(&env)?;
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        let mut escrow = Self::get_escrow(env.clone(), escrow_id)?;
+        escrow.authorize(escrow_id, &escrow.depositor);
+        escrow.authorize(escrow_id, &escrow.recipient);
+
+        if escrow.token_address != old_token {
+            return Err(Error::TokenMismatch);
+        }
+        if rate <= 0 {
+            return Err(Error::InvalidMilestoneAmount);
+        }
+        if escrow.status != EscrowStatus::Created && escrow.status != EscrowStatus::Active {
+            return Err(Error::InvalidEscrowStatus);
+        }
+        // A disputed slice is recorded in old-token units
+        if has_disputed_milestone(&env, escrow_id, &escrow) {
+            return Err(Error::AlreadyInDispute);
+        }
+        // Sealed amounts are only known once revealed
+        if escrow.milestones.iter().any(|m| m.amount == 0) {
+            return Err(Error::MilestoneSealed);
+        }
+        // A fee reserve is sized in old-token units
+        if has_pending_tranches(&env, escrow_id, &escrow)
+            || fee_reserve(&env, escrow_id) > 0
+            || unclaimed::unclaimed_total(&env, escrow_id) > 0
+        {
+            return Err(Error::InvalidEscrowStatus);
+        }
+        let converter: Address = env
+            .storage()
+            .instance()
+            .get(&converter_key(&old_token, &new_token))
+            .ok_or(Error::ConverterNotAllowed)?;
+
+        // Only milestones still backed by locked funds move
+        let mut locked = 0i128;
+        let mut expected = 0i128;
+        let mut new_total = 0i128;
+        let mut milestones = escrow.milestones.clone();
+        for (i, mut milestone) in escrow.milestones.iter().enumerate() {
+            let amount = milestone
+                .amount
+                .checked_mul(rate)
+                .ok_or(Error::InvalidMilestoneAmount)?
+                / RATE_SCALE;
+            match milestone.status {
+                MilestoneStatus::Pending => {
+                    locked += milestone.amount;
+                    expected += amount;
+                }
+                MilestoneStatus::Released => {}
+                MilestoneStatus::SettledOffChain => continue,
+                MilestoneStatus::Disputed => return Err(Error::AlreadyInDispute),
+                // Held payouts are recorded in old-token units
+                MilestoneStatus::Held => return Err(Error::ChargebackWindowOpen),
+            }
+            milestone.amount = amount;
+            new_total += amount;
+            milestones.set(i as u32, milestone);
+        }
+        let funded = escrow.status == EscrowStatus::Active;
+        if funded && locked != escrow.total_amount - escrow.total_released {
+            return Err(Error::InvalidEscrowStatus);
+        }
+
+        let mut received = 0i128;
+        if funded && locked > 0 {
+            let old_client = token::Client::new(&env, &old_token);
+            let new_client = token::Client::new(&env, &new_token);
+            let contract_address = env.current_contract_address();
+            let before = new_client.balance(&contract_address);
+            old_client.transfer(&contract_address, &converter, &locked);
+            TokenConverterClient::new(&env, &converter).convert(
+                &old_token,
+                &new_token,
+                &locked,
+                &contract_address,
+            );
+            received = new_client.balance(&contract_address) - before;
+            if received < expected {
+                return Err(Error::InsufficientBalance);
+            }
+            if received > expected {
+                new_client.transfer(
+                    &contract_address,
+                    &refund_address(&env, escrow_id, &escrow),
+                    &(received - expected),
+                );
+            }
+        }
+
+        let allowance_key = release_allowance_key(escrow_id);
+        let allowance: Option<ReleaseAllowance> = env.storage().persistent().get(&allowance_key);
+        if let Some(mut allowance) = allowance {
+            allowance.amount_left = allowance
+                .amount_left
+                .checked_mul(rate)
+                .ok_or(Error::InvalidMilestoneAmount)?
+                / RATE_SCALE;
+            env.storage().persistent().set(&allowance_key, &allowance);
+        }
+
+        unlock_value(&env, &old_token, escrow.total_amount);
+        lock_value(&env, &new_token, new_total)?;
+        let before = escrow.clone();
+        escrow.token_address = new_token.clone();
+        escrow.milestones = milestones;
+        escrow.total_amount = new_total;
+        escrow.total_released = new_total - expected;
+        forget_deal(&env, escrow_id, &before);
+        record_deal(&env, escrow_id, &escrow);
+
+        let storage_key = get_storage_key(escrow_id);
+        env.storage().persistent().set(&storage_key, &escrow);
+        env.storage()
+            .persistent()
+            .extend_ttl(&storage_key, 100, 2_000_000);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "EscrowTokenMigrated"),
+                escrow_id,
+            ),
+            (old_token, new_token, received),
+        );
+
+        Ok(received)
+    }
+}
+
+fn converter_key(old_token: &Address, new_token: &Address) -> (Symbol, Address, Address) {
+    (
+        symbol_short!("converter"),
+        old_token.clone(),
+        new_token.clone(),
+    )
+}
@@ -0,0 +1,49 @@
+use crate::{extend_instance_ttl, Error, NotificationHint, VaultixEscrow, VaultixEscrowClient};
+use soroban_sdk::{contractimpl, symbol_short, Address, Env, Symbol};
+
+#[cfg(any(test, feature = "testutils"))]
+use crate::__VaultixEscrow_fn_set_registry;
+
+#[contractimpl]
+impl VaultixEscrow {
+    /// Sets or clears where off-chain services should route `owner`'s
+    /// alerts. Signed by the owner.
+    pub fn set_notification_hint(
+        env: Env,
+        owner: Address,
+        hint: Option<NotificationHint>,
+    ) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        owner.require_auth();
+
+        let key = notification_hint_key(&owner);
+        match hint {
+            Some(ref hint) => {
+                env.storage().persistent().set(&key, hint);
+                env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+            }
+            None => env.storage().persistent().remove(&key),
+        }
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "NotificationHintUpdated"),
+                owner,
+            ),
+            hint,
+        );
+
+        Ok(())
+    }
+
+    pub fn get_notification_hint(env: Env, owner: Address) -> Option<NotificationHint> {
+        env.storage()
+            .persistent()
+            .get(&notification_hint_key(&owner))
+    }
+}
+
+fn notification_hint_key(owner: &Address) -> (Symbol, Address) {
+    (symbol_short!("notify"), owner.clone())
+}
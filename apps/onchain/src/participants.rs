@@ -0,0 +1,131 @@
+use crate::{extend_instance_ttl, get_admin, Error, VaultixEscrow, VaultixEscrowClient};
+use soroban_sdk::{contractimpl, symbol_short, Address, Env, Symbol};
+
+#[cfg(any(test, feature = "testutils"))]
+use crate::__VaultixEscrow_fn_set_registry;
+
+#[contractimpl]
+impl VaultixEscrow {
+    /// Turns invite-only mode on or off. While on, only addresses holding
+    /// the participant role may open escrows or be made their recipient.
+    pub fn set_permissioned(env: Env, permissioned: bool) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+
+        env.storage()
+            .instance()
+            .set(&permissioned_key(), &permissioned);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "PermissionedModeUpdated"),
+            ),
+            permissioned,
+        );
+
+        Ok(())
+    }
+
+    pub fn is_permissioned(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&permissioned_key())
+            .unwrap_or(false)
+    }
+
+    /// Adds or removes a registrar, who may grant and revoke the participant
+    /// role alongside the admin
+    pub fn set_registrar(env: Env, registrar: Address, allowed: bool) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+
+        let key = registrar_key(&registrar);
+        if allowed {
+            env.storage().persistent().set(&key, &true);
+            env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "RoleUpdated"),
+                Symbol::new(&env, "Registrar"),
+            ),
+            (registrar, allowed),
+        );
+
+        Ok(())
+    }
+
+    pub fn is_registrar(env: Env, registrar: Address) -> bool {
+        env.storage().persistent().has(&registrar_key(&registrar))
+    }
+
+    /// Grants or revokes the participant role. Signed by `granter`, the
+    /// admin or an allowlisted registrar. Revoking leaves the address's
+    /// existing escrows running.
+    pub fn set_participant(
+        env: Env,
+        granter: Address,
+        participant: Address,
+        allowed: bool,
+    ) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        granter.require_auth();
+        if granter != get_admin(&env)? && !Self::is_registrar(env.clone(), granter.clone()) {
+            return Err(Error::UnauthorizedAccess);
+        }
+
+        let key = participant_key(&participant);
+        if allowed {
+            env.storage().persistent().set(&key, &true);
+            env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "RoleUpdated"),
+                Symbol::new(&env, "Participant"),
+            ),
+            (participant, allowed, granter),
+        );
+
+        Ok(())
+    }
+
+    pub fn is_participant(env: Env, participant: Address) -> bool {
+        env.storage()
+            .persistent()
+            .has(&participant_key(&participant))
+    }
+}
+
+fn permissioned_key() -> Symbol {
+    symbol_short!("permissnd")
+}
+
+fn registrar_key(registrar: &Address) -> (Symbol, Address) {
+    (symbol_short!("registrar"), registrar.clone())
+}
+
+fn participant_key(participant: &Address) -> (Symbol, Address) {
+    (symbol_short!("particpnt"), participant.clone())
+}
+
+/// Rejects a party without the participant role while invite-only mode is on
+pub(crate) fn ensure_participant(env: &Env, party: &Address) -> Result<(), Error> {
+    if VaultixEscrow::is_permissioned(env.clone())
+        && !VaultixEscrow::is_participant(env.clone(), party.clone())
+    {
+        return Err(Error::UnauthorizedAccess);
+    }
+    Ok(())
+}
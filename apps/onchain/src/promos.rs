@@ -0,0 +1,82 @@
+use crate::{extend_instance_ttl, Error, PromoCode, VaultixEscrow, VaultixEscrowClient};
+use soroban_sdk::{contractimpl, symbol_short, Env, Symbol};
+use vaultix_core::fee::{calculate_fee, is_valid_bps};
+
+#[cfg(any(test, feature = "testutils"))]
+use crate::__VaultixEscrow_fn_set_registry;
+
+#[contractimpl]
+impl VaultixEscrow {
+    /// Creates `code`, or replaces its terms while keeping its usage count.
+    /// Escrows created with the code until `expires_at` get `discount_bps`
+    /// off the platform fee, for at most `max_uses` escrows.
+    pub fn create_promo(
+        env: Env,
+        code: Symbol,
+        discount_bps: i128,
+        expires_at: u64,
+        max_uses: u32,
+    ) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        let (treasury, _) = Self::get_config(env.clone())?;
+        treasury.require_auth();
+
+        if !is_valid_bps(discount_bps) || max_uses == 0 {
+            return Err(Error::InvalidFeeConfiguration);
+        }
+        if expires_at <= env.ledger().timestamp() {
+            return Err(Error::InvalidDeadline);
+        }
+
+        let key = promo_key(&code);
+        let uses = Self::get_promo(env.clone(), code.clone()).map_or(0, |promo| promo.uses);
+        let promo = PromoCode {
+            discount_bps,
+            expires_at,
+            max_uses,
+            uses,
+        };
+        env.storage().persistent().set(&key, &promo);
+        env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "PromoCreated"),
+                code,
+            ),
+            (discount_bps, expires_at, max_uses),
+        );
+
+        Ok(())
+    }
+
+    pub fn get_promo(env: Env, code: Symbol) -> Option<PromoCode> {
+        env.storage().persistent().get(&promo_key(&code))
+    }
+}
+
+fn promo_key(code: &Symbol) -> (Symbol, Symbol) {
+    (symbol_short!("promo"), code.clone())
+}
+
+/// Counts a redemption of `code` and returns `fee_bps` less its discount.
+/// Unknown, expired and used-up codes are refused.
+pub(crate) fn redeem_promo(env: &Env, code: &Symbol, fee_bps: i128) -> Result<i128, Error> {
+    let key = promo_key(code);
+    let mut promo: PromoCode = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .ok_or(Error::InvalidFeeConfiguration)?;
+    if env.ledger().timestamp() >= promo.expires_at || promo.uses >= promo.max_uses {
+        return Err(Error::InvalidFeeConfiguration);
+    }
+    promo.uses += 1;
+    env.storage().persistent().set(&key, &promo);
+    env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+
+    let discount =
+        calculate_fee(fee_bps, promo.discount_bps).ok_or(Error::InvalidFeeConfiguration)?;
+    Ok(fee_bps - discount)
+}
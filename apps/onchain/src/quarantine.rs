@@ -0,0 +1,180 @@
+use crate::{
+    ensure_not_paused, extend_instance_ttl, get_storage_key, record_escrow_flow, record_flow,
+    Error, Escrow, Flow, QuarantinedTransfer, TransferLeg, VaultixEscrow, VaultixEscrowClient,
+};
+use soroban_sdk::{contractimpl, symbol_short, token, Address, Env, Symbol};
+
+#[cfg(any(test, feature = "testutils"))]
+use crate::__VaultixEscrow_fn_set_registry;
+
+#[contractimpl]
+impl VaultixEscrow {
+    /// Retries a quarantined transfer, e.g. once the issuer unfreezes its
+    /// destination. Permissionless, since the funds can only go where the
+    /// settlement sent them. Returns the amount moved, which is only then
+    /// counted in the escrow's accounting.
+    pub fn retry_transfer(env: Env, quarantine_id: u64) -> Result<i128, Error> {
+        extend_instance_ttl(&env);
+        ensure_not_paused(&env)?;
+        let key = quarantine_key(quarantine_id);
+        let transfer: QuarantinedTransfer = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(Error::InvalidEscrowStatus)?;
+        env.storage().persistent().remove(&key);
+
+        token::Client::new(&env, &transfer.token).transfer(
+            &env.current_contract_address(),
+            &transfer.to,
+            &transfer.amount,
+        );
+        if owed_to_depositor(transfer.leg) {
+            set_quarantined_refunds(
+                &env,
+                transfer.escrow_id,
+                quarantined_refunds(&env, transfer.escrow_id) - transfer.amount,
+            );
+        }
+        record_delivery(&env, &transfer);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "TransferRetried"),
+                transfer.escrow_id,
+                transfer.leg,
+            ),
+            (quarantine_id, transfer.to, transfer.amount),
+        );
+
+        Ok(transfer.amount)
+    }
+
+    pub fn get_quarantined_transfer(env: Env, quarantine_id: u64) -> Option<QuarantinedTransfer> {
+        env.storage()
+            .persistent()
+            .get(&quarantine_key(quarantine_id))
+    }
+}
+
+fn quarantine_key(quarantine_id: u64) -> (Symbol, u64) {
+    (symbol_short!("quarantin"), quarantine_id)
+}
+
+fn next_quarantine_key() -> Symbol {
+    symbol_short!("quar_next")
+}
+
+fn quarantined_refunds_key(escrow_id: u64) -> (Symbol, u64) {
+    (symbol_short!("quar_rfnd"), escrow_id)
+}
+
+/// Legs returning the escrow's own token to the depositor
+fn owed_to_depositor(leg: TransferLeg) -> bool {
+    matches!(leg, TransferLeg::Refund | TransferLeg::FeeReserve)
+}
+
+/// Refunds of the escrow's token still held in quarantine
+pub(crate) fn quarantined_refunds(env: &Env, escrow_id: u64) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&quarantined_refunds_key(escrow_id))
+        .unwrap_or(0)
+}
+
+fn set_quarantined_refunds(env: &Env, escrow_id: u64, total: i128) {
+    let key = quarantined_refunds_key(escrow_id);
+    if total == 0 {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, &total);
+        env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+    }
+}
+
+/// Credits a retried transfer to the party it settles. Fee legs were
+/// counted against the payee when the fee was withheld.
+fn record_delivery(env: &Env, transfer: &QuarantinedTransfer) {
+    // Archiving waits out quarantined refunds; an archived escrow's books
+    // are gone either way
+    let escrow: Option<Escrow> = env
+        .storage()
+        .persistent()
+        .get(&get_storage_key(transfer.escrow_id));
+    let Some(escrow) = escrow else {
+        return;
+    };
+    match transfer.leg {
+        TransferLeg::Refund | TransferLeg::FeeReserve => record_escrow_flow(
+            env,
+            transfer.escrow_id,
+            &escrow.depositor,
+            Flow::Refunded,
+            transfer.amount,
+        ),
+        // A cancellation hands the counter leg back to the recipient who
+        // funded it; a release pays it to the depositor
+        TransferLeg::CounterLeg if transfer.to == escrow.recipient => {
+            record_flow(env, &escrow.recipient, Flow::Refunded, transfer.amount)
+        }
+        TransferLeg::CounterLeg => {
+            record_flow(env, &escrow.depositor, Flow::Received, transfer.amount)
+        }
+        TransferLeg::Treasury | TransferLeg::Referral | TransferLeg::Burn => {}
+    }
+}
+
+/// Sends one leg of a settlement. A transfer the token refuses (issuer
+/// freeze, trustline limit) is stored for `retry_transfer` rather than
+/// failing the whole settlement, and a `TransferQuarantined` event names
+/// the leg. Returns whether the transfer went through; callers account for
+/// the transfer only if it did, and `retry_transfer` does so otherwise.
+pub(crate) fn transfer_or_quarantine(
+    env: &Env,
+    escrow_id: u64,
+    leg: TransferLeg,
+    token: &Address,
+    to: &Address,
+    amount: i128,
+) -> bool {
+    let sent =
+        token::Client::new(env, token).try_transfer(&env.current_contract_address(), to, &amount);
+    if matches!(sent, Ok(Ok(()))) {
+        return true;
+    }
+
+    let quarantine_id: u64 = env
+        .storage()
+        .instance()
+        .get(&next_quarantine_key())
+        .unwrap_or(0);
+    env.storage()
+        .instance()
+        .set(&next_quarantine_key(), &(quarantine_id + 1));
+    let key = quarantine_key(quarantine_id);
+    let transfer = QuarantinedTransfer {
+        escrow_id,
+        leg,
+        token: token.clone(),
+        to: to.clone(),
+        amount,
+        since: env.ledger().timestamp(),
+    };
+    env.storage().persistent().set(&key, &transfer);
+    env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+    if owed_to_depositor(leg) {
+        set_quarantined_refunds(env, escrow_id, quarantined_refunds(env, escrow_id) + amount);
+    }
+
+    env.events().publish(
+        (
+            Symbol::new(env, "Vaultix"),
+            Symbol::new(env, "TransferQuarantined"),
+            escrow_id,
+            leg,
+        ),
+        (quarantine_id, to.clone(), amount),
+    );
+    false
+}
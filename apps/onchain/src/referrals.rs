@@ -0,0 +1,134 @@
+use crate::{extend_instance_ttl, Error, ReferralCode, VaultixEscrow, VaultixEscrowClient};
+use soroban_sdk::{contractimpl, symbol_short, Address, Env, Symbol};
+use vaultix_core::fee::is_valid_bps;
+
+#[cfg(any(test, feature = "testutils"))]
+use crate::__VaultixEscrow_fn_set_registry;
+
+#[contractimpl]
+impl VaultixEscrow {
+    /// Registers `code` for `referrer`. Depositors attach the code when
+    /// creating an escrow and the referrer then earns the referral share of
+    /// that escrow's fees.
+    pub fn register_referral_code(env: Env, code: Symbol, referrer: Address) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        referrer.require_auth();
+
+        let key = referral_code_key(&code);
+        if env.storage().persistent().has(&key) {
+            return Err(Error::ReferralCodeTaken);
+        }
+        let entry = ReferralCode {
+            referrer: referrer.clone(),
+            uses: 0,
+        };
+        env.storage().persistent().set(&key, &entry);
+        env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "ReferralCodeRegistered"),
+                code,
+            ),
+            referrer,
+        );
+
+        Ok(())
+    }
+
+    pub fn get_referral_code(env: Env, code: Symbol) -> Result<ReferralCode, Error> {
+        env.storage()
+            .persistent()
+            .get(&referral_code_key(&code))
+            .ok_or(Error::ReferralCodeNotFound)
+    }
+
+    /// Sets the share (in bps) of every fee, after any burn, that goes to
+    /// the escrow's referrer instead of the treasury
+    pub fn set_referral_share(env: Env, share_bps: i128) -> Result<(), Error> {
+        extend_instance_ttl(&env);
+        let (treasury, _) = Self::get_config(env.clone())?;
+        treasury.require_auth();
+
+        if !is_valid_bps(share_bps) {
+            return Err(Error::InvalidFeeConfiguration);
+        }
+        let old_share = referral_share_bps(&env);
+        env.storage()
+            .instance()
+            .set(&symbol_short!("ref_share"), &share_bps);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "FeeUpdated"),
+            ),
+            (
+                Symbol::new(&env, "Global"),
+                Symbol::new(&env, "ReferralShare"),
+                old_share,
+                share_bps,
+            ),
+        );
+
+        Ok(())
+    }
+}
+
+fn referral_code_key(code: &Symbol) -> (Symbol, Symbol) {
+    (symbol_short!("ref_code"), code.clone())
+}
+
+pub(crate) fn escrow_referral_key(escrow_id: u64) -> (Symbol, u64) {
+    (symbol_short!("referral"), escrow_id)
+}
+
+pub(crate) fn referral_share_bps(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&symbol_short!("ref_share"))
+        .unwrap_or(0)
+}
+
+/// Ties `code` to a new escrow between `depositor` and `recipient` and
+/// counts the redemption. A party cannot refer its own escrow.
+pub(crate) fn redeem_referral_code(
+    env: &Env,
+    escrow_id: u64,
+    code: &Symbol,
+    depositor: &Address,
+    recipient: &Address,
+) -> Result<(), Error> {
+    let key = referral_code_key(code);
+    let mut entry: ReferralCode = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .ok_or(Error::ReferralCodeNotFound)?;
+    if entry.referrer == *depositor || entry.referrer == *recipient {
+        return Err(Error::SelfDealing);
+    }
+    entry.uses = entry.uses.saturating_add(1);
+    env.storage().persistent().set(&key, &entry);
+    env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+
+    let escrow_key = escrow_referral_key(escrow_id);
+    env.storage().persistent().set(&escrow_key, code);
+    env.storage()
+        .persistent()
+        .extend_ttl(&escrow_key, 100, 2_000_000);
+    Ok(())
+}
+
+/// Current referrer of the code attached to the escrow, if any
+pub(crate) fn escrow_referrer(env: &Env, escrow_id: u64) -> Option<Address> {
+    let code: Symbol = env
+        .storage()
+        .persistent()
+        .get(&escrow_referral_key(escrow_id))?;
+    env.storage()
+        .persistent()
+        .get::<_, ReferralCode>(&referral_code_key(&code))
+        .map(|entry| entry.referrer)
+}
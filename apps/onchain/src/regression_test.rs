@@ -0,0 +1,134 @@
+//! Snapshot regression tests for canonical escrow scenarios.
+//!
+//! Each scenario records the events emitted by the escrow contract and the
+//! final escrow struct. Any change to event payloads or storage layout shows
+//! up as a snapshot diff under `src/snapshots/` that must be reviewed and
+//! accepted with `cargo insta review`.
+extern crate std;
+
+use super::*;
+use soroban_sdk::{
+    testutils::{Address as _, Events},
+    token, vec,
+    xdr::ScVal,
+    Address, Env, TryFromVal,
+};
+use std::format;
+use std::string::String;
+
+struct Scenario {
+    env: Env,
+    contract_id: Address,
+    client: VaultixEscrowClient<'static>,
+    depositor: Address,
+    #[cfg_attr(not(feature = "disputes"), allow(dead_code))]
+    recipient: Address,
+}
+
+fn setup() -> Scenario {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &None);
+    let admin = Address::generate(&env);
+    client.init(&admin);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+
+    let token_address = env.register_stellar_asset_contract(admin.clone());
+    token::StellarAssetClient::new(&env, &token_address).mint(&depositor, &10_000);
+    token::Client::new(&env, &token_address).approve(&depositor, &contract_id, &10_000, &200);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 6000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Design"),
+        },
+        Milestone {
+            amount: 4000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Build"),
+        },
+    ];
+    client.create_escrow(
+        &1,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+        &None,
+        &None,
+        &10000,
+        &EscrowOptions::new(EscrowKind::Standard),
+    );
+
+    Scenario {
+        env,
+        contract_id,
+        client,
+        depositor,
+        recipient,
+    }
+}
+
+/// Renders the escrow contract's events and the final escrow state
+fn render(scenario: &Scenario) -> String {
+    let env = &scenario.env;
+    let mut out = String::new();
+    for (contract, topics, data) in env.events().all().iter() {
+        if contract != scenario.contract_id {
+            continue;
+        }
+        let topics: std::vec::Vec<ScVal> = topics
+            .iter()
+            .map(|t| ScVal::try_from_val(env, &t).unwrap())
+            .collect();
+        let data = ScVal::try_from_val(env, &data).unwrap();
+        out.push_str(&format!("event {topics:?}\n  data {data:?}\n"));
+    }
+    let escrow = scenario.client.get_escrow(&1);
+    out.push_str(&format!("escrow {escrow:#?}\n"));
+    out
+}
+
+#[test]
+fn snapshot_release_and_complete() {
+    let scenario = setup();
+    scenario.client.deposit_funds(&1);
+    scenario.client.release_milestone(&1, &0);
+    scenario
+        .client
+        .confirm_delivery(&1, &1, &scenario.depositor);
+    scenario.client.complete_escrow(&1);
+
+    insta::assert_snapshot!(render(&scenario));
+}
+
+#[test]
+fn snapshot_cancel_funded_escrow() {
+    let scenario = setup();
+    scenario.client.deposit_funds(&1);
+    scenario.client.cancel_escrow(&1);
+
+    insta::assert_snapshot!(render(&scenario));
+}
+
+#[cfg(feature = "disputes")]
+#[test]
+fn snapshot_dispute_resolved_to_depositor() {
+    let scenario = setup();
+    scenario.client.deposit_funds(&1);
+    scenario.client.release_milestone(&1, &0);
+    scenario.client.raise_dispute(&1, &scenario.recipient);
+    scenario.client.resolve_dispute(&1, &scenario.depositor);
+
+    insta::assert_snapshot!(render(&scenario));
+}
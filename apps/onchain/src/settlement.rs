@@ -0,0 +1,322 @@
+use crate::quarantine;
+use crate::{
+    collect_fee, depositor_fee_share, dispute_policy, ensure_pegged, ensure_policy_allows,
+    fee_payer, fee_reserve, has_disputed_milestone, payout_address, quote_fee, record_escrow_flow,
+    refund_address, release_counter_leg, release_schedule_key, set_fee_reserve,
+    tranche_release_key, unclaimed, DisputePolicy, Error, Escrow, Flow, PolicyAction, Receipt,
+    ReceiptKind, ReleaseSchedule, TrancheRelease, TransferLeg, VaultixEscrow,
+};
+use soroban_sdk::{symbol_short, token, Address, Env, Symbol};
+
+/// Ledgers a settlement receipt stays retrievable, about a week
+pub const RECEIPT_TTL: u32 = 120_960;
+
+/// Where `settle` sends a slice of an escrow's funds
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Outcome {
+    /// Pay the payee less the platform fee, on the escrow's release schedule
+    Release,
+    /// Pay the payee in full on the release schedule; confirmed delivery
+    /// carries no platform fee
+    Confirmed,
+    /// Pay the recipient at once, as ruled by a dispute or a lapsed
+    /// guarantee, less the platform fee when `charge_fee`
+    Award { charge_fee: bool },
+    /// Return the funds to the depositor's refund address
+    Refund,
+}
+
+impl Outcome {
+    fn receipt_kind(&self) -> ReceiptKind {
+        match self {
+            Outcome::Release => ReceiptKind::Release,
+            Outcome::Confirmed => ReceiptKind::Confirmed,
+            Outcome::Award { .. } => ReceiptKind::Award,
+            Outcome::Refund => ReceiptKind::Refund,
+        }
+    }
+
+    /// Voluntary releases, which the dispute policy and depeg guard may hold
+    /// back; rulings and refunds always go through
+    fn is_release(&self) -> bool {
+        matches!(self, Outcome::Release | Outcome::Confirmed)
+    }
+}
+
+/// Single settlement engine behind every release, ruling and refund. Moves
+/// `amount` of the escrow's locked funds as `outcome` directs: fee quote and
+/// collection, delivery, flow accounting and `total_released`. Settlements
+/// of one milestone pass its index; escrow-wide ones pass `None`.
+///
+/// Callers own authorization, milestone status transitions and persisting
+/// the escrow afterwards. Returns `false` when a refund was quarantined
+/// instead of reaching the depositor.
+pub(crate) fn settle(
+    env: &Env,
+    escrow_id: u64,
+    escrow: &mut Escrow,
+    milestone_index: Option<u32>,
+    amount: i128,
+    outcome: Outcome,
+) -> Result<bool, Error> {
+    if amount <= 0 {
+        return Ok(true);
+    }
+    if outcome == Outcome::Refund {
+        let refund_to = refund_address(env, escrow_id, escrow);
+        let sent = quarantine::transfer_or_quarantine(
+            env,
+            escrow_id,
+            TransferLeg::Refund,
+            &escrow.token_address,
+            &refund_to,
+            amount,
+        );
+        // A quarantined refund is accounted for when `retry_transfer` lands it
+        if sent {
+            record_escrow_flow(env, escrow_id, &escrow.depositor, Flow::Refunded, amount);
+            record_receipt(
+                env,
+                escrow_id,
+                milestone_index,
+                outcome,
+                refund_to,
+                amount,
+                0,
+                amount,
+            );
+        }
+        return Ok(sent);
+    }
+    if outcome.is_release() {
+        if dispute_policy(env, escrow_id) == DisputePolicy::FreezeAll
+            && has_disputed_milestone(env, escrow_id, escrow)
+        {
+            return Err(Error::AlreadyInDispute);
+        }
+        if let Some(index) = milestone_index {
+            ensure_pegged(env, escrow_id, escrow, index)?;
+        }
+        ensure_policy_allows(
+            env,
+            PolicyAction::Release,
+            &escrow.depositor,
+            &escrow.recipient,
+            &escrow.token_address,
+            amount,
+        )?;
+    }
+
+    let charge_fee = matches!(
+        outcome,
+        Outcome::Release | Outcome::Award { charge_fee: true }
+    );
+    let fee = match (charge_fee, milestone_index) {
+        (false, _) => 0,
+        (true, Some(index)) => {
+            let milestone = escrow
+                .milestones
+                .get(index)
+                .ok_or(Error::MilestoneNotFound)?;
+            quote_fee(env, escrow, &milestone, amount, escrow.fee_bps)?
+        }
+        (true, None) => return Err(Error::MilestoneNotFound),
+    };
+    let from_reserve = reserved_fee(env, escrow_id, fee);
+    let net = amount
+        .checked_sub(fee - from_reserve)
+        .ok_or(Error::InvalidMilestoneAmount)?;
+
+    let payee = match outcome {
+        Outcome::Award { .. } => escrow.recipient.clone(),
+        _ => escrow.payee().clone(),
+    };
+    let paid_now = match (outcome.is_release(), milestone_index) {
+        (true, Some(index)) => schedule_tranches(env, escrow_id, escrow, index, net),
+        _ => net,
+    };
+    deliver(env, escrow_id, escrow, &payee, paid_now);
+    if charge_fee {
+        record_escrow_flow(env, escrow_id, &payee, Flow::FeesPaid, fee - from_reserve);
+        record_escrow_flow(
+            env,
+            escrow_id,
+            &escrow.depositor,
+            Flow::FeesPaid,
+            from_reserve,
+        );
+        set_fee_reserve(env, escrow_id, fee_reserve(env, escrow_id) - from_reserve);
+    }
+    if let (true, Some(index)) = (outcome.is_release(), milestone_index) {
+        release_counter_leg(env, escrow, escrow_id, index)?;
+    }
+    if fee > 0 {
+        let (treasury, _) = VaultixEscrow::get_config(env.clone())?;
+        let token_client = token::Client::new(env, &escrow.token_address);
+        collect_fee(
+            env,
+            escrow_id,
+            &token_client,
+            &escrow.token_address,
+            &treasury,
+            fee,
+        )?;
+    }
+    escrow.total_released = escrow
+        .total_released
+        .checked_add(amount)
+        .ok_or(Error::InvalidMilestoneAmount)?;
+    record_receipt(
+        env,
+        escrow_id,
+        milestone_index,
+        outcome,
+        payee,
+        amount,
+        fee,
+        net,
+    );
+
+    if let (true, Some(index)) = (outcome.is_release(), milestone_index) {
+        // Standardized Event
+        env.events().publish(
+            (
+                Symbol::new(env, "Vaultix"),
+                Symbol::new(env, "MilestoneReleased"),
+                escrow_id,
+                index,
+            ),
+            (net, fee),
+        );
+    }
+
+    Ok(true)
+}
+
+pub(crate) fn receipt_key(escrow_id: u64, index: u32) -> (Symbol, u64, u32) {
+    (symbol_short!("receipt"), escrow_id, index)
+}
+
+pub(crate) fn receipt_count_key(escrow_id: u64) -> (Symbol, u64) {
+    (symbol_short!("receipts"), escrow_id)
+}
+
+/// Appends a settlement receipt. The count is refreshed with every receipt,
+/// so it outlives them all and numbering never restarts under a live one.
+fn record_receipt(
+    env: &Env,
+    escrow_id: u64,
+    milestone_index: Option<u32>,
+    outcome: Outcome,
+    payee: Address,
+    amount: i128,
+    fee: i128,
+    net: i128,
+) {
+    let count_key = receipt_count_key(escrow_id);
+    let index: u32 = env.storage().temporary().get(&count_key).unwrap_or(0);
+    let key = receipt_key(escrow_id, index);
+    let receipt = Receipt {
+        kind: outcome.receipt_kind(),
+        milestone_index,
+        payee,
+        amount,
+        fee,
+        net,
+        ledger: env.ledger().sequence(),
+        timestamp: env.ledger().timestamp(),
+    };
+    env.storage().temporary().set(&key, &receipt);
+    env.storage()
+        .temporary()
+        .extend_ttl(&key, RECEIPT_TTL, RECEIPT_TTL);
+    env.storage().temporary().set(&count_key, &(index + 1));
+    env.storage()
+        .temporary()
+        .extend_ttl(&count_key, RECEIPT_TTL, RECEIPT_TTL);
+}
+
+/// Part of a `fee` the depositor's fee reserve covers. The reserve was
+/// sized on flat-rate fees, so a fee strategy quoting more falls back on
+/// the payout once it runs dry.
+pub(crate) fn reserved_fee(env: &Env, escrow_id: u64, fee: i128) -> i128 {
+    depositor_fee_share(fee_payer(env, escrow_id), fee).min(fee_reserve(env, escrow_id))
+}
+
+/// Records the later tranches of a payout when the escrow releases in
+/// tranches, returning the part to pay now
+fn schedule_tranches(
+    env: &Env,
+    escrow_id: u64,
+    escrow: &Escrow,
+    milestone_index: u32,
+    amount: i128,
+) -> i128 {
+    let Some((count, interval)) = tranche_schedule(env, escrow_id) else {
+        return amount;
+    };
+    let per_tranche = first_tranche(amount, count);
+    if per_tranche == amount {
+        return amount;
+    }
+
+    let key = tranche_release_key(escrow_id, milestone_index);
+    let tranches = TrancheRelease {
+        payee: escrow.payee().clone(),
+        per_tranche,
+        remaining: amount - per_tranche,
+        tranches_left: count - 1,
+        interval,
+        next_claimable_at: env.ledger().timestamp().saturating_add(interval),
+    };
+    env.storage().persistent().set(&key, &tranches);
+    env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+    per_tranche
+}
+
+fn tranche_schedule(env: &Env, escrow_id: u64) -> Option<(u32, u64)> {
+    let schedule: Option<ReleaseSchedule> = env
+        .storage()
+        .persistent()
+        .get(&release_schedule_key(escrow_id));
+    match schedule {
+        Some(ReleaseSchedule::Tranches(count, interval)) => Some((count, interval)),
+        _ => None,
+    }
+}
+
+/// Part of a payout released at once under a `count`-tranche schedule;
+/// payouts too small to split go out whole
+fn first_tranche(amount: i128, count: u32) -> i128 {
+    let per_tranche = amount / count as i128;
+    if count <= 1 || per_tranche <= 0 {
+        return amount;
+    }
+    per_tranche
+}
+
+/// Part of a `payout` released at once under the escrow's schedule
+#[cfg_attr(not(feature = "views-extended"), allow(dead_code))]
+pub(crate) fn paid_now(env: &Env, escrow_id: u64, payout: i128) -> i128 {
+    match tranche_schedule(env, escrow_id) {
+        Some((count, _)) => first_tranche(payout, count),
+        None => payout,
+    }
+}
+
+/// Sends a payout to `party`'s payout address. A party the token refuses
+/// (e.g. deauthorized by the issuer) must not strand the settlement; the
+/// payout waits in `unclaimed` instead.
+pub(crate) fn deliver(env: &Env, escrow_id: u64, escrow: &Escrow, party: &Address, amount: i128) {
+    let token_client = token::Client::new(env, &escrow.token_address);
+    let delivered = token_client.try_transfer(
+        &env.current_contract_address(),
+        &payout_address(env, party),
+        &amount,
+    );
+    if matches!(delivered, Ok(Ok(()))) {
+        record_escrow_flow(env, escrow_id, party, Flow::Received, amount);
+    } else {
+        unclaimed::divert_payout(env, escrow_id, party, amount);
+    }
+}
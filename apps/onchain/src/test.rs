@@ -1,9 +1,35 @@
 use super::*;
 use soroban_sdk::{
-    testutils::{Address as _, Events},
-    token, vec, Address, Env, IntoVal,
+    contract, contracterror, contractimpl,
+    testutils::{Address as _, EnvTestConfig, Events, Ledger},
+    token, vec,
+    xdr::{LedgerKey, ScAddress, ScVal, ToXdr},
+    Address, Bytes, BytesN, Env, IntoVal, TryFromVal, Val,
 };
 
+/// Commitment to a sealed milestone amount, as `create_sealed_escrow` expects
+fn commit_amount(env: &Env, amount: i128, salt: &BytesN<32>) -> BytesN<32> {
+    let mut preimage = Bytes::from_array(env, &amount.to_be_bytes());
+    preimage.append(&salt.clone().into());
+    env.crypto().sha256(&preimage)
+}
+
+/// Env for tests whose recorded snapshot varies with the enabled cargo
+/// features. Only the default feature set writes its snapshot, so the
+/// per-feature test runs leave the committed ones untouched.
+fn feature_dependent_env() -> Env {
+    Env::new_with_config(EnvTestConfig {
+        capture_snapshot_at_drop: cfg!(all(feature = "disputes", feature = "views-extended")),
+    })
+}
+
+/// Options for tests that deliberately open several escrows for one deal
+fn repeat_deal_options(kind: EscrowKind) -> EscrowOptions {
+    let mut options = EscrowOptions::new(kind);
+    options.allow_duplicate = true;
+    options
+}
+
 /// Helper function to create and initialize a test token
 /// Returns admin client for minting and the token address
 fn create_test_token<'a>(env: &Env, admin: &Address) -> (token::StellarAssetClient<'a>, Address) {
@@ -60,6 +86,10 @@ fn test_create_escrow_fails_when_paused() {
         &token_address,
         &milestones,
         &deadline,
+        &None,
+        &None,
+        &10000,
+        &EscrowOptions::new(EscrowKind::Standard),
     );
 
     assert_eq!(result, Err(Ok(Error::ContractPaused)));
@@ -101,6 +131,10 @@ fn test_deposit_funds_fails_when_paused() {
         &token_address,
         &milestones,
         &deadline,
+        &None,
+        &None,
+        &10000,
+        &EscrowOptions::new(EscrowKind::Standard),
     );
 
     token_client.approve(&depositor, &contract_id, &10_000, &200);
@@ -155,6 +189,10 @@ fn test_create_and_get_escrow() {
         &token_address,
         &milestones,
         &deadline,
+        &None,
+        &None,
+        &10000,
+        &EscrowOptions::new(EscrowKind::Standard),
     );
 
     let escrow = client.get_escrow(&escrow_id);
@@ -240,6 +278,10 @@ fn test_deposit_funds() {
         &token_address,
         &milestones,
         &1706400000u64,
+        &None,
+        &None,
+        &10000,
+        &EscrowOptions::new(EscrowKind::Standard),
     );
 
     // Approve contract to spend tokens
@@ -302,6 +344,10 @@ fn test_release_milestone_with_tokens() {
         &token_address,
         &milestones,
         &1706400000u64,
+        &None,
+        &None,
+        &10000,
+        &EscrowOptions::new(EscrowKind::Standard),
     );
     token_client.approve(&depositor, &contract_id, &10_000, &200);
     client.deposit_funds(&escrow_id);
@@ -333,6 +379,7 @@ fn test_release_milestone_with_tokens() {
     assert_eq!(token_client.balance(&recipient), 6000);
 }
 
+#[cfg(feature = "disputes")]
 #[test]
 #[should_panic(expected = "Error(Contract, #9)")]
 fn test_dispute_blocks_release() {
@@ -366,6 +413,10 @@ fn test_dispute_blocks_release() {
         &token_address,
         &milestones,
         &1706400000u64,
+        &None,
+        &None,
+        &500,
+        &EscrowOptions::new(EscrowKind::Standard),
     );
 
     token_client.approve(&depositor, &contract_id, &1000, &200);
@@ -418,6 +469,10 @@ fn test_complete_escrow_with_all_releases() {
         &token_address,
         &milestones,
         &1706400000u64,
+        &None,
+        &None,
+        &10000,
+        &EscrowOptions::new(EscrowKind::Standard),
     );
     token_client.approve(&depositor, &contract_id, &10_000, &200);
     client.deposit_funds(&escrow_id);
@@ -471,6 +526,10 @@ fn test_cancel_escrow_with_refund() {
         &token_address,
         &milestones,
         &1706400000u64,
+        &None,
+        &None,
+        &10000,
+        &EscrowOptions::new(EscrowKind::Standard),
     );
     token_client.approve(&depositor, &contract_id, &10_000, &200);
     client.deposit_funds(&escrow_id);
@@ -486,6 +545,21 @@ fn test_cancel_escrow_with_refund() {
     assert_eq!(token_client.balance(&contract_id), 0);
     assert_eq!(token_client.balance(&depositor), 10_000);
 
+    let expected_topics: soroban_sdk::Vec<soroban_sdk::Val> = (
+        Symbol::new(&env, "Vaultix"),
+        Symbol::new(&env, "EscrowRefunded"),
+        escrow_id,
+    )
+        .into_val(&env);
+    let refunded = env
+        .events()
+        .all()
+        .iter()
+        .find(|event| event.1 == expected_topics)
+        .unwrap();
+    let (refund_to, amount): (Address, i128) = refunded.2.into_val(&env);
+    assert_eq!((refund_to, amount), (depositor.clone(), 10_000));
+
     let escrow = client.get_escrow(&escrow_id);
     assert_eq!(escrow.status, EscrowStatus::Cancelled);
 }
@@ -522,6 +596,10 @@ fn test_cancel_unfunded_escrow() {
         &token_address,
         &milestones,
         &1706400000u64,
+        &None,
+        &None,
+        &5000,
+        &EscrowOptions::new(EscrowKind::Standard),
     );
 
     // Cancel unfunded escrow (no refund needed)
@@ -531,6 +609,7 @@ fn test_cancel_unfunded_escrow() {
     assert_eq!(escrow.status, EscrowStatus::Cancelled);
 }
 
+#[cfg(feature = "disputes")]
 #[test]
 fn test_admin_resolves_dispute_to_recipient() {
     let env = Env::default();
@@ -570,6 +649,10 @@ fn test_admin_resolves_dispute_to_recipient() {
         &token_address,
         &milestones,
         &1706400000u64,
+        &None,
+        &None,
+        &10000,
+        &EscrowOptions::new(EscrowKind::Standard),
     );
 
     token_client.approve(&depositor, &contract_id, &10000, &200);
@@ -593,6 +676,7 @@ fn test_admin_resolves_dispute_to_recipient() {
     assert_eq!(token_client.balance(&depositor), 0);
 }
 
+#[cfg(feature = "disputes")]
 #[test]
 fn test_admin_resolves_dispute_to_depositor() {
     let env = Env::default();
@@ -632,6 +716,10 @@ fn test_admin_resolves_dispute_to_depositor() {
         &token_address,
         &milestones,
         &1706400000u64,
+        &None,
+        &None,
+        &5000,
+        &EscrowOptions::new(EscrowKind::Standard),
     );
 
     token_client.approve(&depositor, &contract_id, &5000, &200);
@@ -688,6 +776,10 @@ fn test_duplicate_escrow_id() {
         &token_address,
         &milestones,
         &1706400000u64,
+        &None,
+        &None,
+        &1000,
+        &EscrowOptions::new(EscrowKind::Standard),
     );
     client.create_escrow(
         &escrow_id,
@@ -696,6 +788,10 @@ fn test_duplicate_escrow_id() {
         &token_address,
         &milestones,
         &1706400000u64,
+        &None,
+        &None,
+        &1000,
+        &EscrowOptions::new(EscrowKind::Standard),
     );
 }
 
@@ -735,6 +831,10 @@ fn test_double_release() {
         &token_address,
         &milestones,
         &1706400000u64,
+        &None,
+        &None,
+        &1000,
+        &EscrowOptions::new(EscrowKind::Standard),
     );
     token_client.approve(&depositor, &contract_id, &1000, &200);
     client.deposit_funds(&escrow_id);
@@ -780,6 +880,10 @@ fn test_too_many_milestones() {
         &token_address,
         &milestones,
         &1706400000u64,
+        &None,
+        &None,
+        &2100,
+        &EscrowOptions::new(EscrowKind::Standard),
     );
 }
 
@@ -816,6 +920,10 @@ fn test_invalid_milestone_amount() {
         &token_address,
         &milestones,
         &1706400000u64,
+        &None,
+        &None,
+        &0,
+        &EscrowOptions::new(EscrowKind::Standard),
     );
 }
 
@@ -853,6 +961,10 @@ fn test_unauthorized_confirm_delivery() {
         &token_address,
         &milestones,
         &1706400000u64,
+        &None,
+        &None,
+        &1000,
+        &EscrowOptions::new(EscrowKind::Standard),
     );
 
     token_client.approve(&buyer, &contract_id, &1000, &200);
@@ -894,6 +1006,10 @@ fn test_double_confirm_delivery() {
         &token_address,
         &milestones,
         &1706400000u64,
+        &None,
+        &None,
+        &1000,
+        &EscrowOptions::new(EscrowKind::Standard),
     );
 
     token_client.approve(&buyer, &contract_id, &1000, &200);
@@ -935,6 +1051,10 @@ fn test_zero_amount_milestone_rejected() {
         &token_address,
         &milestones,
         &1706400000u64,
+        &None,
+        &None,
+        &0,
+        &EscrowOptions::new(EscrowKind::Standard),
     );
 
     assert_eq!(result, Err(Ok(Error::ZeroAmount)));
@@ -971,6 +1091,10 @@ fn test_negative_amount_milestone_rejected() {
         &token_address,
         &milestones,
         &1706400000u64,
+        &None,
+        &None,
+        &-1000,
+        &EscrowOptions::new(EscrowKind::Standard),
     );
 
     assert_eq!(result, Err(Ok(Error::ZeroAmount)));
@@ -1006,6 +1130,10 @@ fn test_self_dealing_rejected() {
         &token_address,
         &milestones,
         &1706400000u64,
+        &None,
+        &None,
+        &5000,
+        &EscrowOptions::new(EscrowKind::Standard),
     );
 
     assert_eq!(result, Err(Ok(Error::SelfDealing)));
@@ -1047,6 +1175,10 @@ fn test_valid_escrow_creation_succeeds() {
         &token_address,
         &milestones,
         &1706400000u64,
+        &None,
+        &None,
+        &10000,
+        &EscrowOptions::new(EscrowKind::Standard),
     );
 
     assert!(result.is_ok());
@@ -1092,6 +1224,10 @@ fn test_double_deposit_rejected() {
         &token_address,
         &milestones,
         &1706400000u64,
+        &None,
+        &None,
+        &5000,
+        &EscrowOptions::new(EscrowKind::Standard),
     );
 
     token_client.approve(&depositor, &contract_id, &10_000, &200);
@@ -1133,9 +1269,5330 @@ fn test_release_milestone_before_deposit() {
         &token_address,
         &milestones,
         &1706400000u64,
+        &None,
+        &None,
+        &5000,
+        &EscrowOptions::new(EscrowKind::Standard),
     );
 
     // Try to release milestone before depositing funds
     // This should panic with Error #9 (EscrowNotActive)
     client.release_milestone(&escrow_id, &0);
 }
+
+#[cfg(feature = "views-extended")]
+#[test]
+fn test_get_pending_for_recipient() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &20_000);
+    token_client.approve(&depositor, &contract_id, &20_000, &200);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 3000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Phase1"),
+        },
+        Milestone {
+            amount: 2000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Phase2"),
+        },
+    ];
+
+    // Two funded escrows and one unfunded escrow for the same recipient
+    for escrow_id in [20u64, 21u64, 22u64] {
+        client.create_escrow(
+            &escrow_id,
+            &depositor,
+            &recipient,
+            &token_address,
+            &milestones,
+            &1706400000u64,
+            &None,
+            &None,
+            &5000,
+            &repeat_deal_options(EscrowKind::Standard),
+        );
+    }
+    client.deposit_funds(&20);
+    client.deposit_funds(&21);
+    client.release_milestone(&21, &0);
+
+    // A funded refund guarantee pays its depositor, not the recipient
+    client.create_escrow(
+        &23,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+        &None,
+        &None,
+        &5000,
+        &repeat_deal_options(EscrowKind::RefundGuarantee),
+    );
+    client.deposit_funds(&23);
+
+    let pending = client.get_pending_for_recipient(&recipient, &0, &10);
+    assert_eq!(pending, vec![&env, (20u64, 5000i128), (21u64, 2000i128)]);
+
+    // Pagination scans the recipient index from the given offset
+    let page = client.get_pending_for_recipient(&recipient, &1, &1);
+    assert_eq!(page, vec![&env, (21u64, 2000i128)]);
+
+    let other = Address::generate(&env);
+    assert_eq!(client.get_pending_for_recipient(&other, &0, &10).len(), 0);
+}
+
+#[cfg(feature = "views-extended")]
+#[test]
+fn test_get_escrow_by_external_ref() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 30u64;
+
+    let (_, token_address) = create_test_token(&env, &admin);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 1000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Order"),
+        },
+    ];
+
+    let order_ref = BytesN::from_array(&env, &[7u8; 32]);
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+        &Some(order_ref.clone()),
+        &None,
+        &1000,
+        &EscrowOptions::new(EscrowKind::Standard),
+    );
+
+    let (found_id, escrow) = client.get_escrow_by_ref(&order_ref);
+    assert_eq!(found_id, escrow_id);
+    assert_eq!(escrow.depositor, depositor);
+
+    // The same reference cannot be bound to a second escrow
+    let result = client.try_create_escrow(
+        &31u64,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+        &Some(order_ref),
+        &None,
+        &1000,
+        &EscrowOptions::new(EscrowKind::Standard),
+    );
+    assert_eq!(result, Err(Ok(Error::ExternalRefAlreadyExists)));
+
+    let unknown_ref = BytesN::from_array(&env, &[9u8; 32]);
+    assert_eq!(
+        client.try_get_escrow_by_ref(&unknown_ref).err(),
+        Some(Ok(Error::EscrowNotFound))
+    );
+}
+
+#[test]
+fn test_get_contract_info() {
+    let env = feature_dependent_env();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let info = client.get_contract_info();
+    assert_eq!(info.name, Symbol::new(&env, "VaultixEscrow"));
+    assert_eq!(
+        info.version,
+        soroban_sdk::String::from_str(&env, env!("CARGO_PKG_VERSION"))
+    );
+    assert_eq!(info.interface_version, 1);
+    assert_eq!(
+        info.features.contains(symbol_short!("disputes")),
+        cfg!(feature = "disputes")
+    );
+    assert!(!info.features.contains(symbol_short!("vesting")));
+}
+
+#[test]
+fn test_fee_burn_share() {
+    let env = feature_dependent_env();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    // 10% platform fee, half of which is burned
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(1000));
+    client.set_burn_config(&5000, &None);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 40u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &10_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 6000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Phase1"),
+        },
+        Milestone {
+            amount: 4000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Phase2"),
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+        &None,
+        &None,
+        &10000,
+        &EscrowOptions::new(EscrowKind::Standard),
+    );
+    token_client.approve(&depositor, &contract_id, &10_000, &200);
+    client.deposit_funds(&escrow_id);
+
+    client.release_milestone(&escrow_id, &0);
+    assert_eq!(token_client.balance(&recipient), 5400);
+    assert_eq!(token_client.balance(&treasury), 300);
+    #[cfg(feature = "views-extended")]
+    assert_eq!(client.burned_total(&token_address), 300);
+    assert_eq!(token_client.balance(&contract_id), 4000);
+
+    // Non-burnable tokens can route the burn share to a dead address instead
+    let dead = Address::generate(&env);
+    client.set_burn_config(&5000, &Some(dead.clone()));
+    client.release_milestone(&escrow_id, &1);
+    assert_eq!(token_client.balance(&dead), 200);
+    assert_eq!(token_client.balance(&treasury), 500);
+    #[cfg(feature = "views-extended")]
+    assert_eq!(client.burned_total(&token_address), 500);
+    assert_eq!(token_client.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_invalid_burn_config_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &None);
+
+    let result = client.try_set_burn_config(&10_001, &None);
+    assert_eq!(result, Err(Ok(Error::InvalidFeeConfiguration)));
+}
+
+#[test]
+fn test_attest_settlement_closes_milestone() {
+    let env = feature_dependent_env();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+    let admin = Address::generate(&env);
+    client.init(&admin);
+    let attestor = Address::generate(&env);
+    client.set_attestor(&attestor);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let escrow_id = 50u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &10_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 6000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Wire"),
+        },
+        Milestone {
+            amount: 4000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Crypto"),
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+        &None,
+        &None,
+        &10000,
+        &EscrowOptions::new(EscrowKind::Standard),
+    );
+    token_client.approve(&depositor, &contract_id, &10_000, &200);
+    client.deposit_funds(&escrow_id);
+
+    let wire_proof = BytesN::from_array(&env, &[1u8; 32]);
+    client.attest_settlement(&escrow_id, &0, &wire_proof);
+
+    // The off-chain milestone pays nothing to the recipient
+    assert_eq!(token_client.balance(&recipient), 0);
+    assert_eq!(token_client.balance(&depositor), 6000);
+    assert_eq!(token_client.balance(&contract_id), 4000);
+    #[cfg(feature = "views-extended")]
+    assert_eq!(client.get_attestation(&escrow_id, &0), Some(wire_proof));
+
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.total_amount, 4000);
+    assert_eq!(
+        escrow.milestones.get(0).unwrap().status,
+        MilestoneStatus::SettledOffChain
+    );
+    assert_eq!(client.get_total_value_locked(&None), 4000);
+
+    // A settled milestone cannot be released again
+    let result = client.try_release_milestone(&escrow_id, &0);
+    assert_eq!(result, Err(Ok(Error::MilestoneAlreadyReleased)));
+
+    client.release_milestone(&escrow_id, &1);
+    client.complete_escrow(&escrow_id);
+    assert_eq!(client.get_state(&escrow_id), EscrowStatus::Completed);
+    assert_eq!(client.get_total_value_locked(&None), 0);
+}
+
+#[test]
+fn test_attest_settlement_requires_attestor() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let proof = BytesN::from_array(&env, &[1u8; 32]);
+    let result = client.try_attest_settlement(&1, &0, &proof);
+    assert_eq!(result, Err(Ok(Error::AttestorNotInitialized)));
+}
+
+#[cfg(feature = "disputes")]
+#[test]
+fn test_partial_dispute_releases_undisputed_remainder() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+    let admin = Address::generate(&env);
+    client.init(&admin);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let escrow_id = 60u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &10_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 6000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Phase1"),
+        },
+        Milestone {
+            amount: 4000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Phase2"),
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+        &None,
+        &None,
+        &10000,
+        &EscrowOptions::new(EscrowKind::Standard),
+    );
+    token_client.approve(&depositor, &contract_id, &10_000, &200);
+    client.deposit_funds(&escrow_id);
+
+    client.open_partial_dispute(&escrow_id, &0, &depositor, &1000);
+    assert_eq!(client.get_partial_dispute(&escrow_id, &0), Some(1000));
+    assert_eq!(client.get_state(&escrow_id), EscrowStatus::Active);
+
+    // Only the undisputed 5000 is released; the slice stays frozen
+    client.release_milestone(&escrow_id, &0);
+    assert_eq!(token_client.balance(&recipient), 5000);
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.total_released, 5000);
+    assert_eq!(
+        escrow.milestones.get(0).unwrap().status,
+        MilestoneStatus::Disputed
+    );
+
+    // Other milestones keep flowing
+    client.release_milestone(&escrow_id, &1);
+    assert_eq!(token_client.balance(&recipient), 9000);
+
+    client.resolve_partial_dispute(&escrow_id, &0, &recipient);
+    assert_eq!(token_client.balance(&recipient), 10_000);
+    assert_eq!(client.get_partial_dispute(&escrow_id, &0), None);
+
+    client.complete_escrow(&escrow_id);
+    assert_eq!(client.get_state(&escrow_id), EscrowStatus::Completed);
+}
+
+#[cfg(feature = "disputes")]
+#[test]
+fn test_partial_dispute_refunds_slice_to_depositor() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+    let admin = Address::generate(&env);
+    client.init(&admin);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let escrow_id = 61u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &5000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 5000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Task"),
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+        &None,
+        &None,
+        &5000,
+        &EscrowOptions::new(EscrowKind::Standard),
+    );
+    token_client.approve(&depositor, &contract_id, &5000, &200);
+    client.deposit_funds(&escrow_id);
+
+    let result = client.try_open_partial_dispute(&escrow_id, &0, &recipient, &5001);
+    assert_eq!(result, Err(Ok(Error::InvalidMilestoneAmount)));
+
+    client.open_partial_dispute(&escrow_id, &0, &recipient, &2000);
+    client.resolve_partial_dispute(&escrow_id, &0, &depositor);
+    assert_eq!(token_client.balance(&depositor), 2000);
+
+    // The milestone shrinks to its undisputed part and releases normally
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.total_amount, 3000);
+    assert_eq!(escrow.milestones.get(0).unwrap().amount, 3000);
+
+    client.release_milestone(&escrow_id, &0);
+    assert_eq!(token_client.balance(&recipient), 3000);
+    assert_eq!(token_client.balance(&contract_id), 0);
+}
+
+#[contract]
+pub struct FlatFeeStrategy;
+
+#[contractimpl]
+impl FlatFeeStrategy {
+    pub fn quote(_env: Env, _escrow: Escrow, milestone: Milestone) -> i128 {
+        if milestone.amount >= 5000 {
+            250
+        } else {
+            100
+        }
+    }
+}
+
+#[test]
+fn test_external_fee_strategy() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &None);
+    let strategy = env.register_contract(None, FlatFeeStrategy);
+    client.set_fee_strategy(&Some(strategy.clone()));
+    assert_eq!(client.get_fee_strategy(), Some(strategy));
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 70u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &10_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 6000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Phase1"),
+        },
+        Milestone {
+            amount: 4000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Phase2"),
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+        &None,
+        &None,
+        &10000,
+        &EscrowOptions::new(EscrowKind::Standard),
+    );
+    token_client.approve(&depositor, &contract_id, &10_000, &200);
+    client.deposit_funds(&escrow_id);
+
+    client.release_milestone(&escrow_id, &0);
+    assert_eq!(token_client.balance(&treasury), 250);
+    assert_eq!(token_client.balance(&recipient), 5750);
+
+    // Clearing the strategy falls back to the built-in 50 bps fee
+    client.set_fee_strategy(&None);
+    client.release_milestone(&escrow_id, &1);
+    assert_eq!(token_client.balance(&treasury), 270);
+    assert_eq!(token_client.balance(&recipient), 9730);
+}
+
+#[test]
+fn test_find_milestone_by_label() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 80u64;
+
+    let (_, token_address) = create_test_token(&env, &admin);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 1000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Design"),
+        },
+        Milestone {
+            amount: 2000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Build"),
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+        &None,
+        &None,
+        &3000,
+        &EscrowOptions::new(EscrowKind::Standard),
+    );
+
+    assert_eq!(
+        client.find_milestone(&escrow_id, &symbol_short!("Build")),
+        1
+    );
+    assert_eq!(
+        client.try_find_milestone(&escrow_id, &symbol_short!("Deploy")),
+        Err(Ok(Error::MilestoneNotFound))
+    );
+}
+
+#[test]
+fn test_duplicate_milestone_labels_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    let (_, token_address) = create_test_token(&env, &admin);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 1000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Task"),
+        },
+        Milestone {
+            amount: 2000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Task"),
+        },
+    ];
+
+    let result = client.try_create_escrow(
+        &81u64,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+        &None,
+        &None,
+        &3000,
+        &EscrowOptions::new(EscrowKind::Standard),
+    );
+    assert_eq!(result, Err(Ok(Error::DuplicateMilestoneLabel)));
+}
+
+#[test]
+fn test_terms_hash_binding() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 90u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &1000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 1000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Task"),
+        },
+    ];
+
+    let terms = BytesN::from_array(&env, &[3u8; 32]);
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+        &None,
+        &Some(terms.clone()),
+        &1000,
+        &EscrowOptions::new(EscrowKind::Standard),
+    );
+    token_client.approve(&depositor, &contract_id, &1000, &200);
+
+    // Funding waits for the recipient to sign the terms
+    assert_eq!(
+        client.try_deposit_funds(&escrow_id),
+        Err(Ok(Error::TermsNotAccepted))
+    );
+    assert!(!client.verify_terms(&escrow_id, &terms));
+
+    let other_document = BytesN::from_array(&env, &[4u8; 32]);
+    assert_eq!(
+        client.try_accept_terms(&escrow_id, &other_document),
+        Err(Ok(Error::TermsNotFound))
+    );
+
+    client.accept_terms(&escrow_id, &terms);
+    assert_eq!(
+        env.auths()[0].0,
+        recipient,
+        "terms must be accepted by the recipient"
+    );
+    assert!(client.verify_terms(&escrow_id, &terms));
+    assert!(!client.verify_terms(&escrow_id, &other_document));
+
+    client.deposit_funds(&escrow_id);
+    assert_eq!(client.get_state(&escrow_id), EscrowStatus::Active);
+}
+
+#[test]
+fn test_refund_guarantee_returned_on_delivery() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let seller = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 91u64;
+
+    client.initialize(&treasury, &Some(0));
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&seller, &1000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 1000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Shipment"),
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &seller,
+        &buyer,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+        &None,
+        &None,
+        &1000,
+        &EscrowOptions::new(EscrowKind::RefundGuarantee),
+    );
+    token_client.approve(&seller, &contract_id, &1000, &200);
+    client.deposit_funds(&escrow_id);
+
+    // The seller cannot sign off on their own delivery
+    assert_eq!(
+        client.try_confirm_delivery(&escrow_id, &0, &seller),
+        Err(Ok(Error::UnauthorizedAccess))
+    );
+
+    client.confirm_delivery(&escrow_id, &0, &buyer);
+    assert_eq!(token_client.balance(&seller), 1000);
+    assert_eq!(token_client.balance(&buyer), 0);
+
+    client.complete_escrow(&escrow_id);
+    assert_eq!(
+        client.try_claim_guarantee(&escrow_id),
+        Err(Ok(Error::InvalidEscrowStatus))
+    );
+}
+
+#[test]
+fn test_refund_guarantee_claimed_after_deadline() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let seller = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 92u64;
+    let deadline = 1706400000u64;
+
+    client.initialize(&treasury, &Some(0));
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&seller, &1000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 600,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Part1"),
+        },
+        Milestone {
+            amount: 400,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Part2"),
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &seller,
+        &buyer,
+        &token_address,
+        &milestones,
+        &deadline,
+        &None,
+        &None,
+        &1000,
+        &EscrowOptions::new(EscrowKind::RefundGuarantee),
+    );
+    token_client.approve(&seller, &contract_id, &1000, &200);
+    client.deposit_funds(&escrow_id);
+
+    // Buyer confirms the first part; the second never arrives
+    client.release_milestone(&escrow_id, &0);
+    assert_eq!(
+        env.auths()[0].0,
+        buyer,
+        "guarantee milestones are released by the buyer"
+    );
+    assert_eq!(token_client.balance(&seller), 600);
+
+    env.ledger().with_mut(|li| li.timestamp = deadline);
+    assert_eq!(
+        client.try_claim_guarantee(&escrow_id),
+        Err(Ok(Error::DeadlineNotReached))
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = deadline + 1);
+    assert_eq!(client.claim_guarantee(&escrow_id), 400);
+    assert_eq!(token_client.balance(&buyer), 400);
+    assert_eq!(token_client.balance(&contract_id), 0);
+
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Completed);
+    assert_eq!(escrow.total_released, 1000);
+}
+
+#[test]
+fn test_claim_guarantee_rejects_standard_escrow() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let (_, _, token_address) = create_token_contract(&env, &admin);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 100,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Task"),
+        },
+    ];
+    client.create_escrow(
+        &93,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &0u64,
+        &None,
+        &None,
+        &100,
+        &EscrowOptions::new(EscrowKind::Standard),
+    );
+
+    assert_eq!(
+        client.try_claim_guarantee(&93),
+        Err(Ok(Error::InvalidEscrowKind))
+    );
+}
+
+#[test]
+fn test_swap_releases_both_legs() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 94u64;
+
+    client.initialize(&treasury, &Some(0));
+    let (token_a, token_a_admin, token_a_address) = create_token_contract(&env, &admin);
+    let (token_b, token_b_admin, token_b_address) = create_token_contract(&env, &admin);
+    token_a_admin.mint(&depositor, &1000);
+    token_b_admin.mint(&recipient, &30);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 700,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Lot1"),
+        },
+        Milestone {
+            amount: 300,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Lot2"),
+        },
+    ];
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_a_address,
+        &milestones,
+        &1706400000u64,
+        &None,
+        &None,
+        &1000,
+        &EscrowOptions::new(EscrowKind::Swap),
+    );
+    token_a.approve(&depositor, &contract_id, &1000, &200);
+    token_b.approve(&recipient, &contract_id, &30, &200);
+
+    // The depositor cannot fund before the recipient's leg is locked
+    assert_eq!(
+        client.try_deposit_funds(&escrow_id),
+        Err(Ok(Error::CounterLegNotFunded))
+    );
+    assert_eq!(
+        client.try_fund_counter_leg(&escrow_id, &token_b_address, &vec![&env, 30]),
+        Err(Ok(Error::InvalidMilestoneAmount))
+    );
+
+    client.fund_counter_leg(&escrow_id, &token_b_address, &vec![&env, 20, 10]);
+    assert_eq!(token_b.balance(&contract_id), 30);
+    client.deposit_funds(&escrow_id);
+
+    client.release_milestone(&escrow_id, &0);
+    assert_eq!(token_a.balance(&recipient), 700);
+    assert_eq!(token_b.balance(&depositor), 20);
+
+    client.confirm_delivery(&escrow_id, &1, &depositor);
+    assert_eq!(token_a.balance(&recipient), 1000);
+    assert_eq!(token_b.balance(&depositor), 30);
+    assert_eq!(token_b.balance(&contract_id), 0);
+
+    client.complete_escrow(&escrow_id);
+    assert_eq!(client.get_state(&escrow_id), EscrowStatus::Completed);
+}
+
+#[test]
+fn test_swap_cancel_refunds_both_legs() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 95u64;
+
+    let (token_a, token_a_admin, token_a_address) = create_token_contract(&env, &admin);
+    let (token_b, token_b_admin, token_b_address) = create_token_contract(&env, &admin);
+    token_a_admin.mint(&depositor, &500);
+    token_b_admin.mint(&recipient, &1);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 500,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Artwork"),
+        },
+    ];
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_a_address,
+        &milestones,
+        &1706400000u64,
+        &None,
+        &None,
+        &500,
+        &EscrowOptions::new(EscrowKind::Swap),
+    );
+    token_a.approve(&depositor, &contract_id, &500, &200);
+    token_b.approve(&recipient, &contract_id, &1, &200);
+
+    client.fund_counter_leg(&escrow_id, &token_b_address, &vec![&env, 1]);
+    client.deposit_funds(&escrow_id);
+    assert_eq!(
+        client.try_withdraw_counter_leg(&escrow_id),
+        Err(Ok(Error::InvalidEscrowStatus))
+    );
+
+    client.cancel_escrow(&escrow_id);
+    assert_eq!(token_a.balance(&depositor), 500);
+    assert_eq!(token_b.balance(&recipient), 1);
+    assert_eq!(token_b.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_sealed_milestone_amounts() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 96u64;
+
+    client.initialize(&treasury, &Some(0));
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &1000);
+
+    let salt_a = BytesN::from_array(&env, &[7u8; 32]);
+    let salt_b = BytesN::from_array(&env, &[8u8; 32]);
+    client.create_sealed_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &1000,
+        &vec![&env, symbol_short!("Audit"), symbol_short!("Fixes")],
+        &vec![
+            &env,
+            commit_amount(&env, 650, &salt_a),
+            commit_amount(&env, 350, &salt_b),
+        ],
+        &1706400000u64,
+    );
+    token_client.approve(&depositor, &contract_id, &1000, &200);
+    client.deposit_funds(&escrow_id);
+
+    // Only the total is visible until a milestone is revealed
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.total_amount, 1000);
+    assert!(escrow.milestones.iter().all(|m| m.amount == 0));
+    assert_eq!(
+        client.try_release_milestone(&escrow_id, &0),
+        Err(Ok(Error::MilestoneSealed))
+    );
+    assert_eq!(
+        client.try_reveal_milestone(&escrow_id, &0, &600, &salt_a),
+        Err(Ok(Error::CommitmentMismatch))
+    );
+
+    client.reveal_milestone(&escrow_id, &0, &650, &salt_a);
+    client.release_milestone(&escrow_id, &0);
+    assert_eq!(token_client.balance(&recipient), 650);
+    assert_eq!(
+        client
+            .get_escrow(&escrow_id)
+            .milestones
+            .get(1)
+            .unwrap()
+            .amount,
+        0
+    );
+
+    client.reveal_milestone(&escrow_id, &1, &350, &salt_b);
+    client.release_milestone(&escrow_id, &1);
+    client.complete_escrow(&escrow_id);
+    assert_eq!(token_client.balance(&recipient), 1000);
+}
+
+#[test]
+fn test_sealed_reveals_must_match_total() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 97u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &500);
+
+    // Commitments that add up to more than the advertised total
+    let salt = BytesN::from_array(&env, &[9u8; 32]);
+    client.create_sealed_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &500,
+        &vec![&env, symbol_short!("Only")],
+        &vec![&env, commit_amount(&env, 800, &salt)],
+        &1706400000u64,
+    );
+    token_client.approve(&depositor, &contract_id, &500, &200);
+    client.deposit_funds(&escrow_id);
+
+    assert_eq!(
+        client.try_reveal_milestone(&escrow_id, &0, &800, &salt),
+        Err(Ok(Error::InvalidMilestoneAmount))
+    );
+    assert_eq!(
+        client.try_create_sealed_escrow(
+            &98,
+            &depositor,
+            &recipient,
+            &token_address,
+            &500,
+            &vec![&env, symbol_short!("A"), symbol_short!("B")],
+            &vec![&env, commit_amount(&env, 500, &salt)],
+            &1706400000u64,
+        ),
+        Err(Ok(Error::InvalidMilestoneAmount))
+    );
+}
+
+#[test]
+fn test_max_active_escrows_per_depositor() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    client.init(&admin);
+    let (_, _, token_address) = create_token_contract(&env, &admin);
+
+    assert_eq!(client.get_max_active_escrows(), 0);
+    client.set_max_active_escrows(&2);
+    assert_eq!(client.get_max_active_escrows(), 2);
+
+    let create = |escrow_id: u64, depositor: &Address| {
+        // Distinct labels so later escrows can be merged into earlier ones
+        let label = if escrow_id > 3 {
+            symbol_short!("Extra")
+        } else {
+            symbol_short!("Task")
+        };
+        let milestones = vec![
+            &env,
+            Milestone {
+                amount: 100,
+                status: MilestoneStatus::Pending,
+                description: label,
+            },
+        ];
+        client.try_create_escrow(
+            &escrow_id,
+            depositor,
+            &recipient,
+            &token_address,
+            &milestones,
+            &1706400000u64,
+            &None,
+            &None,
+            &100,
+            &repeat_deal_options(EscrowKind::Standard),
+        )
+    };
+
+    assert!(create(1, &depositor).is_ok());
+    assert!(create(2, &depositor).is_ok());
+    assert_eq!(create(3, &depositor), Err(Ok(Error::TooManyActiveEscrows)));
+
+    // The cap is per depositor
+    let other_depositor = Address::generate(&env);
+    assert!(create(3, &other_depositor).is_ok());
+
+    // Finished escrows free up a slot
+    client.cancel_escrow(&1);
+    assert!(create(4, &depositor).is_ok());
+    assert_eq!(create(5, &depositor), Err(Ok(Error::TooManyActiveEscrows)));
+
+    // So does merging two into one
+    client.merge_escrows(&vec![&env, 2u64, 4]);
+    assert!(create(5, &depositor).is_ok());
+}
+
+#[cfg(feature = "views-extended")]
+#[test]
+fn test_get_authorized_actions() {
+    let env = feature_dependent_env();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 99u64;
+
+    client.initialize(&treasury, &None);
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &1000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 1000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Task"),
+        },
+    ];
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+        &None,
+        &None,
+        &1000,
+        &EscrowOptions::new(EscrowKind::Standard),
+    );
+
+    // Unfunded: the depositor can only cancel
+    let actions = client.get_authorized_actions(&escrow_id, &depositor);
+    assert!(!actions.release);
+    assert!(actions.cancel);
+    assert!(!actions.dispute);
+    assert!(!actions.claim);
+
+    token_client.approve(&depositor, &contract_id, &1000, &200);
+    client.deposit_funds(&escrow_id);
+
+    let actions = client.get_authorized_actions(&escrow_id, &depositor);
+    assert!(actions.release);
+    assert!(actions.cancel);
+    assert_eq!(actions.dispute, cfg!(feature = "disputes"));
+
+    let actions = client.get_authorized_actions(&escrow_id, &recipient);
+    assert!(!actions.release);
+    assert!(!actions.cancel);
+    assert_eq!(actions.dispute, cfg!(feature = "disputes"));
+
+    assert_eq!(
+        client.get_authorized_actions(&escrow_id, &stranger),
+        AuthorizedActions {
+            release: false,
+            cancel: false,
+            dispute: false,
+            claim: false,
+        }
+    );
+
+    client.release_milestone(&escrow_id, &0);
+    let actions = client.get_authorized_actions(&escrow_id, &depositor);
+    assert!(!actions.release);
+    assert!(!actions.cancel);
+}
+
+#[test]
+fn test_pre_approved_releases() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 100u64;
+
+    client.initialize(&treasury, &Some(0));
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &1000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 200,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Week1"),
+        },
+        Milestone {
+            amount: 300,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Week2"),
+        },
+        Milestone {
+            amount: 500,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Week3"),
+        },
+    ];
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+        &None,
+        &None,
+        &1000,
+        &EscrowOptions::new(EscrowKind::Standard),
+    );
+    token_client.approve(&depositor, &contract_id, &1000, &200);
+    client.deposit_funds(&escrow_id);
+
+    assert_eq!(
+        client.try_release_approved(&escrow_id, &0),
+        Err(Ok(Error::AllowanceExceeded))
+    );
+
+    client.approve_releases(&escrow_id, &2, &600);
+    assert_eq!(env.auths()[0].0, depositor);
+
+    // Triggered without any signature from the depositor
+    client.release_approved(&escrow_id, &0);
+    assert!(env.auths().is_empty());
+    assert_eq!(
+        client.get_release_allowance(&escrow_id),
+        Some(ReleaseAllowance {
+            releases_left: 1,
+            amount_left: 400,
+        })
+    );
+
+    // Milestone 2 is over the remaining budget; milestone 1 fits
+    assert_eq!(
+        client.try_release_approved(&escrow_id, &2),
+        Err(Ok(Error::AllowanceExceeded))
+    );
+    client.release_approved(&escrow_id, &1);
+    assert_eq!(token_client.balance(&recipient), 500);
+
+    // Out of releases even though budget remains
+    assert_eq!(
+        client.try_release_approved(&escrow_id, &2),
+        Err(Ok(Error::AllowanceExceeded))
+    );
+
+    client.approve_releases(&escrow_id, &0, &0);
+    assert_eq!(client.get_release_allowance(&escrow_id), None);
+}
+
+#[test]
+fn test_cliff_locks_first_milestone() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 101u64;
+    let cliff_until = 1_700_000_000u64;
+
+    client.initialize(&treasury, &Some(0));
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &1000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 250,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Cliff"),
+        },
+        Milestone {
+            amount: 750,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Report"),
+        },
+    ];
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+        &None,
+        &None,
+        &1000,
+        &EscrowOptions {
+            kind: EscrowKind::Standard,
+            cliff_until: Some(cliff_until),
+            refund_to: RefundTo::Depositor,
+            referral: Referral::None,
+            lock_recipient: false,
+            auth_mode: AuthMode::Strict,
+            release_schedule: ReleaseSchedule::Immediate,
+            dispute_policy: DisputePolicy::ContinueOthers,
+            allow_duplicate: false,
+            fee_payer: FeePayer::Recipient,
+            promo: Promo::None,
+        },
+    );
+    token_client.approve(&depositor, &contract_id, &1000, &200);
+    client.deposit_funds(&escrow_id);
+
+    env.ledger().with_mut(|li| li.timestamp = cliff_until - 1);
+    assert_eq!(
+        client.try_release_milestone(&escrow_id, &0),
+        Err(Ok(Error::CliffNotReached))
+    );
+    assert_eq!(
+        client.try_confirm_delivery(&escrow_id, &0, &depositor),
+        Err(Ok(Error::CliffNotReached))
+    );
+
+    // Later milestones are not held back by the cliff
+    client.release_milestone(&escrow_id, &1);
+    assert_eq!(token_client.balance(&recipient), 750);
+
+    env.ledger().with_mut(|li| li.timestamp = cliff_until);
+    client.release_milestone(&escrow_id, &0);
+    assert_eq!(token_client.balance(&recipient), 1000);
+}
+
+#[test]
+fn test_grant_reassign_and_clawback() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury_wallet = Address::generate(&env);
+    let grantee = Address::generate(&env);
+    let replacement = Address::generate(&env);
+    let program_admin = Address::generate(&env);
+    let fee_treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 102u64;
+
+    client.initialize(&fee_treasury, &Some(0));
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&treasury_wallet, &900);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 300,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Proposal"),
+        },
+        Milestone {
+            amount: 300,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Beta"),
+        },
+        Milestone {
+            amount: 300,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Launch"),
+        },
+    ];
+    client.create_escrow(
+        &escrow_id,
+        &treasury_wallet,
+        &grantee,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+        &None,
+        &None,
+        &900,
+        &EscrowOptions::new(EscrowKind::Grant),
+    );
+    client.set_program_admin(&escrow_id, &program_admin);
+    assert_eq!(env.auths()[0].0, treasury_wallet);
+    assert_eq!(
+        client.get_program_admin(&escrow_id),
+        Some(program_admin.clone())
+    );
+
+    token_client.approve(&treasury_wallet, &contract_id, &900, &200);
+    client.deposit_funds(&escrow_id);
+    client.release_milestone(&escrow_id, &0);
+    assert_eq!(token_client.balance(&grantee), 300);
+
+    // The grantee goes quiet; the program admin hands the grant over
+    client.reassign_recipient(&escrow_id, &replacement);
+    assert_eq!(env.auths()[0].0, program_admin);
+    assert_eq!(env.auths()[1].0, treasury_wallet);
+    assert_eq!(
+        client.try_reassign_recipient(&escrow_id, &treasury_wallet),
+        Err(Ok(Error::SelfDealing))
+    );
+
+    client.release_milestone(&escrow_id, &1);
+    assert_eq!(token_client.balance(&replacement), 300);
+    assert_eq!(token_client.balance(&grantee), 300);
+
+    assert_eq!(client.clawback_grant(&escrow_id), 300);
+    assert_eq!(env.auths()[0].0, program_admin);
+    assert_eq!(token_client.balance(&treasury_wallet), 300);
+    assert_eq!(client.get_state(&escrow_id), EscrowStatus::Cancelled);
+}
+
+#[test]
+fn test_clawback_requires_grant_escrow() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let (_, _, token_address) = create_token_contract(&env, &admin);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 100,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Task"),
+        },
+    ];
+    client.create_escrow(
+        &103,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+        &None,
+        &None,
+        &100,
+        &EscrowOptions::new(EscrowKind::Standard),
+    );
+
+    assert_eq!(
+        client.try_set_program_admin(&103, &admin),
+        Err(Ok(Error::InvalidEscrowKind))
+    );
+    assert_eq!(
+        client.try_clawback_grant(&103),
+        Err(Ok(Error::InvalidEscrowKind))
+    );
+}
+
+#[test]
+fn test_create_escrow_by_bps_assigns_remainder_to_last() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let (_, _, token_address) = create_token_contract(&env, &admin);
+    let labels = vec![
+        &env,
+        symbol_short!("Design"),
+        symbol_short!("Build"),
+        symbol_short!("Ship"),
+    ];
+
+    client.create_escrow_by_bps(
+        &104,
+        &depositor,
+        &recipient,
+        &token_address,
+        &1000,
+        &labels,
+        &vec![&env, 3333, 3333, 3334],
+        &1706400000u64,
+    );
+    let escrow = client.get_escrow(&104);
+    assert_eq!(escrow.total_amount, 1000);
+    let mut amounts = escrow.milestones.iter().map(|m| m.amount);
+    assert_eq!(amounts.next(), Some(333));
+    assert_eq!(amounts.next(), Some(333));
+    assert_eq!(amounts.next(), Some(334));
+
+    assert_eq!(
+        client.try_create_escrow_by_bps(
+            &105,
+            &depositor,
+            &recipient,
+            &token_address,
+            &1000,
+            &labels,
+            &vec![&env, 5000, 4000, 500],
+            &1706400000u64,
+        ),
+        Err(Ok(Error::InvalidMilestoneWeights))
+    );
+    // A weight too small to be worth a single unit is rejected as zero
+    assert_eq!(
+        client.try_create_escrow_by_bps(
+            &105,
+            &depositor,
+            &recipient,
+            &token_address,
+            &10,
+            &labels,
+            &vec![&env, 5, 4995, 5000],
+            &1706400000u64,
+        ),
+        Err(Ok(Error::ZeroAmount))
+    );
+}
+
+#[test]
+fn test_expected_total_must_match_milestones() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let (_, _, token_address) = create_token_contract(&env, &admin);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 400,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Phase1"),
+        },
+        Milestone {
+            amount: 600,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Phase2"),
+        },
+    ];
+
+    // The depositor reviewed 900 but the milestones add up to 1000
+    assert_eq!(
+        client.try_create_escrow(
+            &106,
+            &depositor,
+            &recipient,
+            &token_address,
+            &milestones,
+            &1706400000u64,
+            &None,
+            &None,
+            &900,
+            &EscrowOptions::new(EscrowKind::Standard),
+        ),
+        Err(Ok(Error::TotalAmountMismatch))
+    );
+    assert_eq!(
+        client.try_get_escrow(&106).err(),
+        Some(Ok(Error::EscrowNotFound))
+    );
+}
+
+#[test]
+fn test_releases_go_to_payout_address() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let exchange_deposit = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 107u64;
+
+    client.initialize(&treasury, &Some(0));
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &1000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 400,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Phase1"),
+        },
+        Milestone {
+            amount: 600,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Phase2"),
+        },
+    ];
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+        &None,
+        &None,
+        &1000,
+        &EscrowOptions::new(EscrowKind::Standard),
+    );
+    token_client.approve(&depositor, &contract_id, &1000, &200);
+    client.deposit_funds(&escrow_id);
+
+    client.set_payout_address(&recipient, &Some(exchange_deposit.clone()));
+    assert_eq!(env.auths()[0].0, recipient);
+    assert_eq!(client.get_payout_address(&recipient), exchange_deposit);
+
+    client.release_milestone(&escrow_id, &0);
+    assert_eq!(token_client.balance(&exchange_deposit), 400);
+    assert_eq!(token_client.balance(&recipient), 0);
+    // The escrow still names the original recipient
+    assert_eq!(client.get_escrow(&escrow_id).recipient, recipient);
+
+    client.set_payout_address(&recipient, &None);
+    client.confirm_delivery(&escrow_id, &1, &depositor);
+    assert_eq!(token_client.balance(&recipient), 600);
+}
+
+#[cfg(feature = "views-extended")]
+#[test]
+fn test_address_stats_track_flows_and_statuses() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    client.initialize(&treasury, &Some(50));
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &3000);
+    token_client.approve(&depositor, &contract_id, &3000, &200);
+
+    for escrow_id in [108u64, 109, 110] {
+        let milestones = vec![
+            &env,
+            Milestone {
+                amount: 400,
+                status: MilestoneStatus::Pending,
+                description: symbol_short!("Phase1"),
+            },
+            Milestone {
+                amount: 600,
+                status: MilestoneStatus::Pending,
+                description: symbol_short!("Phase2"),
+            },
+        ];
+        client.create_escrow(
+            &escrow_id,
+            &depositor,
+            &recipient,
+            &token_address,
+            &milestones,
+            &1706400000u64,
+            &None,
+            &None,
+            &1000,
+            &repeat_deal_options(EscrowKind::Standard),
+        );
+    }
+
+    // 108 is released and completed, 109 refunded, 110 left unfunded
+    client.deposit_funds(&108);
+    client.release_milestone(&108, &0);
+    client.confirm_delivery(&108, &1, &depositor);
+    client.complete_escrow(&108);
+    client.deposit_funds(&109);
+    assert_eq!(client.get_address_stats(&depositor).counts.active, 1);
+    // Cancelling moves it from active to cancelled
+    client.cancel_escrow(&109);
+
+    let stats = client.get_address_stats(&depositor);
+    assert_eq!(
+        stats.totals,
+        AddressTotals {
+            deposited: 2000,
+            received: 0,
+            refunded: 1000,
+            fees_paid: 0,
+        }
+    );
+    assert_eq!(
+        stats.counts,
+        StatusCounts {
+            created: 1,
+            active: 0,
+            completed: 1,
+            cancelled: 1,
+            disputed: 0,
+            resolved: 0,
+        }
+    );
+
+    let stats = client.get_address_stats(&recipient);
+    assert_eq!(
+        stats.totals,
+        AddressTotals {
+            deposited: 0,
+            received: 998,
+            refunded: 0,
+            fees_paid: 2,
+        }
+    );
+    assert_eq!(stats.counts, client.get_address_stats(&depositor).counts);
+
+    let stranger = client.get_address_stats(&Address::generate(&env));
+    assert_eq!(stranger.totals.deposited, 0);
+    assert_eq!(stranger.counts.created, 0);
+}
+
+/// Ledger up to which `contract`'s storage entry under `key` stays live
+fn live_until(env: &Env, contract: &Address, key: &ScVal) -> u32 {
+    let contract = ScAddress::try_from(contract).unwrap();
+    env.to_snapshot()
+        .ledger
+        .ledger_entries
+        .iter()
+        .find_map(|(entry_key, (_, live_until))| match entry_key.as_ref() {
+            LedgerKey::ContractData(data) if data.contract == contract && data.key == *key => {
+                *live_until
+            }
+            _ => None,
+        })
+        .unwrap()
+}
+
+fn instance_live_until(env: &Env, contract: &Address) -> u32 {
+    live_until(env, contract, &ScVal::LedgerKeyContractInstance)
+}
+
+#[test]
+fn test_write_paths_extend_instance_ttl() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    client.initialize(&treasury, &None);
+    client.init(&admin);
+    let sequence = env.ledger().sequence();
+    assert_eq!(
+        instance_live_until(&env, &contract_id),
+        sequence + 2_000_000
+    );
+
+    // Reads leave the TTL alone, writes bump it once under the threshold
+    env.ledger().with_mut(|l| l.sequence_number += 1_950_000);
+    client.get_config();
+    assert_eq!(
+        instance_live_until(&env, &contract_id),
+        sequence + 2_000_000
+    );
+    client.update_fee(&100);
+    let sequence = env.ledger().sequence();
+    assert_eq!(
+        instance_live_until(&env, &contract_id),
+        sequence + 2_000_000
+    );
+
+    assert_eq!(
+        client.try_set_instance_ttl(&5000, &1000),
+        Err(Ok(Error::InvalidTtlConfig))
+    );
+    assert_eq!(
+        client.try_set_instance_ttl(&1000, &(env.storage().max_ttl() + 1)),
+        Err(Ok(Error::InvalidTtlConfig))
+    );
+    client.set_instance_ttl(&1000, &3_000_000);
+    assert_eq!(
+        client.get_instance_ttl(),
+        InstanceTtl {
+            threshold: 1000,
+            extend_to: 3_000_000,
+        }
+    );
+
+    client.extend_contract_ttl();
+    assert_eq!(env.auths()[0].0, admin);
+    assert_eq!(
+        instance_live_until(&env, &contract_id),
+        sequence + 3_000_000
+    );
+}
+
+#[test]
+fn test_archive_completed_escrow() {
+    let env = feature_dependent_env();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 111u64;
+
+    client.initialize(&treasury, &Some(0));
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &1000);
+    token_client.approve(&depositor, &contract_id, &1000, &200);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 1000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Phase1"),
+        },
+    ];
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+        &None,
+        &None,
+        &1000,
+        &EscrowOptions::new(EscrowKind::Standard),
+    );
+    client.deposit_funds(&escrow_id);
+    client.release_milestone(&escrow_id, &0);
+    assert_eq!(
+        client.try_archive_escrow(&escrow_id, &depositor),
+        Err(Ok(Error::InvalidEscrowStatus))
+    );
+    client.complete_escrow(&escrow_id);
+    let closed_at = env.ledger().timestamp();
+    let final_state = client.get_escrow(&escrow_id);
+
+    assert_eq!(
+        client.try_archive_escrow(&escrow_id, &recipient),
+        Err(Ok(Error::RetentionNotElapsed))
+    );
+    env.ledger()
+        .with_mut(|l| l.timestamp += archive::ARCHIVE_RETENTION_SECS);
+    assert_eq!(
+        client.try_archive_escrow(&escrow_id, &Address::generate(&env)),
+        Err(Ok(Error::UnauthorizedAccess))
+    );
+    client.archive_escrow(&escrow_id, &recipient);
+
+    assert_eq!(
+        client.try_get_escrow(&escrow_id).err(),
+        Some(Ok(Error::EscrowNotFound))
+    );
+    assert_eq!(
+        client.get_archived_escrow(&escrow_id),
+        ArchivedEscrow {
+            state_hash: env.crypto().sha256(&final_state.to_xdr(&env)),
+            depositor: depositor.clone(),
+            recipient: recipient.clone(),
+            token_address: token_address.clone(),
+            total_amount: 1000,
+            total_released: 1000,
+            status: EscrowStatus::Completed,
+            closed_at,
+        }
+    );
+    #[cfg(feature = "views-extended")]
+    assert_eq!(client.get_address_stats(&depositor).counts.completed, 1);
+
+    // The archived ID stays taken
+    assert_eq!(
+        client
+            .try_create_escrow(
+                &escrow_id,
+                &depositor,
+                &recipient,
+                &token_address,
+                &milestones,
+                &1706400000u64,
+                &None,
+                &None,
+                &1000,
+                &EscrowOptions::new(EscrowKind::Standard),
+            )
+            .err(),
+        Some(Ok(Error::EscrowAlreadyExists))
+    );
+}
+
+#[test]
+fn test_bump_escrows_in_bulk() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    client.initialize(&treasury, &Some(0));
+    let (_, _, token_address) = create_token_contract(&env, &admin);
+    for escrow_id in [112u64, 113] {
+        let milestones = vec![
+            &env,
+            Milestone {
+                amount: 500,
+                status: MilestoneStatus::Pending,
+                description: symbol_short!("Phase1"),
+            },
+        ];
+        client.create_escrow(
+            &escrow_id,
+            &depositor,
+            &recipient,
+            &token_address,
+            &milestones,
+            &1706400000u64,
+            &None,
+            &None,
+            &500,
+            &repeat_deal_options(EscrowKind::Standard),
+        );
+    }
+
+    let ledgers = 3_000_000;
+    let results = client.bump_escrows(&vec![&env, 112u64, 999, 113], &ledgers);
+    assert_eq!(results, vec![&env, true, false, true]);
+    for escrow_id in [112u64, 113] {
+        let key: Val = get_storage_key(escrow_id).into_val(&env);
+        let key = ScVal::try_from_val(&env, &key).unwrap();
+        assert_eq!(
+            live_until(&env, &contract_id, &key),
+            env.ledger().sequence() + ledgers
+        );
+    }
+
+    assert_eq!(
+        client.try_bump_escrows(&vec![&env, 112u64], &(env.storage().max_ttl() + 1)),
+        Err(Ok(Error::InvalidTtlConfig))
+    );
+    let mut too_many = Vec::new(&env);
+    for escrow_id in 0..51u64 {
+        too_many.push_back(escrow_id);
+    }
+    assert_eq!(
+        client.try_bump_escrows(&too_many, &ledgers),
+        Err(Ok(Error::VectorTooLarge))
+    );
+}
+
+#[test]
+fn test_fee_in_effect_at_creation_applies() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    client.initialize(&treasury, &Some(100));
+    let first_ledger = env.ledger().sequence();
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &2000);
+    token_client.approve(&depositor, &contract_id, &2000, &200);
+
+    let create = |escrow_id: u64| {
+        let milestones = vec![
+            &env,
+            Milestone {
+                amount: 1000,
+                status: MilestoneStatus::Pending,
+                description: symbol_short!("Phase1"),
+            },
+        ];
+        client.create_escrow(
+            &escrow_id,
+            &depositor,
+            &recipient,
+            &token_address,
+            &milestones,
+            &1706400000u64,
+            &None,
+            &None,
+            &1000,
+            &repeat_deal_options(EscrowKind::Standard),
+        );
+        client.deposit_funds(&escrow_id);
+    };
+
+    create(114);
+    env.ledger().with_mut(|l| l.sequence_number += 10);
+    client.update_fee(&1000);
+    create(115);
+
+    assert_eq!(
+        client.get_fee_history(&0, &10),
+        vec![
+            &env,
+            FeeChange {
+                fee_bps: 100,
+                effective_ledger: first_ledger,
+            },
+            FeeChange {
+                fee_bps: 1000,
+                effective_ledger: first_ledger + 10,
+            },
+        ]
+    );
+    let page = client.get_fee_history(&1, &1);
+    assert_eq!(page.len(), 1);
+    assert_eq!(page.get_unchecked(0).fee_bps, 1000);
+
+    // The fee hike only applies to the escrow created after it
+    assert_eq!(client.get_escrow_fee(&114), 100);
+    assert_eq!(client.get_escrow_fee(&115), 1000);
+    client.release_milestone(&114, &0);
+    assert_eq!(token_client.balance(&treasury), 10);
+    client.release_milestone(&115, &0);
+    assert_eq!(token_client.balance(&treasury), 110);
+}
+
+#[test]
+fn test_refunds_go_to_refund_address() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let cold_wallet = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 116u64;
+
+    client.initialize(&treasury, &Some(0));
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &1000);
+    token_client.approve(&depositor, &contract_id, &1000, &200);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 1000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Phase1"),
+        },
+    ];
+    let mut options = EscrowOptions::new(EscrowKind::Standard);
+    options.refund_to = RefundTo::Address(cold_wallet.clone());
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+        &None,
+        &None,
+        &1000,
+        &options,
+    );
+    assert_eq!(client.get_refund_address(&escrow_id), cold_wallet);
+
+    client.set_refund_address(&escrow_id, &None);
+    assert_eq!(env.auths()[0].0, depositor);
+    assert_eq!(client.get_refund_address(&escrow_id), depositor);
+    client.set_refund_address(&escrow_id, &Some(cold_wallet.clone()));
+
+    client.deposit_funds(&escrow_id);
+    client.cancel_escrow(&escrow_id);
+    assert_eq!(token_client.balance(&cold_wallet), 1000);
+    assert_eq!(token_client.balance(&depositor), 0);
+}
+
+#[test]
+fn test_chargeback_window_holds_confirmed_releases() {
+    let env = feature_dependent_env();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let window = 3 * 24 * 60 * 60;
+
+    client.initialize(&treasury, &Some(0));
+    client.init(&admin);
+    client.set_chargeback_window(&window);
+    assert_eq!(client.get_chargeback_window(), window);
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &2000);
+    token_client.approve(&depositor, &contract_id, &2000, &200);
+    for escrow_id in [117u64, 118] {
+        let milestones = vec![
+            &env,
+            Milestone {
+                amount: 400,
+                status: MilestoneStatus::Pending,
+                description: symbol_short!("Phase1"),
+            },
+            Milestone {
+                amount: 600,
+                status: MilestoneStatus::Pending,
+                description: symbol_short!("Phase2"),
+            },
+        ];
+        client.create_escrow(
+            &escrow_id,
+            &depositor,
+            &recipient,
+            &token_address,
+            &milestones,
+            &1706400000u64,
+            &None,
+            &None,
+            &1000,
+            &repeat_deal_options(EscrowKind::Standard),
+        );
+        client.deposit_funds(&escrow_id);
+    }
+
+    client.confirm_delivery(&117, &0, &depositor);
+    assert_eq!(token_client.balance(&recipient), 0);
+    let escrow = client.get_escrow(&117);
+    assert_eq!(
+        escrow.milestones.get(0).unwrap().status,
+        MilestoneStatus::Held
+    );
+    assert_eq!(escrow.total_released, 0);
+
+    assert_eq!(
+        client.try_claim_held_release(&117, &0),
+        Err(Ok(Error::ChargebackWindowOpen))
+    );
+    assert_eq!(
+        client.try_cancel_escrow(&117),
+        Err(Ok(Error::MilestoneAlreadyReleased))
+    );
+
+    env.ledger().with_mut(|l| l.timestamp += window);
+    client.claim_held_release(&117, &0);
+    assert_eq!(token_client.balance(&recipient), 400);
+    assert_eq!(client.get_escrow(&117).total_released, 400);
+    assert_eq!(
+        client.try_claim_held_release(&117, &0),
+        Err(Ok(Error::InvalidEscrowStatus))
+    );
+
+    // Within the window the depositor can still dispute and win the funds back
+    #[cfg(feature = "disputes")]
+    {
+        client.confirm_delivery(&118, &0, &depositor);
+        client.raise_dispute(&118, &depositor);
+        client.resolve_dispute(&118, &depositor);
+        assert_eq!(token_client.balance(&depositor), 1000);
+        assert_eq!(
+            client.get_escrow(&118).milestones.get(0).unwrap().status,
+            MilestoneStatus::Disputed
+        );
+    }
+}
+
+#[test]
+fn test_referral_code_earns_fee_share() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let integrator = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 119u64;
+    let code = symbol_short!("ACME10");
+
+    client.initialize(&treasury, &Some(100));
+    client.set_referral_share(&2000);
+    client.register_referral_code(&code, &integrator);
+    assert_eq!(env.auths()[0].0, integrator);
+    assert_eq!(
+        client.try_register_referral_code(&code, &Address::generate(&env)),
+        Err(Ok(Error::ReferralCodeTaken))
+    );
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &10_000);
+    token_client.approve(&depositor, &contract_id, &10_000, &200);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 10_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Phase1"),
+        },
+    ];
+    let mut options = EscrowOptions::new(EscrowKind::Standard);
+    options.referral = Referral::Code(symbol_short!("NOPE"));
+    assert_eq!(
+        client
+            .try_create_escrow(
+                &escrow_id,
+                &depositor,
+                &recipient,
+                &token_address,
+                &milestones,
+                &1706400000u64,
+                &None,
+                &None,
+                &10_000,
+                &options,
+            )
+            .err(),
+        Some(Ok(Error::ReferralCodeNotFound))
+    );
+
+    options.referral = Referral::Code(code.clone());
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+        &None,
+        &None,
+        &10_000,
+        &options,
+    );
+    assert_eq!(
+        client.get_referral_code(&code),
+        ReferralCode {
+            referrer: integrator.clone(),
+            uses: 1,
+        }
+    );
+
+    // 100 fee: 20% to the referrer, the rest to the treasury
+    client.deposit_funds(&escrow_id);
+    client.release_milestone(&escrow_id, &0);
+    assert_eq!(token_client.balance(&recipient), 9900);
+    assert_eq!(token_client.balance(&integrator), 20);
+    assert_eq!(token_client.balance(&treasury), 80);
+}
+
+#[test]
+fn test_recipient_change_restrictions() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let grantee = Address::generate(&env);
+    let replacement = Address::generate(&env);
+    let program_admin = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    client.initialize(&treasury, &Some(0));
+    client.init(&admin);
+    client.set_chargeback_window(&(24 * 60 * 60));
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &1000);
+    token_client.approve(&depositor, &contract_id, &1000, &200);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 200,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Alpha"),
+        },
+        Milestone {
+            amount: 300,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Beta"),
+        },
+    ];
+    let mut locked = repeat_deal_options(EscrowKind::Grant);
+    locked.lock_recipient = true;
+    for (escrow_id, options) in [
+        (124u64, locked),
+        (125u64, repeat_deal_options(EscrowKind::Grant)),
+    ] {
+        client.create_escrow(
+            &escrow_id,
+            &depositor,
+            &grantee,
+            &token_address,
+            &milestones,
+            &1706400000u64,
+            &None,
+            &None,
+            &500,
+            &options,
+        );
+        client.set_program_admin(&escrow_id, &program_admin);
+        client.deposit_funds(&escrow_id);
+    }
+
+    // Locked at creation: not even the depositor can swap the grantee
+    assert_eq!(
+        client.try_reassign_recipient(&124, &replacement),
+        Err(Ok(Error::RecipientLocked))
+    );
+
+    // A confirmed payout waiting out the chargeback window pins the payee
+    client.confirm_delivery(&125, &0, &depositor);
+    assert_eq!(
+        client.try_reassign_recipient(&125, &replacement),
+        Err(Ok(Error::InvalidEscrowStatus))
+    );
+
+    env.ledger().with_mut(|li| li.timestamp += 24 * 60 * 60);
+    client.claim_held_release(&125, &0);
+    assert_eq!(token_client.balance(&grantee), 200);
+
+    client.reassign_recipient(&125, &replacement);
+    assert_eq!(env.auths()[0].0, program_admin);
+    assert_eq!(env.auths()[1].0, depositor);
+    assert_eq!(client.get_escrow(&125).recipient, replacement);
+}
+
+#[test]
+fn test_auth_mode_controls_depositor_actions() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let operator = Address::generate(&env);
+    let co_signer_a = Address::generate(&env);
+    let co_signer_b = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    client.initialize(&treasury, &Some(0));
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &1000);
+    token_client.approve(&depositor, &contract_id, &1000, &200);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 500,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Build"),
+        },
+    ];
+    let create = |escrow_id: u64, auth_mode: AuthMode| {
+        let mut options = repeat_deal_options(EscrowKind::Standard);
+        options.auth_mode = auth_mode;
+        client.try_create_escrow(
+            &escrow_id,
+            &depositor,
+            &recipient,
+            &token_address,
+            &milestones,
+            &1706400000u64,
+            &None,
+            &None,
+            &500,
+            &options,
+        )
+    };
+
+    assert_eq!(
+        create(126, AuthMode::Delegated(depositor.clone())),
+        Err(Ok(Error::InvalidAuthMode))
+    );
+    assert_eq!(
+        create(126, AuthMode::Multisig(Vec::new(&env))),
+        Err(Ok(Error::InvalidAuthMode))
+    );
+
+    // Delegated: the operator signs releases instead of the depositor
+    create(126, AuthMode::Delegated(operator.clone()))
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        client.get_escrow(&126).auth_mode,
+        AuthMode::Delegated(operator.clone())
+    );
+    client.deposit_funds(&126);
+    assert_eq!(env.auths()[0].0, operator);
+    client.release_milestone(&126, &0);
+    assert_eq!(env.auths().len(), 1);
+    assert_eq!(env.auths()[0].0, operator);
+
+    // Multisig: the depositor and every co-signer sign
+    let co_signers = vec![&env, co_signer_a.clone(), co_signer_b.clone()];
+    create(127, AuthMode::Multisig(co_signers))
+        .unwrap()
+        .unwrap();
+    client.deposit_funds(&127);
+    client.release_milestone(&127, &0);
+    assert_eq!(env.auths().len(), 3);
+    assert_eq!(env.auths()[0].0, depositor);
+    assert_eq!(env.auths()[1].0, co_signer_a);
+    assert_eq!(env.auths()[2].0, co_signer_b);
+    assert_eq!(token_client.balance(&recipient), 1000);
+}
+
+#[test]
+fn test_tvl_caps_limit_new_escrows() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    client.initialize(&treasury, &Some(0));
+    client.init(&admin);
+    let (_, _, usdc) = create_token_contract(&env, &admin);
+    let (_, _, eurc) = create_token_contract(&env, &admin);
+
+    client.set_tvl_cap(&None, &1500);
+    client.set_tvl_cap(&Some(usdc.clone()), &1000);
+    assert_eq!(env.auths()[0].0, admin);
+    assert_eq!(client.get_tvl_cap(&None), 1500);
+    assert_eq!(client.get_tvl_cap(&Some(usdc.clone())), 1000);
+    assert_eq!(client.get_tvl_cap(&Some(eurc.clone())), 0);
+
+    let create = |escrow_id: u64, token: &Address, amount: i128| {
+        let milestones = vec![
+            &env,
+            Milestone {
+                amount,
+                status: MilestoneStatus::Pending,
+                description: symbol_short!("Work"),
+            },
+        ];
+        client.try_create_escrow(
+            &escrow_id,
+            &depositor,
+            &recipient,
+            token,
+            &milestones,
+            &1706400000u64,
+            &None,
+            &None,
+            &amount,
+            &EscrowOptions::new(EscrowKind::Standard),
+        )
+    };
+
+    create(128, &usdc, 600).unwrap().unwrap();
+    create(129, &usdc, 400).unwrap().unwrap();
+    assert_eq!(create(130, &usdc, 1), Err(Ok(Error::TvlCapExceeded)));
+    assert_eq!(client.get_total_value_locked(&Some(usdc.clone())), 1000);
+
+    // Other tokens still count towards the global cap
+    create(130, &eurc, 500).unwrap().unwrap();
+    assert_eq!(create(131, &eurc, 1), Err(Ok(Error::TvlCapExceeded)));
+    assert_eq!(client.get_total_value_locked(&None), 1500);
+
+    // Closing an escrow frees its share of the cap
+    client.cancel_escrow(&128);
+    assert_eq!(client.get_total_value_locked(&Some(usdc.clone())), 400);
+    assert_eq!(client.get_total_value_locked(&None), 900);
+    create(131, &usdc, 600).unwrap().unwrap();
+
+    client.set_tvl_cap(&None, &0);
+    create(132, &eurc, 5000).unwrap().unwrap();
+    assert_eq!(client.get_total_value_locked(&None), 6500);
+}
+
+#[test]
+fn test_tranched_release_pays_out_over_time() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let day = 24 * 60 * 60;
+
+    client.initialize(&treasury, &Some(0));
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &1001);
+    token_client.approve(&depositor, &contract_id, &1001, &200);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 1001,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Delivery"),
+        },
+    ];
+    let create = |schedule: ReleaseSchedule| {
+        let mut options = EscrowOptions::new(EscrowKind::Standard);
+        options.release_schedule = schedule;
+        client.try_create_escrow(
+            &133,
+            &depositor,
+            &recipient,
+            &token_address,
+            &milestones,
+            &1706400000u64,
+            &None,
+            &None,
+            &1001,
+            &options,
+        )
+    };
+    assert_eq!(
+        create(ReleaseSchedule::Tranches(0, day)),
+        Err(Ok(Error::InvalidTrancheSchedule))
+    );
+    assert_eq!(
+        create(ReleaseSchedule::Tranches(4, 0)),
+        Err(Ok(Error::InvalidTrancheSchedule))
+    );
+    create(ReleaseSchedule::Tranches(4, day)).unwrap().unwrap();
+    client.deposit_funds(&133);
+
+    // The first tranche pays out with the release itself
+    client.release_milestone(&133, &0);
+    assert_eq!(token_client.balance(&recipient), 250);
+    assert_eq!(
+        client.get_escrow(&133).milestones.get(0).unwrap().status,
+        MilestoneStatus::Released
+    );
+    assert_eq!(
+        client.try_claim_tranches(&133, &0),
+        Err(Ok(Error::TrancheNotDue))
+    );
+
+    // Two intervals later two more tranches are due at once
+    env.ledger().with_mut(|li| li.timestamp += 2 * day);
+    assert_eq!(client.claim_tranches(&133, &0), 500);
+    let pending = client.get_tranche_release(&133, &0).unwrap();
+    assert_eq!(pending.tranches_left, 1);
+    assert_eq!(pending.remaining, 251);
+
+    // The escrow can close, but not be archived, before the last tranche
+    client.complete_escrow(&133);
+    env.ledger().with_mut(|li| li.timestamp += 40 * day);
+    assert_eq!(
+        client.try_archive_escrow(&133, &depositor),
+        Err(Ok(Error::InvalidEscrowStatus))
+    );
+
+    // The last tranche absorbs the rounding remainder
+    assert_eq!(client.claim_tranches(&133, &0), 251);
+    assert_eq!(token_client.balance(&recipient), 1001);
+    assert_eq!(client.get_tranche_release(&133, &0), None);
+    assert_eq!(
+        client.try_claim_tranches(&133, &0),
+        Err(Ok(Error::InvalidEscrowStatus))
+    );
+    client.archive_escrow(&133, &depositor);
+}
+
+#[test]
+fn test_tranche_claim_to_frozen_payee_waits_unclaimed() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+    let token_address = env.register_contract(None, FreezableToken);
+    let token = FreezableTokenClient::new(&env, &token_address);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let day = 24 * 60 * 60;
+
+    client.initialize(&treasury, &Some(0));
+    token.mint(&depositor, &1000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 1000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Delivery"),
+        },
+    ];
+    let mut options = EscrowOptions::new(EscrowKind::Standard);
+    options.release_schedule = ReleaseSchedule::Tranches(2, day);
+    client.create_escrow(
+        &180,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+        &None,
+        &None,
+        &1000,
+        &options,
+    );
+    client.deposit_funds(&180);
+    client.release_milestone(&180, &0);
+    assert_eq!(token.balance(&recipient), 500);
+
+    // The issuer freezes the recipient; the claim still consumes the tranche
+    token.set_frozen(&recipient, &true);
+    env.ledger().with_mut(|li| li.timestamp += day);
+    assert_eq!(client.claim_tranches(&180, &0), 500);
+    assert_eq!(client.get_tranche_release(&180, &0), None);
+    assert_eq!(token.balance(&recipient), 500);
+    assert_eq!(
+        client
+            .get_unclaimed_payout(&180, &recipient)
+            .unwrap()
+            .amount,
+        500
+    );
+
+    token.set_frozen(&recipient, &false);
+    assert_eq!(client.claim_unclaimed_payout(&180, &recipient), 500);
+    assert_eq!(token.balance(&recipient), 1000);
+}
+
+#[contract]
+pub struct MockPriceOracle;
+
+#[contractimpl]
+impl MockPriceOracle {
+    pub fn set_price(env: Env, price: i128) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("price"), &price);
+    }
+
+    pub fn price(env: Env, _token: Address) -> i128 {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("price"))
+            .unwrap()
+    }
+}
+
+#[test]
+fn test_depeg_guard_pauses_stablecoin_releases() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+    let oracle_id = env.register_contract(None, MockPriceOracle);
+    let oracle = MockPriceOracleClient::new(&env, &oracle_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 134u64;
+
+    client.initialize(&treasury, &Some(0));
+    client.init(&admin);
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &900);
+    token_client.approve(&depositor, &contract_id, &900, &200);
+
+    // $1.00 peg with seven decimals, paused beyond a 2% deviation
+    let guard = StablecoinGuard {
+        oracle: oracle_id.clone(),
+        peg: 10_000_000,
+        band_bps: 200,
+    };
+    client.set_stablecoin_guard(&token_address, &Some(guard.clone()));
+    assert_eq!(env.auths()[0].0, admin);
+    assert_eq!(client.get_stablecoin_guard(&token_address), Some(guard));
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 300,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("One"),
+        },
+        Milestone {
+            amount: 300,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Two"),
+        },
+        Milestone {
+            amount: 300,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Three"),
+        },
+    ];
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+        &None,
+        &None,
+        &900,
+        &EscrowOptions::new(EscrowKind::Standard),
+    );
+    client.deposit_funds(&escrow_id);
+
+    oracle.set_price(&9_850_000);
+    client.release_milestone(&escrow_id, &0);
+    assert_eq!(token_client.balance(&recipient), 300);
+
+    oracle.set_price(&9_500_000);
+    assert_eq!(
+        client.try_release_milestone(&escrow_id, &1),
+        Err(Ok(Error::DepegDetected))
+    );
+
+    // Both parties accept settling off peg for this one milestone
+    client.override_depeg_guard(&escrow_id, &1);
+    assert_eq!(env.auths()[0].0, depositor);
+    assert_eq!(env.auths()[1].0, recipient);
+    client.release_milestone(&escrow_id, &1);
+    assert_eq!(token_client.balance(&recipient), 600);
+    assert_eq!(
+        client.try_release_milestone(&escrow_id, &2),
+        Err(Ok(Error::DepegDetected))
+    );
+
+    client.set_stablecoin_guard(&token_address, &None);
+    client.release_milestone(&escrow_id, &2);
+    assert_eq!(token_client.balance(&recipient), 900);
+}
+
+#[contract]
+pub struct MockTokenConverter;
+
+#[contractimpl]
+impl MockTokenConverter {
+    pub fn set_rate(env: Env, rate: i128) {
+        env.storage().instance().set(&symbol_short!("rate"), &rate);
+    }
+
+    pub fn convert(
+        env: Env,
+        _from: Address,
+        to: Address,
+        amount: i128,
+        recipient: Address,
+    ) -> i128 {
+        let rate: i128 = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("rate"))
+            .unwrap();
+        let out = amount * rate / migration::RATE_SCALE;
+        token::Client::new(&env, &to).transfer(&env.current_contract_address(), &recipient, &out);
+        out
+    }
+}
+
+#[test]
+fn test_migrate_escrow_token() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+    let converter_id = env.register_contract(None, MockTokenConverter);
+    let converter = MockTokenConverterClient::new(&env, &converter_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 135u64;
+
+    client.initialize(&treasury, &Some(0));
+    client.init(&admin);
+    let (old_client, old_admin, old_token) = create_token_contract(&env, &admin);
+    let (new_client, new_admin, new_token) = create_token_contract(&env, &admin);
+    old_admin.mint(&depositor, &900);
+    new_admin.mint(&converter_id, &10_000);
+    old_client.approve(&depositor, &contract_id, &900, &200);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 300,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("One"),
+        },
+        Milestone {
+            amount: 300,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Two"),
+        },
+        Milestone {
+            amount: 300,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Three"),
+        },
+    ];
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &old_token,
+        &milestones,
+        &1706400000u64,
+        &None,
+        &None,
+        &900,
+        &EscrowOptions::new(EscrowKind::Standard),
+    );
+    client.deposit_funds(&escrow_id);
+    client.release_milestone(&escrow_id, &0);
+
+    // The issuer redenominates at two new units per old unit
+    let rate = 2 * migration::RATE_SCALE;
+    assert_eq!(
+        client.try_migrate_escrow_token(&escrow_id, &old_token, &new_token, &rate),
+        Err(Ok(Error::ConverterNotAllowed))
+    );
+    client.set_token_converter(&old_token, &new_token, &Some(converter_id.clone()));
+    assert_eq!(
+        client.get_token_converter(&old_token, &new_token),
+        Some(converter_id.clone())
+    );
+    assert_eq!(
+        client.try_migrate_escrow_token(&escrow_id, &new_token, &old_token, &rate),
+        Err(Ok(Error::TokenMismatch))
+    );
+
+    converter.set_rate(&(19 * migration::RATE_SCALE / 10));
+    assert_eq!(
+        client.try_migrate_escrow_token(&escrow_id, &old_token, &new_token, &rate),
+        Err(Ok(Error::InsufficientBalance))
+    );
+
+    // A converter paying slightly over leaves a surplus for the depositor
+    converter.set_rate(&(205 * migration::RATE_SCALE / 100));
+    assert_eq!(
+        client.migrate_escrow_token(&escrow_id, &old_token, &new_token, &rate),
+        1230
+    );
+    assert_eq!(env.auths()[0].0, admin);
+    assert_eq!(env.auths()[1].0, depositor);
+    assert_eq!(env.auths()[2].0, recipient);
+    assert_eq!(new_client.balance(&depositor), 30);
+    assert_eq!(new_client.balance(&contract_id), 1200);
+    assert_eq!(old_client.balance(&contract_id), 0);
+
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.token_address, new_token);
+    assert_eq!(escrow.total_amount, 1800);
+    assert_eq!(escrow.total_released, 600);
+    assert_eq!(escrow.milestones.get(1).unwrap().amount, 600);
+    assert_eq!(
+        client.get_total_value_locked(&Some(new_token.clone())),
+        1800
+    );
+    assert_eq!(client.get_total_value_locked(&Some(old_token.clone())), 0);
+
+    client.release_milestone(&escrow_id, &1);
+    client.release_milestone(&escrow_id, &2);
+    assert_eq!(new_client.balance(&recipient), 1200);
+    assert_eq!(old_client.balance(&recipient), 300);
+    client.complete_escrow(&escrow_id);
+}
+
+#[cfg(feature = "disputes")]
+#[test]
+fn test_dispute_policy_freezes_or_continues() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    client.initialize(&treasury, &Some(0));
+    client.init(&admin);
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &1200);
+    token_client.approve(&depositor, &contract_id, &1200, &200);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 400,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Design"),
+        },
+        Milestone {
+            amount: 200,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Build"),
+        },
+    ];
+    for (escrow_id, policy) in [
+        (136u64, DisputePolicy::FreezeAll),
+        (137u64, DisputePolicy::ContinueOthers),
+    ] {
+        let mut options = repeat_deal_options(EscrowKind::Standard);
+        options.dispute_policy = policy;
+        client.create_escrow(
+            &escrow_id,
+            &depositor,
+            &recipient,
+            &token_address,
+            &milestones,
+            &1706400000u64,
+            &None,
+            &None,
+            &600,
+            &options,
+        );
+        client.deposit_funds(&escrow_id);
+        assert_eq!(client.get_dispute_policy(&escrow_id), policy);
+        client.open_partial_dispute(&escrow_id, &0, &depositor, &100);
+
+        // A disputed slice blocks cancelling under either policy
+        assert_eq!(
+            client.try_cancel_escrow(&escrow_id),
+            Err(Ok(Error::AlreadyInDispute))
+        );
+    }
+    assert_eq!(
+        client.try_get_dispute_policy(&999),
+        Err(Ok(Error::EscrowNotFound))
+    );
+
+    // Frozen: nothing pays out until the dispute is resolved
+    assert_eq!(
+        client.try_release_milestone(&136, &1),
+        Err(Ok(Error::AlreadyInDispute))
+    );
+    assert_eq!(
+        client.try_release_milestone(&136, &0),
+        Err(Ok(Error::AlreadyInDispute))
+    );
+    client.resolve_partial_dispute(&136, &0, &depositor);
+    client.release_milestone(&136, &0);
+    client.release_milestone(&136, &1);
+    client.complete_escrow(&136);
+
+    // Continue: other milestones release while the slice is contested
+    client.release_milestone(&137, &1);
+    client.release_milestone(&137, &0);
+    assert_eq!(
+        client.get_escrow(&137).milestones.get(0).unwrap().status,
+        MilestoneStatus::Disputed
+    );
+    assert_eq!(
+        client.try_complete_escrow(&137),
+        Err(Ok(Error::AlreadyInDispute))
+    );
+    client.resolve_partial_dispute(&137, &0, &recipient);
+    client.complete_escrow(&137);
+    assert_eq!(token_client.balance(&recipient), 1100);
+    assert_eq!(token_client.balance(&depositor), 100);
+}
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FreezableTokenError {
+    AccountFrozen = 1,
+}
+
+/// Token whose issuer can freeze holders, standing in for an asset whose
+/// issuer deauthorizes an account
+#[contract]
+pub struct FreezableToken;
+
+#[contractimpl]
+impl FreezableToken {
+    pub fn mint(env: Env, to: Address, amount: i128) {
+        let balance = Self::balance(env.clone(), to.clone());
+        env.storage().instance().set(&to, &(balance + amount));
+    }
+
+    pub fn set_frozen(env: Env, id: Address, frozen: bool) {
+        env.storage()
+            .instance()
+            .set(&(symbol_short!("frozen"), id), &frozen);
+    }
+
+    pub fn balance(env: Env, id: Address) -> i128 {
+        env.storage().instance().get(&id).unwrap_or(0)
+    }
+
+    pub fn transfer(
+        env: Env,
+        from: Address,
+        to: Address,
+        amount: i128,
+    ) -> Result<(), FreezableTokenError> {
+        from.require_auth();
+        let frozen: bool = env
+            .storage()
+            .instance()
+            .get(&(symbol_short!("frozen"), to.clone()))
+            .unwrap_or(false);
+        if frozen {
+            return Err(FreezableTokenError::AccountFrozen);
+        }
+        let from_balance = Self::balance(env.clone(), from.clone());
+        env.storage()
+            .instance()
+            .set(&from, &(from_balance - amount));
+        Self::mint(env, to, amount);
+        Ok(())
+    }
+
+    pub fn transfer_from(env: Env, spender: Address, from: Address, to: Address, amount: i128) {
+        spender.require_auth();
+        let from_balance = Self::balance(env.clone(), from.clone());
+        env.storage()
+            .instance()
+            .set(&from, &(from_balance - amount));
+        Self::mint(env, to, amount);
+    }
+}
+
+#[test]
+fn test_undeliverable_payouts_are_claimable_then_escheat() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+    let token_address = env.register_contract(None, FreezableToken);
+    let token = FreezableTokenClient::new(&env, &token_address);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+
+    client.initialize(&treasury, &Some(0));
+    token.mint(&depositor, &1200);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 300,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("One"),
+        },
+        Milestone {
+            amount: 300,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Two"),
+        },
+    ];
+    for escrow_id in [138u64, 139] {
+        client.create_escrow(
+            &escrow_id,
+            &depositor,
+            &recipient,
+            &token_address,
+            &milestones,
+            &1706400000u64,
+            &None,
+            &None,
+            &600,
+            &repeat_deal_options(EscrowKind::Standard),
+        );
+        client.deposit_funds(&escrow_id);
+    }
+
+    // The issuer freezes the recipient; releases still go through
+    token.set_frozen(&recipient, &true);
+    client.release_milestone(&138, &0);
+    client.release_milestone(&138, &1);
+    assert_eq!(token.balance(&recipient), 0);
+    let unclaimed = client.get_unclaimed_payout(&138, &recipient).unwrap();
+    assert_eq!(unclaimed.payee, recipient);
+    assert_eq!(unclaimed.amount, 600);
+    client.complete_escrow(&138);
+
+    assert_eq!(
+        client.try_escheat_unclaimed_payout(&138, &recipient),
+        Err(Ok(Error::RetentionNotElapsed))
+    );
+
+    token.set_frozen(&recipient, &false);
+    assert_eq!(client.claim_unclaimed_payout(&138, &recipient), 600);
+    assert_eq!(token.balance(&recipient), 600);
+    assert_eq!(client.get_unclaimed_payout(&138, &recipient), None);
+
+    // Left unclaimed past the escheat period, it reverts to the depositor
+    token.set_frozen(&recipient, &true);
+    client.release_milestone(&139, &0);
+    env.ledger()
+        .with_mut(|li| li.timestamp += unclaimed::ESCHEAT_PERIOD_SECS);
+    assert_eq!(client.escheat_unclaimed_payout(&139, &recipient), 300);
+    assert_eq!(env.auths()[0].0, depositor);
+    assert_eq!(token.balance(&depositor), 300);
+    assert_eq!(
+        client.try_claim_unclaimed_payout(&139, &recipient),
+        Err(Ok(Error::InvalidEscrowStatus))
+    );
+}
+
+#[cfg(feature = "views-extended")]
+#[test]
+fn test_escrow_accounting_breaks_down_funds() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let window = 24 * 60 * 60;
+
+    client.initialize(&treasury, &Some(100));
+    client.init(&admin);
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &5000);
+    token_client.approve(&depositor, &contract_id, &5000, &200);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 1000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("One"),
+        },
+        Milestone {
+            amount: 1000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Two"),
+        },
+    ];
+    for escrow_id in [140u64, 141] {
+        client.create_escrow(
+            &escrow_id,
+            &depositor,
+            &recipient,
+            &token_address,
+            &milestones,
+            &1706400000u64,
+            &None,
+            &None,
+            &2000,
+            &repeat_deal_options(EscrowKind::Standard),
+        );
+        client.deposit_funds(&escrow_id);
+    }
+
+    client.release_milestone(&140, &0);
+    client.set_chargeback_window(&window);
+    client.confirm_delivery(&140, &1, &depositor);
+    assert_eq!(
+        client.get_escrow_accounting(&140),
+        EscrowAccounting {
+            deposited: 2000,
+            released_to_recipient: 990,
+            fees_paid: 10,
+            refunded_to_depositor: 0,
+            claimable_outstanding: 1000,
+        }
+    );
+
+    env.ledger().with_mut(|li| li.timestamp += window);
+    client.claim_held_release(&140, &1);
+    let accounting = client.get_escrow_accounting(&140);
+    assert_eq!(accounting.released_to_recipient, 1990);
+    assert_eq!(accounting.claimable_outstanding, 0);
+    assert_eq!(token_client.balance(&recipient), 1990);
+
+    client.cancel_escrow(&141);
+    assert_eq!(
+        client.get_escrow_accounting(&141),
+        EscrowAccounting {
+            deposited: 2000,
+            released_to_recipient: 0,
+            fees_paid: 0,
+            refunded_to_depositor: 2000,
+            claimable_outstanding: 0,
+        }
+    );
+}
+
+#[test]
+fn test_min_milestone_units_rejects_dust() {
+    let env = feature_dependent_env();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    client.initialize(&treasury, &None);
+    client.init(&admin);
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    // Stellar asset contracts use 7 decimals
+    let unit = 10_000_000i128;
+    token_admin.mint(&depositor, &(10 * unit));
+    token_client.approve(&depositor, &contract_id, &(10 * unit), &200);
+
+    assert_eq!(client.get_min_milestone_units(), 0);
+    client.set_min_milestone_units(&2);
+    assert_eq!(client.get_min_milestone_units(), 2);
+
+    let milestone = |amount: i128, description: Symbol| Milestone {
+        amount,
+        status: MilestoneStatus::Pending,
+        description,
+    };
+    let dusty = vec![
+        &env,
+        milestone(2 * unit, symbol_short!("One")),
+        milestone(2 * unit - 1, symbol_short!("Two")),
+    ];
+    let result = client.try_create_escrow(
+        &142,
+        &depositor,
+        &recipient,
+        &token_address,
+        &dusty,
+        &1706400000u64,
+        &None,
+        &None,
+        &(5 * unit + 2_500_000),
+        &EscrowOptions::new(EscrowKind::Standard),
+    );
+    assert_eq!(result, Err(Ok(Error::InvalidMilestoneAmount)));
+
+    let milestones = vec![
+        &env,
+        milestone(2 * unit, symbol_short!("One")),
+        milestone(3 * unit + 2_500_000, symbol_short!("Two")),
+    ];
+    client.create_escrow(
+        &142,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+        &None,
+        &None,
+        &(5 * unit + 2_500_000),
+        &EscrowOptions::new(EscrowKind::Standard),
+    );
+    client.deposit_funds(&142);
+    client.release_milestone(&142, &0);
+
+    #[cfg(feature = "views-extended")]
+    assert_eq!(
+        client.get_escrow_summary(&142),
+        EscrowSummary {
+            status: EscrowStatus::Active,
+            token_address: token_address.clone(),
+            decimals: 7,
+            total: DisplayAmount {
+                units: 5,
+                fraction: 2_500_000,
+            },
+            released: DisplayAmount {
+                units: 2,
+                fraction: 0,
+            },
+            remaining: DisplayAmount {
+                units: 3,
+                fraction: 2_500_000,
+            },
+        }
+    );
+
+    client.set_min_milestone_units(&0);
+    let result = client.try_set_min_milestone_units(&-1);
+    assert_eq!(result, Err(Ok(Error::InvalidMilestoneAmount)));
+}
+
+#[cfg(feature = "disputes")]
+#[test]
+fn test_dispute_award_to_frozen_recipient_is_diverted() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+    let token_address = env.register_contract(None, FreezableToken);
+    let token = FreezableTokenClient::new(&env, &token_address);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    client.initialize(&treasury, &Some(0));
+    client.init(&admin);
+    token.mint(&depositor, &600);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 600,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Audit"),
+        },
+    ];
+    client.create_escrow(
+        &143,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+        &None,
+        &None,
+        &600,
+        &EscrowOptions::new(EscrowKind::Standard),
+    );
+    client.deposit_funds(&143);
+
+    // A ruling settles like any release: a refused payee does not block it
+    token.set_frozen(&recipient, &true);
+    client.raise_dispute(&143, &depositor);
+    client.resolve_dispute(&143, &recipient);
+    let escrow = client.get_escrow(&143);
+    assert_eq!(escrow.status, EscrowStatus::Resolved);
+    assert_eq!(escrow.total_released, 600);
+    assert_eq!(
+        client
+            .get_unclaimed_payout(&143, &recipient)
+            .unwrap()
+            .amount,
+        600
+    );
+
+    token.set_frozen(&recipient, &false);
+    assert_eq!(client.claim_unclaimed_payout(&143, &recipient), 600);
+    assert_eq!(token.balance(&recipient), 600);
+}
+
+#[test]
+fn test_unclaimed_payouts_are_held_per_payee() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+    let token_address = env.register_contract(None, FreezableToken);
+    let token = FreezableTokenClient::new(&env, &token_address);
+
+    let seller = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let deadline = 1706400000u64;
+
+    client.initialize(&treasury, &Some(0));
+    token.mint(&seller, &1000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 600,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Part1"),
+        },
+        Milestone {
+            amount: 400,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Part2"),
+        },
+    ];
+    client.create_escrow(
+        &181,
+        &seller,
+        &buyer,
+        &token_address,
+        &milestones,
+        &deadline,
+        &None,
+        &None,
+        &1000,
+        &EscrowOptions::new(EscrowKind::RefundGuarantee),
+    );
+    client.deposit_funds(&181);
+
+    // The release to the seller and the lapsed guarantee's award to the
+    // buyer are both refused, and held apart
+    token.set_frozen(&seller, &true);
+    token.set_frozen(&buyer, &true);
+    client.release_milestone(&181, &0);
+    let released_at = env.ledger().timestamp();
+    env.ledger().with_mut(|li| li.timestamp = deadline + 1);
+    client.claim_guarantee(&181);
+
+    let to_seller = client.get_unclaimed_payout(&181, &seller).unwrap();
+    assert_eq!((to_seller.amount, to_seller.since), (600, released_at));
+    let to_buyer = client.get_unclaimed_payout(&181, &buyer).unwrap();
+    assert_eq!((to_buyer.amount, to_buyer.since), (400, deadline + 1));
+
+    token.set_frozen(&seller, &false);
+    assert_eq!(client.claim_unclaimed_payout(&181, &seller), 600);
+    assert_eq!(token.balance(&seller), 600);
+    assert_eq!(
+        client.get_unclaimed_payout(&181, &buyer).unwrap().amount,
+        400
+    );
+    assert_eq!(
+        client.try_claim_unclaimed_payout(&181, &seller),
+        Err(Ok(Error::InvalidEscrowStatus))
+    );
+}
+
+#[cfg(feature = "views-extended")]
+#[test]
+fn test_project_settlement_previews_release_and_refund() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    client.initialize(&treasury, &Some(100));
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &4000);
+    token_client.approve(&depositor, &contract_id, &4000, &200);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 1000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Design"),
+        },
+        Milestone {
+            amount: 3000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Build"),
+        },
+    ];
+    let mut options = EscrowOptions::new(EscrowKind::Standard);
+    options.cliff_until = Some(env.ledger().timestamp() + 1000);
+    options.release_schedule = ReleaseSchedule::Tranches(3, 60);
+    client.create_escrow(
+        &144,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+        &None,
+        &None,
+        &4000,
+        &options,
+    );
+
+    // Nothing is deposited yet, so nothing can move
+    let unfunded = client.project_settlement(&144);
+    assert!(unfunded
+        .iter()
+        .all(|p| !p.releasable && p.release_amount == 0 && p.refund_amount == 0));
+
+    client.deposit_funds(&144);
+    let projections = client.project_settlement(&144);
+    // The cliff holds back the first milestone; a refund would still return it
+    assert_eq!(
+        projections.get(0).unwrap(),
+        SettlementProjection {
+            milestone_index: 0,
+            status: MilestoneStatus::Pending,
+            releasable: false,
+            release_amount: 0,
+            release_fee: 0,
+            release_payout: 0,
+            release_paid_now: 0,
+            refund_amount: 1000,
+        }
+    );
+    let build = SettlementProjection {
+        milestone_index: 1,
+        status: MilestoneStatus::Pending,
+        releasable: true,
+        release_amount: 3000,
+        release_fee: 30,
+        release_payout: 2970,
+        release_paid_now: 990,
+        refund_amount: 3000,
+    };
+    assert_eq!(projections.get(1).unwrap(), build);
+
+    // The projection matches what the release actually moves
+    client.release_milestone(&144, &1);
+    assert_eq!(token_client.balance(&recipient), build.release_paid_now);
+    assert_eq!(token_client.balance(&treasury), build.release_fee);
+    let released = client.project_settlement(&144).get(1).unwrap();
+    assert_eq!(released.status, MilestoneStatus::Released);
+    assert!(!released.releasable);
+    assert_eq!(released.refund_amount, 0);
+}
+
+#[test]
+fn test_duplicate_deal_rejected_while_open() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    client.initialize(&treasury, &None);
+    client.init(&admin);
+    let (_, _, token_address) = create_token_contract(&env, &admin);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 1000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Task"),
+        },
+    ];
+    let create = |escrow_id: u64, options: EscrowOptions| {
+        client.try_create_escrow(
+            &escrow_id,
+            &depositor,
+            &recipient,
+            &token_address,
+            &milestones,
+            &1706400000u64,
+            &None,
+            &None,
+            &1000,
+            &options,
+        )
+    };
+
+    assert!(create(145, EscrowOptions::new(EscrowKind::Standard)).is_ok());
+    // A retried creation under a fresh ID is the same deal
+    assert_eq!(
+        create(146, EscrowOptions::new(EscrowKind::Standard)),
+        Err(Ok(Error::EscrowAlreadyExists))
+    );
+    assert!(create(146, repeat_deal_options(EscrowKind::Standard)).is_ok());
+
+    // Closing the deliberate duplicate leaves the original guarded
+    client.cancel_escrow(&146);
+    assert_eq!(
+        create(147, EscrowOptions::new(EscrowKind::Standard)),
+        Err(Ok(Error::EscrowAlreadyExists))
+    );
+    client.cancel_escrow(&145);
+    assert!(create(147, EscrowOptions::new(EscrowKind::Standard)).is_ok());
+}
+
+#[test]
+fn test_reshaped_deals_move_their_dedupe_record() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    client.initialize(&treasury, &None);
+    let (_, _, token_address) = create_token_contract(&env, &admin);
+
+    let create = |escrow_id: u64, terms: &[(i128, Symbol)]| {
+        let mut milestones = Vec::new(&env);
+        let mut total = 0;
+        for (amount, description) in terms {
+            milestones.push_back(Milestone {
+                amount: *amount,
+                status: MilestoneStatus::Pending,
+                description: description.clone(),
+            });
+            total += amount;
+        }
+        client.try_create_escrow(
+            &escrow_id,
+            &depositor,
+            &recipient,
+            &token_address,
+            &milestones,
+            &1706400000u64,
+            &None,
+            &None,
+            &total,
+            &EscrowOptions::new(EscrowKind::Standard),
+        )
+    };
+    let design = symbol_short!("Design");
+    let build = symbol_short!("Build");
+
+    // Reallocating frees the old terms and guards the new ones
+    assert!(create(182, &[(600, design.clone()), (400, build.clone())]).is_ok());
+    client.reallocate(&182, &0, &1, &100);
+    assert!(create(183, &[(600, design.clone()), (400, build.clone())]).is_ok());
+    assert_eq!(
+        create(184, &[(500, design.clone()), (500, build.clone())]),
+        Err(Ok(Error::EscrowAlreadyExists))
+    );
+
+    // So does merging
+    assert!(create(185, &[(300, design.clone())]).is_ok());
+    assert!(create(186, &[(200, build.clone())]).is_ok());
+    client.merge_escrows(&vec![&env, 185u64, 186]);
+    assert!(create(187, &[(300, design.clone())]).is_ok());
+    assert!(create(188, &[(200, build.clone())]).is_ok());
+    assert_eq!(
+        create(189, &[(300, design), (200, build)]),
+        Err(Ok(Error::EscrowAlreadyExists))
+    );
+}
+
+#[test]
+fn test_marketplace_creates_escrow_from_allowance() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let marketplace = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    client.initialize(&treasury, &None);
+    client.init(&admin);
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &5000);
+    // The buyer approves the marketplace, not the escrow contract
+    token_client.approve(&depositor, &marketplace, &3000, &200);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 3000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Task"),
+        },
+    ];
+    let create = |escrow_id: u64, kind: EscrowKind| {
+        client.try_create_escrow_from_allowance(
+            &marketplace,
+            &escrow_id,
+            &depositor,
+            &recipient,
+            &token_address,
+            &milestones,
+            &1706400000u64,
+            &3000,
+            &EscrowOptions::new(kind),
+        )
+    };
+
+    // A swap's counter leg must be funded before the escrow can be
+    assert_eq!(
+        create(148, EscrowKind::Swap),
+        Err(Ok(Error::InvalidEscrowKind))
+    );
+
+    create(148, EscrowKind::Standard).unwrap().unwrap();
+    // Only the marketplace signed; the buyer's consent is the allowance
+    assert_eq!(env.auths().len(), 1);
+    assert_eq!(env.auths()[0].0, marketplace);
+
+    assert_eq!(client.get_state(&148), EscrowStatus::Active);
+    assert_eq!(token_client.balance(&depositor), 2000);
+    assert_eq!(token_client.balance(&contract_id), 3000);
+    assert_eq!(token_client.allowance(&depositor, &marketplace), 0);
+
+    // Releases still need the depositor
+    client.release_milestone(&148, &0);
+    assert_eq!(env.auths()[0].0, depositor);
+    assert_eq!(token_client.balance(&recipient), 3000 - 3000 * 50 / 10000);
+}
+
+#[test]
+fn test_party_authorized_records_actual_signers() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let operator = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    client.initialize(&treasury, &None);
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &1000);
+    token_client.approve(&depositor, &contract_id, &1000, &200);
+
+    let mut options = EscrowOptions::new(EscrowKind::Standard);
+    options.auth_mode = AuthMode::Delegated(operator.clone());
+    client.create_escrow(
+        &149,
+        &depositor,
+        &recipient,
+        &token_address,
+        &vec![
+            &env,
+            Milestone {
+                amount: 1000,
+                status: MilestoneStatus::Pending,
+                description: symbol_short!("Task"),
+            },
+        ],
+        &1706400000u64,
+        &None,
+        &None,
+        &1000,
+        &options,
+    );
+
+    // A relayer may submit the deposit; the event names who actually signed
+    client.deposit_funds(&149);
+    let topics: soroban_sdk::Vec<Val> = (
+        Symbol::new(&env, "Vaultix"),
+        Symbol::new(&env, "PartyAuthorized"),
+        149u64,
+    )
+        .into_val(&env);
+    let authorized = env
+        .events()
+        .all()
+        .iter()
+        .find(|event| event.1 == topics)
+        .unwrap();
+    let (party, signers): (Address, soroban_sdk::Vec<Address>) = authorized.2.into_val(&env);
+    assert_eq!(party, depositor);
+    assert_eq!(signers, vec![&env, operator]);
+}
+
+#[test]
+fn test_fee_payer_selects_who_bears_the_fee() {
+    let env = feature_dependent_env();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    // 50 bps: 50 on the first milestone, 25 on the second
+    client.initialize(&treasury, &None);
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &100_000);
+    token_client.approve(&depositor, &contract_id, &100_000, &200);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 10_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("One"),
+        },
+        Milestone {
+            amount: 5000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Two"),
+        },
+    ];
+    let create = |escrow_id: u64, fee_payer: FeePayer| {
+        let mut options = repeat_deal_options(EscrowKind::Standard);
+        options.fee_payer = fee_payer;
+        client.create_escrow(
+            &escrow_id,
+            &depositor,
+            &recipient,
+            &token_address,
+            &milestones,
+            &1706400000u64,
+            &None,
+            &None,
+            &15_000,
+            &options,
+        );
+        client.deposit_funds(&escrow_id);
+    };
+
+    // The depositor's whole fee is paid in on top at funding
+    create(150, FeePayer::Depositor);
+    assert_eq!(
+        client.get_fee_split(&150),
+        FeeSplit {
+            payer: FeePayer::Depositor,
+            depositor_reserve: 75,
+        }
+    );
+    assert_eq!(token_client.balance(&depositor), 100_000 - 15_075);
+    client.release_milestone(&150, &0);
+    assert_eq!(token_client.balance(&recipient), 10_000);
+    assert_eq!(token_client.balance(&treasury), 50);
+    assert_eq!(client.get_fee_split(&150).depositor_reserve, 25);
+
+    // Split: the recipient's half is rounded up
+    create(151, FeePayer::Split);
+    assert_eq!(client.get_fee_split(&151).depositor_reserve, 25 + 12);
+    client.release_milestone(&151, &1);
+    assert_eq!(token_client.balance(&recipient), 10_000 + 5000 - 13);
+    assert_eq!(token_client.balance(&treasury), 50 + 25);
+    assert_eq!(client.get_fee_split(&151).depositor_reserve, 25);
+
+    // Cancelling returns the unspent reserve with the locked funds
+    let before = token_client.balance(&depositor);
+    create(152, FeePayer::Depositor);
+    client.cancel_escrow(&152);
+    assert_eq!(token_client.balance(&depositor), before);
+    assert_eq!(client.get_fee_split(&152).depositor_reserve, 0);
+
+    #[cfg(feature = "views-extended")]
+    assert_eq!(
+        client.get_escrow_accounting(&152),
+        EscrowAccounting {
+            deposited: 15_075,
+            released_to_recipient: 0,
+            fees_paid: 0,
+            refunded_to_depositor: 15_075,
+            claimable_outstanding: 0,
+        }
+    );
+}
+
+#[test]
+fn test_promo_code_discounts_escrow_fee() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    client.initialize(&treasury, &Some(100));
+    let (_, _, token_address) = create_token_contract(&env, &admin);
+
+    let code = symbol_short!("LAUNCH");
+    assert_eq!(client.get_promo(&code), None);
+    assert_eq!(
+        client.try_create_promo(&code, &10_001, &1000, &2),
+        Err(Ok(Error::InvalidFeeConfiguration))
+    );
+    client.create_promo(&code, &2500, &1000, &2);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 1000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Task"),
+        },
+    ];
+    let create = |escrow_id: u64| {
+        let mut options = repeat_deal_options(EscrowKind::Standard);
+        options.promo = Promo::Code(code.clone());
+        client.try_create_escrow(
+            &escrow_id,
+            &depositor,
+            &recipient,
+            &token_address,
+            &milestones,
+            &1706400000u64,
+            &None,
+            &None,
+            &1000,
+            &options,
+        )
+    };
+
+    create(153).unwrap().unwrap();
+    assert_eq!(client.get_escrow_fee(&153), 75);
+    create(154).unwrap().unwrap();
+    assert_eq!(client.get_promo(&code).unwrap().uses, 2);
+    // Used up
+    assert_eq!(create(155), Err(Ok(Error::InvalidFeeConfiguration)));
+
+    // Renewing the terms keeps the count, so only the extra use is left
+    client.create_promo(&code, &2500, &1000, &3);
+    env.ledger().with_mut(|li| li.timestamp = 1000);
+    assert_eq!(create(155), Err(Ok(Error::InvalidFeeConfiguration)));
+}
+
+#[test]
+fn test_acceptance_criteria_track_delivery_and_acceptance() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    client.initialize(&treasury, &None);
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &1000);
+    token_client.approve(&depositor, &contract_id, &1000, &200);
+
+    client.create_escrow(
+        &156,
+        &depositor,
+        &recipient,
+        &token_address,
+        &vec![
+            &env,
+            Milestone {
+                amount: 1000,
+                status: MilestoneStatus::Pending,
+                description: symbol_short!("Site"),
+            },
+        ],
+        &1706400000u64,
+        &None,
+        &None,
+        &1000,
+        &EscrowOptions::new(EscrowKind::Standard),
+    );
+
+    let responsive = BytesN::from_array(&env, &[1; 32]);
+    let accessible = BytesN::from_array(&env, &[2; 32]);
+    let mut too_many = Vec::new(&env);
+    for _ in 0..=criteria::MAX_CRITERIA {
+        too_many.push_back(responsive.clone());
+    }
+    assert_eq!(
+        client.try_set_acceptance_criteria(&156, &0, &too_many),
+        Err(Ok(Error::VectorTooLarge))
+    );
+    client.set_acceptance_criteria(
+        &156,
+        &0,
+        &vec![&env, responsive.clone(), accessible.clone()],
+    );
+    assert_eq!(env.auths()[0].0, depositor);
+
+    client.deposit_funds(&156);
+    assert_eq!(
+        client.try_set_acceptance_criteria(&156, &0, &vec![&env, responsive.clone()]),
+        Err(Ok(Error::InvalidEscrowStatus))
+    );
+
+    client.mark_criterion_delivered(&156, &0, &0);
+    assert_eq!(env.auths()[0].0, recipient);
+    // Only delivered criteria can be accepted
+    assert_eq!(
+        client.try_accept_criteria(&156, &0, &vec![&env, 1u32]),
+        Err(Ok(Error::InvalidEscrowStatus))
+    );
+    client.accept_criteria(&156, &0, &vec![&env, 0u32]);
+
+    assert_eq!(
+        client.get_acceptance_criteria(&156, &0),
+        vec![
+            &env,
+            Criterion {
+                hash: responsive,
+                delivered: true,
+                accepted: true,
+            },
+            Criterion {
+                hash: accessible,
+                delivered: false,
+                accepted: false,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_hourly_escrow_pays_approved_work_logs() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    client.initialize(&treasury, &None);
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &2000);
+    token_client.approve(&depositor, &contract_id, &2000, &200);
+
+    let pool = |amount: i128| {
+        vec![
+            &env,
+            Milestone {
+                amount,
+                status: MilestoneStatus::Pending,
+                description: symbol_short!("Hours"),
+            },
+        ]
+    };
+    // The whole engagement is one pool
+    let mut split = pool(500);
+    split.push_back(Milestone {
+        amount: 500,
+        status: MilestoneStatus::Pending,
+        description: symbol_short!("More"),
+    });
+    assert_eq!(
+        client.try_create_escrow(
+            &157,
+            &depositor,
+            &recipient,
+            &token_address,
+            &split,
+            &1706400000u64,
+            &None,
+            &None,
+            &1000,
+            &EscrowOptions::new(EscrowKind::Hourly),
+        ),
+        Err(Ok(Error::InvalidEscrowKind))
+    );
+
+    client.create_escrow(
+        &157,
+        &depositor,
+        &recipient,
+        &token_address,
+        &pool(1000),
+        &1706400000u64,
+        &None,
+        &None,
+        &1000,
+        &EscrowOptions::new(EscrowKind::Hourly),
+    );
+    client.deposit_funds(&157);
+
+    let week_one = BytesN::from_array(&env, &[1; 32]);
+    assert_eq!(client.log_work(&157, &week_one, &6, &50), 0);
+    assert_eq!(env.auths()[0].0, recipient);
+    // Logged hours can never exceed the funded pool
+    assert_eq!(
+        client.try_log_work(&157, &BytesN::from_array(&env, &[2; 32]), &20, &50),
+        Err(Ok(Error::InsufficientBalance))
+    );
+    assert_eq!(
+        client.try_release_milestone(&157, &0),
+        Err(Ok(Error::InvalidEscrowKind))
+    );
+
+    client.approve_work_log(&157, &0);
+    assert_eq!(env.auths()[0].0, depositor);
+    assert!(client.get_work_log(&157, &0).unwrap().approved);
+    assert_eq!(
+        client.try_approve_work_log(&157, &0),
+        Err(Ok(Error::MilestoneAlreadyReleased))
+    );
+    assert_eq!(
+        token_client.balance(&recipient) + token_client.balance(&treasury),
+        300
+    );
+    assert_eq!(
+        client.get_escrow(&157).milestones.get(0).unwrap().amount,
+        700
+    );
+
+    // Ending the engagement refunds whatever was not paid out
+    client.log_work(&157, &BytesN::from_array(&env, &[3; 32]), &2, &50);
+    client.cancel_escrow(&157);
+    assert_eq!(token_client.balance(&depositor), 1700);
+    assert_eq!(client.get_escrow(&157).status, EscrowStatus::Cancelled);
+}
+
+#[test]
+fn test_reallocate_moves_budget_between_pending_milestones() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    client.initialize(&treasury, &None);
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &1000);
+    token_client.approve(&depositor, &contract_id, &1000, &200);
+
+    client.create_escrow(
+        &158,
+        &depositor,
+        &recipient,
+        &token_address,
+        &vec![
+            &env,
+            Milestone {
+                amount: 300,
+                status: MilestoneStatus::Pending,
+                description: symbol_short!("Design"),
+            },
+            Milestone {
+                amount: 300,
+                status: MilestoneStatus::Pending,
+                description: symbol_short!("Build"),
+            },
+            Milestone {
+                amount: 400,
+                status: MilestoneStatus::Pending,
+                description: symbol_short!("Launch"),
+            },
+        ],
+        &1706400000u64,
+        &None,
+        &None,
+        &1000,
+        &EscrowOptions::new(EscrowKind::Standard),
+    );
+    client.deposit_funds(&158);
+    client.release_milestone(&158, &0);
+
+    client.reallocate(&158, &2, &1, &150);
+    assert_eq!(env.auths()[0].0, depositor);
+    assert_eq!(env.auths()[1].0, recipient);
+
+    let escrow = client.get_escrow(&158);
+    assert_eq!(escrow.milestones.get(1).unwrap().amount, 450);
+    assert_eq!(escrow.milestones.get(2).unwrap().amount, 250);
+    assert_eq!(escrow.total_amount, 1000);
+
+    assert_eq!(
+        client.try_reallocate(&158, &0, &1, &100),
+        Err(Ok(Error::MilestoneAlreadyReleased))
+    );
+    // The source milestone cannot be emptied
+    assert_eq!(
+        client.try_reallocate(&158, &2, &1, &250),
+        Err(Ok(Error::InsufficientBalance))
+    );
+    assert_eq!(
+        client.try_reallocate(&158, &2, &3, &50),
+        Err(Ok(Error::MilestoneNotFound))
+    );
+}
+
+#[test]
+fn test_merge_escrows_consolidates_schedules() {
+    let env = feature_dependent_env();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let other_recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    client.initialize(&treasury, &None);
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &1000);
+    token_client.approve(&depositor, &contract_id, &1000, &200);
+
+    let labels = [
+        symbol_short!("Jan"),
+        symbol_short!("Feb"),
+        symbol_short!("Mar"),
+        symbol_short!("Apr"),
+    ];
+    let order_ref = BytesN::from_array(&env, &[7u8; 32]);
+    for (i, label) in labels.iter().enumerate() {
+        let payee = if i == 3 { &other_recipient } else { &recipient };
+        client.create_escrow(
+            &(159 + i as u64),
+            &depositor,
+            payee,
+            &token_address,
+            &vec![
+                &env,
+                Milestone {
+                    amount: 200,
+                    status: MilestoneStatus::Pending,
+                    description: label.clone(),
+                },
+            ],
+            &(1706400000u64 + i as u64),
+            &(i == 2).then(|| order_ref.clone()),
+            &None,
+            &200,
+            &EscrowOptions::new(EscrowKind::Standard),
+        );
+        client.deposit_funds(&(159 + i as u64));
+    }
+
+    assert_eq!(
+        client.try_merge_escrows(&vec![&env, 159u64, 162]),
+        Err(Ok(Error::UnauthorizedAccess))
+    );
+    assert_eq!(client.merge_escrows(&vec![&env, 159u64, 160, 161]), 159);
+    assert_eq!(env.auths()[0].0, depositor);
+    assert_eq!(env.auths()[1].0, recipient);
+
+    let merged = client.get_escrow(&159);
+    assert_eq!(merged.milestones.len(), 3);
+    assert_eq!(merged.total_amount, 600);
+    assert_eq!(merged.deadline, 1706400002);
+    #[cfg(feature = "views-extended")]
+    assert_eq!(client.get_escrow_accounting(&159).deposited, 600);
+    assert_eq!(
+        client.try_get_escrow(&161).err(),
+        Some(Ok(Error::EscrowNotFound))
+    );
+    assert_eq!(client.get_merged_into(&161), Some(159));
+    // The merged-away escrow's order reference leads to the merged escrow
+    #[cfg(feature = "views-extended")]
+    assert_eq!(client.get_escrow_by_ref(&order_ref).0, 159);
+
+    // The merged schedule releases like any other
+    client.release_milestone(&159, &2);
+    assert_eq!(
+        client.get_escrow(&159).milestones.get(2).unwrap().status,
+        MilestoneStatus::Released
+    );
+}
+
+#[test]
+fn test_expense_milestone_pays_approved_claims_up_to_cap() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    client.initialize(&treasury, &None);
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &1000);
+    token_client.approve(&depositor, &contract_id, &1000, &200);
+
+    client.create_escrow(
+        &163,
+        &depositor,
+        &recipient,
+        &token_address,
+        &vec![
+            &env,
+            Milestone {
+                amount: 700,
+                status: MilestoneStatus::Pending,
+                description: symbol_short!("Service"),
+            },
+            Milestone {
+                amount: 300,
+                status: MilestoneStatus::Pending,
+                description: symbol_short!("Expenses"),
+            },
+        ],
+        &1706400000u64,
+        &None,
+        &None,
+        &1000,
+        &EscrowOptions::new(EscrowKind::Standard),
+    );
+    client.set_expense_milestone(&163, &1);
+    assert_eq!(env.auths()[0].0, depositor);
+    client.deposit_funds(&163);
+
+    // The cap only pays out through approved claims
+    assert_eq!(
+        client.try_release_milestone(&163, &1),
+        Err(Ok(Error::InvalidEscrowKind))
+    );
+    let travel = BytesN::from_array(&env, &[1; 32]);
+    assert_eq!(
+        client.try_claim_expense(&163, &1, &400, &travel),
+        Err(Ok(Error::InsufficientBalance))
+    );
+    assert_eq!(client.claim_expense(&163, &1, &120, &travel), 0);
+    assert_eq!(env.auths()[0].0, recipient);
+    client.claim_expense(&163, &1, &50, &BytesN::from_array(&env, &[2; 32]));
+
+    client.approve_expense(&163, &1, &0);
+    assert!(client.get_expense_claim(&163, &1, &0).unwrap().approved);
+    assert_eq!(
+        client.try_approve_expense(&163, &1, &0),
+        Err(Ok(Error::MilestoneAlreadyReleased))
+    );
+    assert_eq!(
+        token_client.balance(&recipient) + token_client.balance(&treasury),
+        120
+    );
+
+    // Closing refunds the unspent cap; the unapproved claim lapses
+    client.close_expenses(&163, &1);
+    assert_eq!(token_client.balance(&depositor), 180);
+    let escrow = client.get_escrow(&163);
+    assert_eq!(escrow.total_amount, 820);
+    assert_eq!(
+        escrow.milestones.get(1).unwrap().status,
+        MilestoneStatus::Released
+    );
+
+    client.release_milestone(&163, &0);
+    client.complete_escrow(&163);
+    assert_eq!(client.get_escrow(&163).status, EscrowStatus::Completed);
+}
+
+#[test]
+fn test_settlements_leave_receipts() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    client.initialize(&treasury, &Some(100));
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &1500);
+    token_client.approve(&depositor, &contract_id, &1500, &200);
+
+    client.create_escrow(
+        &164,
+        &depositor,
+        &recipient,
+        &token_address,
+        &vec![
+            &env,
+            Milestone {
+                amount: 600,
+                status: MilestoneStatus::Pending,
+                description: symbol_short!("Draft"),
+            },
+            Milestone {
+                amount: 400,
+                status: MilestoneStatus::Pending,
+                description: symbol_short!("Final"),
+            },
+        ],
+        &1706400000u64,
+        &None,
+        &None,
+        &1000,
+        &EscrowOptions::new(EscrowKind::Standard),
+    );
+    client.deposit_funds(&164);
+    assert_eq!(client.get_receipt_count(&164), 0);
+
+    env.ledger().with_mut(|li| li.sequence_number = 42);
+    client.release_milestone(&164, &0);
+    assert_eq!(
+        client.get_receipt(&164, &0),
+        Some(Receipt {
+            kind: ReceiptKind::Release,
+            milestone_index: Some(0),
+            payee: recipient.clone(),
+            amount: 600,
+            fee: 6,
+            net: 594,
+            ledger: 42,
+            timestamp: env.ledger().timestamp(),
+        })
+    );
+
+    client.release_milestone(&164, &1);
+    assert_eq!(client.get_receipt_count(&164), 2);
+
+    client.create_escrow(
+        &165,
+        &depositor,
+        &recipient,
+        &token_address,
+        &vec![
+            &env,
+            Milestone {
+                amount: 500,
+                status: MilestoneStatus::Pending,
+                description: symbol_short!("Retainer"),
+            },
+        ],
+        &1706400000u64,
+        &None,
+        &None,
+        &500,
+        &EscrowOptions::new(EscrowKind::Standard),
+    );
+    client.deposit_funds(&165);
+    client.cancel_escrow(&165);
+    let refund = client.get_receipt(&165, &0).unwrap();
+    assert_eq!(refund.kind, ReceiptKind::Refund);
+    assert_eq!(
+        (refund.payee, refund.amount, refund.fee),
+        (depositor, 500, 0)
+    );
+}
+
+#[test]
+fn test_permissioned_mode_admits_only_participants() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let registrar = Address::generate(&env);
+
+    client.initialize(&treasury, &None);
+    client.init(&admin);
+    let (_, _, token_address) = create_token_contract(&env, &admin);
+    let create = |escrow_id: u64| {
+        client.try_create_escrow(
+            &escrow_id,
+            &depositor,
+            &recipient,
+            &token_address,
+            &vec![
+                &env,
+                Milestone {
+                    amount: 1000,
+                    status: MilestoneStatus::Pending,
+                    description: symbol_short!("Supply"),
+                },
+            ],
+            &1706400000u64,
+            &None,
+            &None,
+            &1000,
+            &repeat_deal_options(EscrowKind::Standard),
+        )
+    };
+
+    client.set_permissioned(&true);
+    assert_eq!(env.auths()[0].0, admin);
+    assert_eq!(create(166), Err(Ok(Error::UnauthorizedAccess)));
+
+    client.set_participant(&admin, &depositor, &true);
+    // Only the admin and allowlisted registrars hand out the role
+    assert_eq!(
+        client.try_set_participant(&registrar, &recipient, &true),
+        Err(Ok(Error::UnauthorizedAccess))
+    );
+    client.set_registrar(&registrar, &true);
+    client.set_participant(&registrar, &recipient, &true);
+    assert_eq!(env.auths()[0].0, registrar);
+    assert!(client.is_participant(&recipient));
+    assert!(create(166).is_ok());
+
+    client.set_participant(&registrar, &recipient, &false);
+    assert_eq!(create(167), Err(Ok(Error::UnauthorizedAccess)));
+
+    client.set_permissioned(&false);
+    assert!(create(167).is_ok());
+}
+
+#[contract]
+pub struct LimitPolicy;
+
+#[contractimpl]
+impl LimitPolicy {
+    pub fn check(
+        _env: Env,
+        action: PolicyAction,
+        _parties: Vec<Address>,
+        _token: Address,
+        amount: i128,
+    ) -> bool {
+        match action {
+            PolicyAction::Create => amount <= 5000,
+            PolicyAction::Release => amount <= 1000,
+        }
+    }
+}
+
+#[test]
+fn test_policy_engine_gates_creation_and_release() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    client.initialize(&treasury, &None);
+    client.init(&admin);
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &3000);
+    token_client.approve(&depositor, &contract_id, &3000, &200);
+
+    let policy = env.register_contract(None, LimitPolicy);
+    client.set_policy(&Some(policy.clone()));
+    assert_eq!(env.auths()[0].0, admin);
+    assert_eq!(client.get_policy(), Some(policy));
+
+    let create = |escrow_id: u64, amounts: &[i128]| {
+        let mut milestones = Vec::new(&env);
+        let labels = [symbol_short!("One"), symbol_short!("Two")];
+        for (amount, label) in amounts.iter().zip(labels) {
+            milestones.push_back(Milestone {
+                amount: *amount,
+                status: MilestoneStatus::Pending,
+                description: label,
+            });
+        }
+        client.try_create_escrow(
+            &escrow_id,
+            &depositor,
+            &recipient,
+            &token_address,
+            &milestones,
+            &1706400000u64,
+            &None,
+            &None,
+            &amounts.iter().sum(),
+            &EscrowOptions::new(EscrowKind::Standard),
+        )
+    };
+    assert_eq!(
+        create(168, &[3000, 3000]),
+        Err(Ok(Error::UnauthorizedAccess))
+    );
+    assert!(create(168, &[1000, 2000]).is_ok());
+    client.deposit_funds(&168);
+
+    client.release_milestone(&168, &0);
+    assert_eq!(
+        client.try_release_milestone(&168, &1),
+        Err(Ok(Error::UnauthorizedAccess))
+    );
+
+    client.set_policy(&None);
+    client.release_milestone(&168, &1);
+    assert_eq!(client.get_escrow(&168).total_released, 3000);
+}
+
+#[test]
+fn test_notifier_key_is_published_by_admin() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.init(&admin);
+
+    assert_eq!(client.get_notifier_key(), None);
+
+    let key = BytesN::from_array(&env, &[7; 32]);
+    client.set_notifier_key(&Some(key.clone()));
+    assert_eq!(env.auths()[0].0, admin);
+    assert_eq!(client.get_notifier_key(), Some(key));
+
+    client.set_notifier_key(&None);
+    assert_eq!(client.get_notifier_key(), None);
+}
+
+#[test]
+fn test_refused_refund_is_quarantined_and_retried() {
+    let env = feature_dependent_env();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+    let token_address = env.register_contract(None, FreezableToken);
+    let token = FreezableTokenClient::new(&env, &token_address);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+
+    client.initialize(&treasury, &Some(0));
+    token.mint(&depositor, &600);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 600,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("One"),
+        },
+    ];
+    client.create_escrow(
+        &169,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+        &None,
+        &None,
+        &600,
+        &EscrowOptions::new(EscrowKind::Standard),
+    );
+    client.deposit_funds(&169);
+
+    // The issuer freezes the depositor; the cancellation still settles
+    token.set_frozen(&depositor, &true);
+    client.cancel_escrow(&169);
+    let refunded: soroban_sdk::Vec<soroban_sdk::Val> = (
+        Symbol::new(&env, "Vaultix"),
+        Symbol::new(&env, "EscrowRefunded"),
+        169u64,
+    )
+        .into_val(&env);
+    assert!(!env.events().all().iter().any(|event| event.1 == refunded));
+    assert_eq!(client.get_escrow(&169).status, EscrowStatus::Cancelled);
+    assert_eq!(token.balance(&depositor), 0);
+    let held = client.get_quarantined_transfer(&0).unwrap();
+    assert_eq!(held.escrow_id, 169);
+    assert_eq!(held.leg, TransferLeg::Refund);
+    assert_eq!(held.to, depositor);
+    assert_eq!(held.amount, 600);
+    // The refund is still in the contract, so it is not counted as refunded
+    #[cfg(feature = "views-extended")]
+    {
+        let accounting = client.get_escrow_accounting(&169);
+        assert_eq!(accounting.refunded_to_depositor, 0);
+        assert_eq!(accounting.claimable_outstanding, 600);
+    }
+
+    token.set_frozen(&depositor, &false);
+    assert_eq!(client.retry_transfer(&0), 600);
+    assert_eq!(token.balance(&depositor), 600);
+    assert_eq!(client.get_quarantined_transfer(&0), None);
+    #[cfg(feature = "views-extended")]
+    {
+        let accounting = client.get_escrow_accounting(&169);
+        assert_eq!(accounting.refunded_to_depositor, 600);
+        assert_eq!(accounting.claimable_outstanding, 0);
+        assert_eq!(client.get_address_stats(&depositor).totals.refunded, 600);
+    }
+    assert_eq!(
+        client.try_retry_transfer(&0),
+        Err(Ok(Error::InvalidEscrowStatus))
+    );
+}
+
+#[test]
+fn test_notification_hint_is_set_by_owner() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+    let owner = Address::generate(&env);
+    assert_eq!(client.get_notification_hint(&owner), None);
+
+    let contact = NotificationHint::Contact(BytesN::from_array(&env, &[3; 32]));
+    client.set_notification_hint(&owner, &Some(contact.clone()));
+    assert_eq!(env.auths()[0].0, owner);
+    assert_eq!(client.get_notification_hint(&owner), Some(contact));
+
+    let notifier = NotificationHint::Notifier(Address::generate(&env));
+    client.set_notification_hint(&owner, &Some(notifier.clone()));
+    assert_eq!(client.get_notification_hint(&owner), Some(notifier));
+
+    client.set_notification_hint(&owner, &None);
+    assert_eq!(client.get_notification_hint(&owner), None);
+}
+
+#[cfg(feature = "disputes")]
+#[test]
+fn test_expense_milestone_cannot_be_refunded_twice_through_partial_dispute() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    client.initialize(&treasury, &None);
+    client.init(&admin);
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &1000);
+    token_client.approve(&depositor, &contract_id, &1000, &200);
+
+    client.create_escrow(
+        &170,
+        &depositor,
+        &recipient,
+        &token_address,
+        &vec![
+            &env,
+            Milestone {
+                amount: 700,
+                status: MilestoneStatus::Pending,
+                description: symbol_short!("Service"),
+            },
+            Milestone {
+                amount: 300,
+                status: MilestoneStatus::Pending,
+                description: symbol_short!("Expenses"),
+            },
+        ],
+        &1706400000u64,
+        &None,
+        &None,
+        &1000,
+        &EscrowOptions::new(EscrowKind::Standard),
+    );
+    client.set_expense_milestone(&170, &1);
+    client.deposit_funds(&170);
+
+    assert_eq!(
+        client.try_open_partial_dispute(&170, &1, &depositor, &100),
+        Err(Ok(Error::InvalidEscrowKind))
+    );
+    client.close_expenses(&170, &1);
+    assert_eq!(
+        client.try_resolve_partial_dispute(&170, &1, &depositor),
+        Err(Ok(Error::InvalidEscrowStatus))
+    );
+
+    // The cap was refunded exactly once
+    assert_eq!(token_client.balance(&depositor), 300);
+    let escrow = client.get_escrow(&170);
+    assert_eq!(escrow.total_amount, 700);
+    assert_eq!(escrow.milestones.get(1).unwrap().amount, 0);
+}
+
+#[cfg(feature = "views-extended")]
+#[test]
+fn test_authorized_actions_skip_expense_milestones() {
+    let env = feature_dependent_env();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    client.initialize(&treasury, &None);
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &1000);
+    token_client.approve(&depositor, &contract_id, &1000, &200);
+
+    client.create_escrow(
+        &171,
+        &depositor,
+        &recipient,
+        &token_address,
+        &vec![
+            &env,
+            Milestone {
+                amount: 700,
+                status: MilestoneStatus::Pending,
+                description: symbol_short!("Service"),
+            },
+            Milestone {
+                amount: 300,
+                status: MilestoneStatus::Pending,
+                description: symbol_short!("Expenses"),
+            },
+        ],
+        &1706400000u64,
+        &None,
+        &None,
+        &1000,
+        &EscrowOptions::new(EscrowKind::Standard),
+    );
+    client.set_expense_milestone(&171, &1);
+    client.deposit_funds(&171);
+    assert!(client.get_authorized_actions(&171, &depositor).release);
+
+    // Only the expense milestone is left, which pays out through claims
+    client.release_milestone(&171, &0);
+    assert!(!client.get_authorized_actions(&171, &depositor).release);
+}
+
+#[cfg(all(feature = "disputes", feature = "views-extended"))]
+#[test]
+fn test_authorized_actions_follow_entrypoint_checks() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let operator = Address::generate(&env);
+    let co_signer = Address::generate(&env);
+
+    client.initialize(&treasury, &Some(0));
+    client.init(&admin);
+    client.set_chargeback_window(&(3 * 24 * 60 * 60));
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &5000);
+    token_client.approve(&depositor, &contract_id, &5000, &200);
+
+    let create = |escrow_id: u64, options: EscrowOptions| {
+        let milestones = vec![
+            &env,
+            Milestone {
+                amount: 300,
+                status: MilestoneStatus::Pending,
+                description: symbol_short!("Design"),
+            },
+            Milestone {
+                amount: 200,
+                status: MilestoneStatus::Pending,
+                description: symbol_short!("Build"),
+            },
+        ];
+        client.create_escrow(
+            &escrow_id,
+            &depositor,
+            &recipient,
+            &token_address,
+            &milestones,
+            &1706400000u64,
+            &None,
+            &None,
+            &500,
+            &options,
+        );
+        client.deposit_funds(&escrow_id);
+    };
+
+    // An open partial dispute blocks cancelling, and under FreezeAll every
+    // release too
+    let mut frozen = repeat_deal_options(EscrowKind::Standard);
+    frozen.dispute_policy = DisputePolicy::FreezeAll;
+    create(172, frozen);
+    let actions = client.get_authorized_actions(&172, &depositor);
+    assert!(actions.release && actions.cancel);
+    client.open_partial_dispute(&172, &0, &depositor, &100);
+    let actions = client.get_authorized_actions(&172, &depositor);
+    assert!(!actions.release && !actions.cancel);
+
+    // A fully disputed slice leaves nothing to release on its milestone
+    create(173, repeat_deal_options(EscrowKind::Standard));
+    client.open_partial_dispute(&173, &0, &depositor, &300);
+    client.release_milestone(&173, &1);
+    assert!(!client.get_authorized_actions(&173, &depositor).release);
+
+    // A held release is confirmed and can no longer be cancelled
+    create(174, repeat_deal_options(EscrowKind::Standard));
+    client.confirm_delivery(&174, &0, &depositor);
+    let actions = client.get_authorized_actions(&174, &depositor);
+    assert!(actions.release && !actions.cancel);
+
+    // The operator signs for a delegated depositor, not the depositor
+    let mut delegated = repeat_deal_options(EscrowKind::Standard);
+    delegated.auth_mode = AuthMode::Delegated(operator.clone());
+    create(175, delegated);
+    let actions = client.get_authorized_actions(&175, &operator);
+    assert!(actions.release && actions.cancel && actions.dispute);
+    let actions = client.get_authorized_actions(&175, &depositor);
+    assert!(!actions.release && !actions.cancel && !actions.dispute);
+
+    // Every multisig signer takes part in the depositor's actions
+    let mut multisig = repeat_deal_options(EscrowKind::Standard);
+    multisig.auth_mode = AuthMode::Multisig(vec![&env, co_signer.clone()]);
+    create(176, multisig);
+    assert!(client.get_authorized_actions(&176, &co_signer).cancel);
+    assert!(client.get_authorized_actions(&176, &depositor).cancel);
+    assert!(!client.get_authorized_actions(&176, &operator).cancel);
+}
+
+#[test]
+fn test_clawback_grant_leaves_held_releases_alone() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury_wallet = Address::generate(&env);
+    let grantee = Address::generate(&env);
+    let program_admin = Address::generate(&env);
+    let fee_treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let window = 3 * 24 * 60 * 60;
+
+    client.initialize(&fee_treasury, &Some(0));
+    client.init(&admin);
+    client.set_chargeback_window(&window);
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&treasury_wallet, &600);
+    token_client.approve(&treasury_wallet, &contract_id, &600, &200);
+
+    client.create_escrow(
+        &177,
+        &treasury_wallet,
+        &grantee,
+        &token_address,
+        &vec![
+            &env,
+            Milestone {
+                amount: 300,
+                status: MilestoneStatus::Pending,
+                description: symbol_short!("Proposal"),
+            },
+            Milestone {
+                amount: 300,
+                status: MilestoneStatus::Pending,
+                description: symbol_short!("Launch"),
+            },
+        ],
+        &1706400000u64,
+        &None,
+        &None,
+        &600,
+        &EscrowOptions::new(EscrowKind::Grant),
+    );
+    client.set_program_admin(&177, &program_admin);
+    client.deposit_funds(&177);
+
+    // The confirmed milestone is the grantee's; it pays out before clawback
+    client.confirm_delivery(&177, &0, &treasury_wallet);
+    assert_eq!(
+        client.try_clawback_grant(&177),
+        Err(Ok(Error::ChargebackWindowOpen))
+    );
+
+    env.ledger().with_mut(|li| li.timestamp += window);
+    client.claim_held_release(&177, &0);
+    assert_eq!(client.clawback_grant(&177), 300);
+    assert_eq!(token_client.balance(&grantee), 300);
+    assert_eq!(token_client.balance(&treasury_wallet), 300);
+}
+
+#[test]
+fn test_migration_rejects_disputed_and_sealed_escrows() {
+    let env = feature_dependent_env();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    client.initialize(&treasury, &Some(0));
+    client.init(&admin);
+    let (old_client, old_admin, old_token) = create_token_contract(&env, &admin);
+    let (_, _, new_token) = create_token_contract(&env, &admin);
+    old_admin.mint(&depositor, &600);
+    old_client.approve(&depositor, &contract_id, &600, &200);
+    let rate = 2 * migration::RATE_SCALE;
+
+    // A sealed escrow only has its total until its milestones are revealed
+    let salt = BytesN::from_array(&env, &[7u8; 32]);
+    client.create_sealed_escrow(
+        &178,
+        &depositor,
+        &recipient,
+        &old_token,
+        &300,
+        &vec![&env, symbol_short!("Audit")],
+        &vec![&env, commit_amount(&env, 300, &salt)],
+        &1706400000u64,
+    );
+    assert_eq!(
+        client.try_migrate_escrow_token(&178, &old_token, &new_token, &rate),
+        Err(Ok(Error::MilestoneSealed))
+    );
+
+    client.create_escrow(
+        &179,
+        &depositor,
+        &recipient,
+        &old_token,
+        &vec![
+            &env,
+            Milestone {
+                amount: 300,
+                status: MilestoneStatus::Pending,
+                description: symbol_short!("One"),
+            },
+        ],
+        &1706400000u64,
+        &None,
+        &None,
+        &300,
+        &EscrowOptions::new(EscrowKind::Standard),
+    );
+    client.deposit_funds(&179);
+
+    // The slice stays Pending but is recorded in old-token units
+    #[cfg(feature = "disputes")]
+    {
+        client.open_partial_dispute(&179, &0, &depositor, &100);
+        assert_eq!(
+            client.try_migrate_escrow_token(&179, &old_token, &new_token, &rate),
+            Err(Ok(Error::AlreadyInDispute))
+        );
+    }
+}
+
+#[cfg(feature = "disputes")]
+#[test]
+fn test_unfunded_escrow_cannot_be_disputed() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    client.initialize(&treasury, &Some(0));
+    client.init(&admin);
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &1000);
+    token_client.approve(&depositor, &contract_id, &1000, &200);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 1000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Task"),
+        },
+    ];
+    for escrow_id in [190u64, 191] {
+        client.create_escrow(
+            &escrow_id,
+            &depositor,
+            &recipient,
+            &token_address,
+            &milestones,
+            &1706400000u64,
+            &None,
+            &None,
+            &1000,
+            &repeat_deal_options(EscrowKind::Standard),
+        );
+    }
+    client.deposit_funds(&190);
+
+    // A ruling on 191 would pay out of 190's deposit
+    assert_eq!(
+        client.try_raise_dispute(&191, &recipient),
+        Err(Ok(Error::EscrowNotActive))
+    );
+    assert_eq!(
+        client.try_resolve_dispute(&191, &recipient),
+        Err(Ok(Error::InvalidEscrowStatus))
+    );
+    assert_eq!(token_client.balance(&contract_id), 1000);
+    assert_eq!(token_client.balance(&recipient), 0);
+
+    client.release_milestone(&190, &0);
+    assert_eq!(token_client.balance(&recipient), 1000);
+}
+
+#[test]
+fn test_parties_cannot_refer_their_own_escrow() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    client.initialize(&treasury, &Some(100));
+    client.set_referral_share(&2000);
+    client.register_referral_code(&symbol_short!("MINE"), &depositor);
+    client.register_referral_code(&symbol_short!("THEIRS"), &recipient);
+    let (_, _, token_address) = create_token_contract(&env, &admin);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 1000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Task"),
+        },
+    ];
+    // Either party's own code would kick part of the fee back to the deal
+    for code in [symbol_short!("MINE"), symbol_short!("THEIRS")] {
+        let mut options = EscrowOptions::new(EscrowKind::Standard);
+        options.referral = Referral::Code(code.clone());
+        assert_eq!(
+            client
+                .try_create_escrow(
+                    &192,
+                    &depositor,
+                    &recipient,
+                    &token_address,
+                    &milestones,
+                    &1706400000u64,
+                    &None,
+                    &None,
+                    &1000,
+                    &options,
+                )
+                .err(),
+            Some(Ok(Error::SelfDealing))
+        );
+        assert_eq!(client.get_referral_code(&code).uses, 0);
+    }
+}
@@ -0,0 +1,177 @@
+use crate::{
+    ensure_not_paused, extend_instance_ttl, payout_address, record_escrow_flow, refund_address,
+    Error, Flow, UnclaimedPayout, VaultixEscrow, VaultixEscrowClient,
+};
+use soroban_sdk::{contractimpl, symbol_short, token, Address, Env, Symbol};
+
+#[cfg(any(test, feature = "testutils"))]
+use crate::__VaultixEscrow_fn_set_registry;
+
+/// How long a diverted payout waits for its payee before the depositor may
+/// take it back
+pub const ESCHEAT_PERIOD_SECS: u64 = 180 * 24 * 60 * 60;
+
+#[contractimpl]
+impl VaultixEscrow {
+    /// Retries the payouts to `payee` that could not be delivered, e.g. once
+    /// the issuer re-authorizes it. Permissionless, since the funds can only
+    /// go to the payee. Returns the amount paid.
+    pub fn claim_unclaimed_payout(env: Env, escrow_id: u64, payee: Address) -> Result<i128, Error> {
+        extend_instance_ttl(&env);
+        ensure_not_paused(&env)?;
+        let escrow = Self::get_escrow(env.clone(), escrow_id)?;
+        let unclaimed = take_unclaimed(&env, escrow_id, &payee)?;
+
+        let token_client = token::Client::new(&env, &escrow.token_address);
+        token_client.transfer(
+            &env.current_contract_address(),
+            &payout_address(&env, &unclaimed.payee),
+            &unclaimed.amount,
+        );
+        record_escrow_flow(
+            &env,
+            escrow_id,
+            &unclaimed.payee,
+            Flow::Received,
+            unclaimed.amount,
+        );
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "UnclaimedPayoutClaimed"),
+                escrow_id,
+            ),
+            (unclaimed.payee, unclaimed.amount),
+        );
+
+        Ok(unclaimed.amount)
+    }
+
+    /// Escheat rule: payouts to `payee` left undeliverable for
+    /// `ESCHEAT_PERIOD_SECS` since their last diversion revert to the
+    /// depositor's refund address.
+    pub fn escheat_unclaimed_payout(
+        env: Env,
+        escrow_id: u64,
+        payee: Address,
+    ) -> Result<i128, Error> {
+        extend_instance_ttl(&env);
+        ensure_not_paused(&env)?;
+        let escrow = Self::get_escrow(env.clone(), escrow_id)?;
+        escrow.authorize(escrow_id, &escrow.depositor);
+        let unclaimed: UnclaimedPayout = env
+            .storage()
+            .persistent()
+            .get(&unclaimed_payout_key(escrow_id, &payee))
+            .ok_or(Error::InvalidEscrowStatus)?;
+        if env.ledger().timestamp() < unclaimed.since.saturating_add(ESCHEAT_PERIOD_SECS) {
+            return Err(Error::RetentionNotElapsed);
+        }
+        take_unclaimed(&env, escrow_id, &payee)?;
+
+        let token_client = token::Client::new(&env, &escrow.token_address);
+        token_client.transfer(
+            &env.current_contract_address(),
+            &refund_address(&env, escrow_id, &escrow),
+            &unclaimed.amount,
+        );
+        record_escrow_flow(
+            &env,
+            escrow_id,
+            &escrow.depositor,
+            Flow::Refunded,
+            unclaimed.amount,
+        );
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "UnclaimedPayoutEscheated"),
+                escrow_id,
+            ),
+            (unclaimed.payee, unclaimed.amount),
+        );
+
+        Ok(unclaimed.amount)
+    }
+
+    pub fn get_unclaimed_payout(
+        env: Env,
+        escrow_id: u64,
+        payee: Address,
+    ) -> Option<UnclaimedPayout> {
+        env.storage()
+            .persistent()
+            .get(&unclaimed_payout_key(escrow_id, &payee))
+    }
+}
+
+fn unclaimed_payout_key(escrow_id: u64, payee: &Address) -> (Symbol, u64, Address) {
+    (symbol_short!("unclaimed"), escrow_id, payee.clone())
+}
+
+fn unclaimed_total_key(escrow_id: u64) -> (Symbol, u64) {
+    (symbol_short!("unclm_tot"), escrow_id)
+}
+
+/// Undelivered payouts held for the escrow, across all payees
+pub(crate) fn unclaimed_total(env: &Env, escrow_id: u64) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&unclaimed_total_key(escrow_id))
+        .unwrap_or(0)
+}
+
+fn set_unclaimed_total(env: &Env, escrow_id: u64, total: i128) {
+    let key = unclaimed_total_key(escrow_id);
+    if total == 0 {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, &total);
+        env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+    }
+}
+
+/// Removes `payee`'s undelivered payouts from the escrow's books
+fn take_unclaimed(env: &Env, escrow_id: u64, payee: &Address) -> Result<UnclaimedPayout, Error> {
+    let key = unclaimed_payout_key(escrow_id, payee);
+    let unclaimed: UnclaimedPayout = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .ok_or(Error::InvalidEscrowStatus)?;
+    env.storage().persistent().remove(&key);
+    set_unclaimed_total(
+        env,
+        escrow_id,
+        unclaimed_total(env, escrow_id) - unclaimed.amount,
+    );
+    Ok(unclaimed)
+}
+
+/// Parks a payout the token refused to deliver so it can be claimed or
+/// escheated later. Each payee is held separately, e.g. a grant's previous
+/// and new grantee, and its escheat clock restarts with every diversion to
+/// it.
+pub(crate) fn divert_payout(env: &Env, escrow_id: u64, payee: &Address, amount: i128) {
+    let key = unclaimed_payout_key(escrow_id, payee);
+    let earlier: Option<UnclaimedPayout> = env.storage().persistent().get(&key);
+    let unclaimed = UnclaimedPayout {
+        payee: payee.clone(),
+        amount: earlier.map_or(0, |u| u.amount) + amount,
+        since: env.ledger().timestamp(),
+    };
+    env.storage().persistent().set(&key, &unclaimed);
+    env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+    set_unclaimed_total(env, escrow_id, unclaimed_total(env, escrow_id) + amount);
+
+    env.events().publish(
+        (
+            Symbol::new(env, "Vaultix"),
+            Symbol::new(env, "PayoutDiverted"),
+            escrow_id,
+        ),
+        (payee.clone(), amount),
+    );
+}
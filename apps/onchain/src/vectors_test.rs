@@ -0,0 +1,335 @@
+//! Canonical test vectors for every settlement path.
+//!
+//! Each vector runs one scenario from a fixed setup and records its inputs,
+//! the escrow contract's events, the resulting token balances and the final
+//! escrow record, with events and the record as base64 XDR so auditors and
+//! third-party implementations can compare byte for byte. Vectors are plain
+//! JSON under `test_vectors/`. A behavior change shows up as a mismatch,
+//! accepted by rerunning with `VAULTIX_UPDATE_VECTORS=1`.
+extern crate std;
+
+use super::*;
+use serde_json::{json, Value};
+use soroban_sdk::{
+    testutils::{Address as _, Events, Ledger},
+    token,
+    xdr::{Limits, ScVal, WriteXdr},
+    Address, BytesN, Env, IntoVal, TryFromVal, Val,
+};
+use std::format;
+use std::string::{String, ToString};
+use std::vec::Vec as StdVec;
+
+const ESCROW_ID: u64 = 1;
+const FEE_BPS: i128 = 100;
+const DAY: u64 = 24 * 60 * 60;
+
+struct Vector {
+    env: Env,
+    contract_id: Address,
+    client: VaultixEscrowClient<'static>,
+    depositor: Address,
+    recipient: Address,
+    treasury: Address,
+    token: Address,
+    inputs: Value,
+    calls: StdVec<String>,
+}
+
+impl Vector {
+    /// Creates escrow `ESCROW_ID` over `amounts`, unfunded
+    fn new(amounts: &[i128], options: EscrowOptions) -> Self {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register_contract(None, VaultixEscrow);
+        let client = VaultixEscrowClient::new(&env, &contract_id);
+        let treasury = Address::generate(&env);
+        let admin = Address::generate(&env);
+        let depositor = Address::generate(&env);
+        let recipient = Address::generate(&env);
+        client.initialize(&treasury, &Some(FEE_BPS));
+        client.init(&admin);
+
+        let token = env.register_stellar_asset_contract(admin);
+        token::StellarAssetClient::new(&env, &token).mint(&depositor, &20_000);
+        token::Client::new(&env, &token).approve(&depositor, &contract_id, &20_000, &200);
+
+        let labels = [symbol_short!("M0"), symbol_short!("M1")];
+        let mut milestones = Vec::new(&env);
+        for (amount, description) in amounts.iter().zip(labels) {
+            milestones.push_back(Milestone {
+                amount: *amount,
+                status: MilestoneStatus::Pending,
+                description,
+            });
+        }
+        let inputs = json!({
+            "fee_bps": FEE_BPS.to_string(),
+            "depositor": strkey(&depositor),
+            "recipient": strkey(&recipient),
+            "treasury": strkey(&treasury),
+            "milestones": amounts.iter().map(|a| a.to_string()).collect::<StdVec<_>>(),
+            "options": {
+                "kind": format!("{:?}", options.kind),
+                "fee_payer": format!("{:?}", options.fee_payer),
+                "release_schedule": format!("{:?}", options.release_schedule),
+            },
+        });
+        client.create_escrow(
+            &ESCROW_ID,
+            &depositor,
+            &recipient,
+            &token,
+            &milestones,
+            &1706400000u64,
+            &None,
+            &None,
+            &amounts.iter().sum(),
+            &options,
+        );
+
+        Vector {
+            env,
+            contract_id,
+            client,
+            depositor,
+            recipient,
+            treasury,
+            token,
+            inputs,
+            calls: StdVec::new(),
+        }
+    }
+
+    /// Runs one contract call, recording it under `call`
+    fn step(&mut self, call: &str, f: impl FnOnce(&VaultixEscrowClient)) {
+        self.calls.push(call.to_string());
+        f(&self.client);
+    }
+
+    fn advance_time(&mut self, secs: u64) {
+        self.calls.push(format!("advance_time({secs})"));
+        self.env.ledger().with_mut(|li| li.timestamp += secs);
+    }
+
+    fn render(&self) -> Value {
+        let env = &self.env;
+        let mut events = StdVec::new();
+        for (contract, topics, data) in env.events().all().iter() {
+            if contract != self.contract_id {
+                continue;
+            }
+            let topics: StdVec<ScVal> = topics
+                .iter()
+                .map(|t| ScVal::try_from_val(env, &t).unwrap())
+                .collect();
+            let name = match topics.get(1) {
+                Some(ScVal::Symbol(name)) => name.to_utf8_string_lossy(),
+                _ => String::new(),
+            };
+            events.push(json!({
+                "name": name,
+                "topics": topics.iter().map(xdr).collect::<StdVec<_>>(),
+                "data": xdr(&ScVal::try_from_val(env, &data).unwrap()),
+            }));
+        }
+
+        let balance = |addr: &Address| {
+            token::Client::new(env, &self.token)
+                .balance(addr)
+                .to_string()
+        };
+        let escrow = self.client.get_escrow(&ESCROW_ID);
+        let record: Val = escrow.clone().into_val(env);
+        let record = ScVal::try_from_val(env, &record).unwrap();
+        json!({
+            "inputs": self.inputs,
+            "calls": self.calls,
+            "events": events,
+            "balances": {
+                "depositor": balance(&self.depositor),
+                "recipient": balance(&self.recipient),
+                "treasury": balance(&self.treasury),
+                "contract": balance(&self.contract_id),
+            },
+            "escrow": {
+                "status": format!("{:?}", escrow.status),
+                "total_amount": escrow.total_amount.to_string(),
+                "total_released": escrow.total_released.to_string(),
+                "milestones": escrow
+                    .milestones
+                    .iter()
+                    .map(|m| json!({
+                        "amount": m.amount.to_string(),
+                        "status": format!("{:?}", m.status),
+                    }))
+                    .collect::<StdVec<_>>(),
+                "xdr": xdr(&record),
+            },
+        })
+    }
+
+    /// Compares the vector with `test_vectors/<name>.json`, writing it when
+    /// missing or when updates are requested
+    fn check(&self, name: &str) {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("test_vectors")
+            .join(format!("{name}.json"));
+        let rendered = serde_json::to_string_pretty(&self.render()).unwrap() + "\n";
+        let update = std::env::var("VAULTIX_UPDATE_VECTORS").is_ok_and(|v| v == "1");
+        match std::fs::read_to_string(&path) {
+            Ok(stored) if !update => assert!(
+                stored == rendered,
+                "{name} diverged from {}; rerun with VAULTIX_UPDATE_VECTORS=1 to accept",
+                path.display()
+            ),
+            _ => {
+                std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                std::fs::write(&path, rendered).unwrap();
+            }
+        }
+    }
+}
+
+fn xdr(val: &ScVal) -> String {
+    val.to_xdr_base64(Limits::none()).unwrap()
+}
+
+fn strkey(addr: &Address) -> String {
+    let strkey = addr.to_string();
+    let mut buf = [0u8; 56];
+    strkey.copy_into_slice(&mut buf[..strkey.len() as usize]);
+    String::from_utf8_lossy(&buf[..strkey.len() as usize]).into_owned()
+}
+
+fn standard() -> EscrowOptions {
+    EscrowOptions::new(EscrowKind::Standard)
+}
+
+#[test]
+fn vector_release_milestones() {
+    let mut v = Vector::new(&[6000, 4000], standard());
+    v.step("deposit_funds(1)", |c| c.deposit_funds(&ESCROW_ID));
+    v.step("release_milestone(1, 0)", |c| {
+        c.release_milestone(&ESCROW_ID, &0)
+    });
+    v.step("release_milestone(1, 1)", |c| {
+        c.release_milestone(&ESCROW_ID, &1)
+    });
+    v.step("complete_escrow(1)", |c| c.complete_escrow(&ESCROW_ID));
+    v.check("release_milestones");
+}
+
+#[test]
+fn vector_release_fee_paid_by_depositor() {
+    let mut options = standard();
+    options.fee_payer = FeePayer::Depositor;
+    let mut v = Vector::new(&[6000, 4000], options);
+    v.step("deposit_funds(1)", |c| c.deposit_funds(&ESCROW_ID));
+    v.step("release_milestone(1, 0)", |c| {
+        c.release_milestone(&ESCROW_ID, &0)
+    });
+    v.check("release_fee_paid_by_depositor");
+}
+
+#[test]
+fn vector_confirm_delivery() {
+    let mut v = Vector::new(&[6000, 4000], standard());
+    let buyer = v.depositor.clone();
+    v.step("deposit_funds(1)", |c| c.deposit_funds(&ESCROW_ID));
+    v.step("confirm_delivery(1, 0, depositor)", |c| {
+        c.confirm_delivery(&ESCROW_ID, &0, &buyer)
+    });
+    v.check("confirm_delivery");
+}
+
+#[test]
+fn vector_release_in_tranches() {
+    let mut options = standard();
+    options.release_schedule = ReleaseSchedule::Tranches(2, DAY);
+    let mut v = Vector::new(&[6000, 4000], options);
+    v.step("deposit_funds(1)", |c| c.deposit_funds(&ESCROW_ID));
+    v.step("release_milestone(1, 0)", |c| {
+        c.release_milestone(&ESCROW_ID, &0)
+    });
+    v.advance_time(DAY);
+    v.step("claim_tranches(1, 0)", |c| {
+        c.claim_tranches(&ESCROW_ID, &0);
+    });
+    v.check("release_in_tranches");
+}
+
+#[test]
+fn vector_cancel_refund() {
+    let mut v = Vector::new(&[6000, 4000], standard());
+    v.step("deposit_funds(1)", |c| c.deposit_funds(&ESCROW_ID));
+    v.step("cancel_escrow(1)", |c| c.cancel_escrow(&ESCROW_ID));
+    v.check("cancel_refund");
+}
+
+#[test]
+fn vector_hourly_work_log() {
+    let mut v = Vector::new(&[5000], EscrowOptions::new(EscrowKind::Hourly));
+    let log_hash = BytesN::from_array(&v.env, &[1; 32]);
+    v.step("deposit_funds(1)", |c| c.deposit_funds(&ESCROW_ID));
+    v.step("log_work(1, [1; 32], 10, 100)", |c| {
+        c.log_work(&ESCROW_ID, &log_hash, &10, &100);
+    });
+    v.step("approve_work_log(1, 0)", |c| {
+        c.approve_work_log(&ESCROW_ID, &0)
+    });
+    v.step("cancel_escrow(1)", |c| c.cancel_escrow(&ESCROW_ID));
+    v.check("hourly_work_log");
+}
+
+#[test]
+fn vector_expense_claims() {
+    let mut v = Vector::new(&[6000, 4000], standard());
+    let receipt = BytesN::from_array(&v.env, &[2; 32]);
+    v.step("set_expense_milestone(1, 1)", |c| {
+        c.set_expense_milestone(&ESCROW_ID, &1)
+    });
+    v.step("deposit_funds(1)", |c| c.deposit_funds(&ESCROW_ID));
+    v.step("claim_expense(1, 1, 1500, [2; 32])", |c| {
+        c.claim_expense(&ESCROW_ID, &1, &1500, &receipt);
+    });
+    v.step("approve_expense(1, 1, 0)", |c| {
+        c.approve_expense(&ESCROW_ID, &1, &0)
+    });
+    v.step("close_expenses(1, 1)", |c| c.close_expenses(&ESCROW_ID, &1));
+    v.check("expense_claims");
+}
+
+#[cfg(feature = "disputes")]
+#[test]
+fn vector_dispute_awarded_to_recipient() {
+    let mut v = Vector::new(&[6000, 4000], standard());
+    let recipient = v.recipient.clone();
+    v.step("deposit_funds(1)", |c| c.deposit_funds(&ESCROW_ID));
+    v.step("raise_dispute(1, recipient)", |c| {
+        c.raise_dispute(&ESCROW_ID, &recipient)
+    });
+    v.step("resolve_dispute(1, recipient)", |c| {
+        c.resolve_dispute(&ESCROW_ID, &recipient)
+    });
+    v.check("dispute_awarded_to_recipient");
+}
+
+#[cfg(feature = "disputes")]
+#[test]
+fn vector_dispute_refunded_to_depositor() {
+    let mut v = Vector::new(&[6000, 4000], standard());
+    let depositor = v.depositor.clone();
+    v.step("deposit_funds(1)", |c| c.deposit_funds(&ESCROW_ID));
+    v.step("release_milestone(1, 0)", |c| {
+        c.release_milestone(&ESCROW_ID, &0)
+    });
+    v.step("raise_dispute(1, depositor)", |c| {
+        c.raise_dispute(&ESCROW_ID, &depositor)
+    });
+    v.step("resolve_dispute(1, depositor)", |c| {
+        c.resolve_dispute(&ESCROW_ID, &depositor)
+    });
+    v.check("dispute_refunded_to_depositor");
+}
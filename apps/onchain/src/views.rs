@@ -0,0 +1,244 @@
+use crate::settlement::{paid_now, reserved_fee};
+use crate::{
+    attestation_key, burned_total_key, ensure_cancellable, ensure_disputable, ensure_not_paused,
+    ensure_releasable, external_ref_key, get_address_totals, get_escrow_totals, get_status_counts,
+    get_storage_key, held_release_key, partial_dispute_key, quarantine, quote_fee,
+    recipient_index_key, recipient_index_len, token_decimals, token_unit, tranche_release_key,
+    unclaimed, AddressStats, AuthorizedActions, DisplayAmount, Error, Escrow, EscrowAccounting,
+    EscrowKind, EscrowStatus, EscrowSummary, HeldRelease, MilestoneStatus, SettlementProjection,
+    TrancheRelease, VaultixEscrow, VaultixEscrowClient,
+};
+use soroban_sdk::{contractimpl, Address, BytesN, Env, Vec};
+
+#[cfg(any(test, feature = "testutils"))]
+use crate::__VaultixEscrow_fn_set_registry;
+
+const MAX_PAGE_SIZE: u32 = 50;
+
+#[contractimpl]
+impl VaultixEscrow {
+    /// Resolves an off-chain reference (e.g. a marketplace order ID) to the
+    /// escrow created with it, or to the escrow it was since merged into.
+    pub fn get_escrow_by_ref(env: Env, external_ref: BytesN<32>) -> Result<(u64, Escrow), Error> {
+        let mut escrow_id: u64 = env
+            .storage()
+            .persistent()
+            .get(&external_ref_key(&external_ref))
+            .ok_or(Error::EscrowNotFound)?;
+        while let Some(target_id) = Self::get_merged_into(env.clone(), escrow_id) {
+            escrow_id = target_id;
+        }
+        let escrow = Self::get_escrow(env, escrow_id)?;
+        Ok((escrow_id, escrow))
+    }
+
+    /// Returns `(escrow_id, pending)` pairs for every funded escrow paying
+    /// `recipient`, where `pending` is the amount not yet released. Refund
+    /// guarantees pay their depositor, so they are left out.
+    ///
+    /// Results are paginated over the recipient index: `start` is the offset
+    /// into the index and at most `limit` entries (capped at 50) are scanned.
+    pub fn get_pending_for_recipient(
+        env: Env,
+        recipient: Address,
+        start: u32,
+        limit: u32,
+    ) -> Vec<(u64, i128)> {
+        let end = start
+            .saturating_add(limit.min(MAX_PAGE_SIZE))
+            .min(recipient_index_len(&env, &recipient));
+
+        let mut pending = Vec::new(&env);
+        for slot in start..end {
+            let Some(escrow_id) = env
+                .storage()
+                .persistent()
+                .get(&recipient_index_key(&recipient, slot))
+            else {
+                continue;
+            };
+            let escrow: Option<Escrow> =
+                env.storage().persistent().get(&get_storage_key(escrow_id));
+            if let Some(escrow) = escrow {
+                // Grants can be reassigned, leaving stale index entries behind
+                if escrow.status != EscrowStatus::Active || *escrow.payee() != recipient {
+                    continue;
+                }
+                let outstanding = escrow.total_amount - escrow.total_released;
+                if outstanding > 0 {
+                    pending.push_back((escrow_id, outstanding));
+                }
+            }
+        }
+        pending
+    }
+
+    /// Total amount of `token` burned (or sent to the burn address) from fees
+    pub fn burned_total(env: Env, token: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&burned_total_key(&token))
+            .unwrap_or(0)
+    }
+
+    pub fn get_attestation(env: Env, escrow_id: u64, milestone_index: u32) -> Option<BytesN<32>> {
+        env.storage()
+            .persistent()
+            .get(&attestation_key(escrow_id, milestone_index))
+    }
+
+    /// Reports which of release, cancel, dispute and claim `addr` could
+    /// currently perform on the escrow, running the same checks as each
+    /// entrypoint. `addr` counts when it is one of the signers the escrow's
+    /// auth mode requires, so a multisig co-signer still needs the others.
+    /// Everything is `false` while the contract is paused.
+    pub fn get_authorized_actions(
+        env: Env,
+        escrow_id: u64,
+        addr: Address,
+    ) -> Result<AuthorizedActions, Error> {
+        let escrow = Self::get_escrow(env.clone(), escrow_id)?;
+        let mut actions = AuthorizedActions {
+            release: false,
+            cancel: false,
+            dispute: false,
+            claim: false,
+        };
+        if ensure_not_paused(&env).is_err() {
+            return Ok(actions);
+        }
+
+        let for_depositor = escrow.signs_for(&addr, &escrow.depositor);
+        let for_recipient = escrow.signs_for(&addr, &escrow.recipient);
+        let funded = escrow.status == EscrowStatus::Active;
+
+        actions.release = escrow.signs_for(&addr, escrow.releaser())
+            && (0..escrow.milestones.len())
+                .any(|i| ensure_releasable(&env, escrow_id, &escrow, i).is_ok());
+        // Withdrawing a funded guarantee also needs the buyer's signature
+        actions.cancel = for_depositor
+            && ensure_cancellable(&env, escrow_id, &escrow).is_ok()
+            && !(funded && escrow.kind == EscrowKind::RefundGuarantee);
+        let disputes_as = |party: &Address| {
+            escrow.signs_for(&addr, party) && ensure_disputable(&escrow, party).is_ok()
+        };
+        actions.dispute = cfg!(feature = "disputes")
+            && (disputes_as(&escrow.depositor) || disputes_as(&escrow.recipient));
+        actions.claim = funded
+            && for_recipient
+            && escrow.kind == EscrowKind::RefundGuarantee
+            && env.ledger().timestamp() > escrow.deadline;
+
+        Ok(actions)
+    }
+
+    /// Escrow totals in whole tokens plus remainder, using the token's
+    /// decimals (fetched and cached on first use)
+    pub fn get_escrow_summary(env: Env, escrow_id: u64) -> Result<EscrowSummary, Error> {
+        let escrow = Self::get_escrow(env.clone(), escrow_id)?;
+        let unit = token_unit(&env, &escrow.token_address)?;
+        let display = |amount: i128| DisplayAmount {
+            units: amount / unit,
+            fraction: amount % unit,
+        };
+
+        Ok(EscrowSummary {
+            status: escrow.status,
+            decimals: token_decimals(&env, &escrow.token_address),
+            total: display(escrow.total_amount),
+            released: display(escrow.total_released),
+            remaining: display(escrow.total_amount - escrow.total_released),
+            token_address: escrow.token_address,
+        })
+    }
+
+    /// Dry run of settling each milestone now: what a release would pay out,
+    /// withhold as fee and defer into tranches, against what a refund would
+    /// return to the depositor. Nothing is written, and the depeg guard is
+    /// not consulted.
+    pub fn project_settlement(
+        env: Env,
+        escrow_id: u64,
+    ) -> Result<Vec<SettlementProjection>, Error> {
+        let escrow = Self::get_escrow(env.clone(), escrow_id)?;
+        let funded = matches!(escrow.status, EscrowStatus::Active | EscrowStatus::Disputed);
+
+        let mut projections = Vec::new(&env);
+        for (i, milestone) in escrow.milestones.iter().enumerate() {
+            let index = i as u32;
+            let disputed: Option<i128> = env
+                .storage()
+                .persistent()
+                .get(&partial_dispute_key(escrow_id, index));
+            let refund_amount = match milestone.status {
+                _ if !funded => 0,
+                MilestoneStatus::Released | MilestoneStatus::SettledOffChain => 0,
+                // Only the disputed slice is left once the remainder is released
+                MilestoneStatus::Disputed => disputed.unwrap_or(milestone.amount),
+                MilestoneStatus::Pending | MilestoneStatus::Held => milestone.amount,
+            };
+
+            let release_amount =
+                ensure_releasable(&env, escrow_id, &escrow, index).map_or(0, |(amount, _)| amount);
+            let release_fee = if release_amount > 0 {
+                quote_fee(&env, &escrow, &milestone, release_amount, escrow.fee_bps)?
+            } else {
+                0
+            };
+            let release_payout =
+                release_amount - release_fee + reserved_fee(&env, escrow_id, release_fee);
+
+            projections.push_back(SettlementProjection {
+                milestone_index: index,
+                status: milestone.status,
+                releasable: release_amount > 0,
+                release_amount,
+                release_fee,
+                release_payout,
+                release_paid_now: paid_now(&env, escrow_id, release_payout),
+                refund_amount,
+            });
+        }
+        Ok(projections)
+    }
+
+    /// Breakdown of an escrow's funds: what was deposited, paid out, taken
+    /// as fees and refunded, plus releases and refunds still waiting to be
+    /// delivered.
+    pub fn get_escrow_accounting(env: Env, escrow_id: u64) -> Result<EscrowAccounting, Error> {
+        let escrow = Self::get_escrow(env.clone(), escrow_id)?;
+        let totals = get_escrow_totals(&env, escrow_id);
+
+        let storage = env.storage().persistent();
+        let mut outstanding = 0i128;
+        for (i, milestone) in escrow.milestones.iter().enumerate() {
+            let i = i as u32;
+            if milestone.status == MilestoneStatus::Held {
+                let held: Option<HeldRelease> = storage.get(&held_release_key(escrow_id, i));
+                outstanding += held.map_or(0, |h| h.amount);
+            }
+            let tranches: Option<TrancheRelease> = storage.get(&tranche_release_key(escrow_id, i));
+            outstanding += tranches.map_or(0, |t| t.remaining);
+        }
+        outstanding += unclaimed::unclaimed_total(&env, escrow_id);
+        outstanding += quarantine::quarantined_refunds(&env, escrow_id);
+
+        Ok(EscrowAccounting {
+            deposited: totals.deposited,
+            released_to_recipient: totals.received,
+            fees_paid: totals.fees_paid,
+            refunded_to_depositor: totals.refunded,
+            claimable_outstanding: outstanding,
+        })
+    }
+
+    /// Money moved by `addr` across all escrows, plus how many escrows it is
+    /// currently party to in each status. Totals are raw token units summed
+    /// over every token.
+    pub fn get_address_stats(env: Env, addr: Address) -> AddressStats {
+        AddressStats {
+            totals: get_address_totals(&env, &addr),
+            counts: get_status_counts(&env, &addr),
+        }
+    }
+}